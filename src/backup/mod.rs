@@ -0,0 +1,286 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::utils::file::{atomic_write, FileLock};
+
+/// Backups kept per file before the oldest are deleted, unless
+/// `backup_retention` under `[settings]` says otherwise.
+pub const DEFAULT_RETENTION: usize = 10;
+
+#[derive(Debug)]
+pub struct BackupError(pub String);
+
+impl std::fmt::Display for BackupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for BackupError {}
+
+impl From<std::io::Error> for BackupError {
+    fn from(err: std::io::Error) -> Self {
+        BackupError(format!("IO Error: {}", err))
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum BackupKind {
+    /// The guse profiles file.
+    Profiles,
+    /// `~/.ssh/config` or a file it includes.
+    SshConfig,
+    /// `~/.gitconfig`.
+    GitConfig,
+}
+
+impl std::fmt::Display for BackupKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BackupKind::Profiles => write!(f, "profiles"),
+            BackupKind::SshConfig => write!(f, "ssh-config"),
+            BackupKind::GitConfig => write!(f, "gitconfig"),
+        }
+    }
+}
+
+/// Sidecar stored next to each backup as `<id>.toml`.
+#[derive(Deserialize, Serialize, Debug)]
+struct Metadata {
+    kind: BackupKind,
+    source: PathBuf,
+    created: DateTime<Local>,
+}
+
+/// One saved copy of a file, taken right before guse overwrote it.
+#[derive(Debug, Clone)]
+pub struct Backup {
+    pub id: String,
+    pub kind: BackupKind,
+    /// The file the copy was taken from, and where a restore writes to.
+    pub source: PathBuf,
+    pub created: DateTime<Local>,
+    path: PathBuf,
+}
+
+impl Backup {
+    pub fn read(&self) -> Result<String, BackupError> {
+        fs::read_to_string(&self.path)
+            .map_err(|e| BackupError(format!("Cannot read backup '{}': {}", self.id, e)))
+    }
+}
+
+/// Directory of backups shared by every file guse edits.
+#[derive(Debug, Clone)]
+pub struct BackupStore {
+    pub dir: PathBuf,
+    pub retention: usize,
+}
+
+impl BackupStore {
+    /// The store in the user's data directory, e.g. `~/.local/share/guse/backups`.
+    pub fn new() -> Self {
+        let dir = dirs::data_local_dir()
+            .map(|d| d.join("guse"))
+            .or_else(|| dirs::home_dir().map(|h| h.join(".guse")))
+            .unwrap_or_else(|| PathBuf::from(".guse"))
+            .join("backups");
+        Self::at(dir)
+    }
+
+    pub fn at(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            retention: DEFAULT_RETENTION,
+        }
+    }
+
+    /// Copies `source` into the store and drops its oldest backups beyond the
+    /// retention limit. Returns `None` when `source` does not exist yet.
+    pub fn create(&self, kind: BackupKind, source: &Path) -> Result<Option<Backup>, BackupError> {
+        if !source.exists() {
+            return Ok(None);
+        }
+        let source = fs::canonicalize(source)?;
+        let backup = self.store(kind, &source, &source, Local::now())?;
+        self.prune(&backup.source)?;
+        Ok(Some(backup))
+    }
+
+    /// Moves the copies older releases left next to `source` into the store:
+    /// `<file>.<unix time>` for the profiles file and `<file>.config.bak`
+    /// for SSH config. Returns the backups created from them.
+    pub fn import_legacy(
+        &self,
+        kind: BackupKind,
+        source: &Path,
+    ) -> Result<Vec<Backup>, BackupError> {
+        let (Some(dir), Some(name)) = (source.parent(), source.file_name()) else {
+            return Ok(Vec::new());
+        };
+        let Ok(entries) = fs::read_dir(dir) else {
+            return Ok(Vec::new());
+        };
+        let name = name.to_string_lossy();
+        let ssh_backup = source.with_extension("config.bak");
+
+        let mut legacy: Vec<(PathBuf, DateTime<Local>)> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter_map(|path| {
+                let created = match kind {
+                    BackupKind::Profiles => {
+                        let file_name = path.file_name()?.to_string_lossy().to_string();
+                        let seconds: i64 = file_name
+                            .strip_prefix(name.as_ref())?
+                            .strip_prefix('.')?
+                            .parse()
+                            .ok()?;
+                        DateTime::from_timestamp(seconds, 0)?.with_timezone(&Local)
+                    }
+                    BackupKind::SshConfig if path == ssh_backup => {
+                        DateTime::from(fs::metadata(&path).ok()?.modified().ok()?)
+                    }
+                    BackupKind::SshConfig | BackupKind::GitConfig => return None,
+                };
+                Some((path, created))
+            })
+            .collect();
+        if legacy.is_empty() {
+            return Ok(Vec::new());
+        }
+        legacy.sort_by_key(|(_, created)| *created);
+
+        let source = fs::canonicalize(source).unwrap_or_else(|_| source.to_path_buf());
+        let mut imported = Vec::new();
+        for (path, created) in legacy {
+            imported.push(self.store(kind, &source, &path, created)?);
+            fs::remove_file(&path)?;
+        }
+        self.prune(&source)?;
+        Ok(imported)
+    }
+    /// All backups, newest first. Entries with unreadable metadata are skipped.
+    pub fn list(&self) -> Result<Vec<Backup>, BackupError> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Ok(Vec::new());
+        };
+
+        let mut backups: Vec<Backup> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|e| e == "toml"))
+            .filter_map(|p| {
+                let id = p.file_stem()?.to_string_lossy().to_string();
+                let metadata: Metadata = toml::from_str(&fs::read_to_string(&p).ok()?).ok()?;
+                let path = self.data_path(&id);
+                path.is_file().then_some(Backup {
+                    id,
+                    kind: metadata.kind,
+                    source: metadata.source,
+                    created: metadata.created,
+                    path,
+                })
+            })
+            .collect();
+        backups.sort_by(|a, b| b.created.cmp(&a.created).then(b.id.cmp(&a.id)));
+        Ok(backups)
+    }
+
+    /// Looks up a backup by id or by an unambiguous id prefix.
+    pub fn find(&self, id: &str) -> Result<Backup, BackupError> {
+        let backups = self.list()?;
+        if let Some(backup) = backups.iter().find(|b| b.id == id) {
+            return Ok(backup.clone());
+        }
+
+        let mut matches = backups.into_iter().filter(|b| b.id.starts_with(id));
+        match (matches.next(), matches.next()) {
+            (Some(backup), None) => Ok(backup),
+            (Some(_), Some(_)) => Err(BackupError(format!(
+                "Backup id '{}' is ambiguous; use more characters",
+                id
+            ))),
+            (None, _) => Err(BackupError(format!("Backup '{}' not found", id))),
+        }
+    }
+
+    /// Writes `backup` back to its source. The current file is backed up
+    /// first, so a restore can itself be undone.
+    pub fn restore(&self, backup: &Backup) -> Result<(), BackupError> {
+        let contents = backup.read()?;
+        // The profiles file is shared with concurrent guse processes.
+        let _lock = match backup.kind {
+            BackupKind::Profiles => Some(FileLock::acquire(&backup.source)?),
+            BackupKind::SshConfig | BackupKind::GitConfig => None,
+        };
+        self.create(backup.kind, &backup.source)?;
+        atomic_write(&backup.source, contents)?;
+        Ok(())
+    }
+
+    /// Saves the contents of `from` as a backup of `source` taken at `created`.
+    fn store(
+        &self,
+        kind: BackupKind,
+        source: &Path,
+        from: &Path,
+        created: DateTime<Local>,
+    ) -> Result<Backup, BackupError> {
+        fs::create_dir_all(&self.dir)?;
+
+        let base = created.format("%Y%m%d-%H%M%S-%3f").to_string();
+        let mut id = base.clone();
+        let mut n = 1;
+        while self.data_path(&id).exists() || self.metadata_path(&id).exists() {
+            id = format!("{}-{}", base, n);
+            n += 1;
+        }
+
+        let backup = Backup {
+            path: self.data_path(&id),
+            id,
+            kind,
+            source: source.to_path_buf(),
+            created,
+        };
+        fs::copy(from, &backup.path)
+            .map_err(|e| BackupError(format!("Failed to back up {}: {}", from.display(), e)))?;
+        let metadata = Metadata {
+            kind,
+            source: backup.source.clone(),
+            created,
+        };
+        let metadata = toml::to_string(&metadata)
+            .map_err(|e| BackupError(format!("Cannot write backup metadata: {}", e)))?;
+        fs::write(self.metadata_path(&backup.id), metadata)?;
+        Ok(backup)
+    }
+
+    fn prune(&self, source: &Path) -> Result<(), BackupError> {
+        let stale = self
+            .list()?
+            .into_iter()
+            .filter(|b| b.source == source)
+            .skip(self.retention);
+        for backup in stale {
+            fs::remove_file(&backup.path)?;
+            fs::remove_file(self.metadata_path(&backup.id))?;
+        }
+        Ok(())
+    }
+
+    fn data_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.bak", id))
+    }
+
+    fn metadata_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.toml", id))
+    }
+}
+
+#[cfg(test)]
+mod tests;
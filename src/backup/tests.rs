@@ -0,0 +1,124 @@
+use crate::backup::{BackupKind, BackupStore};
+use std::fs;
+
+#[test]
+fn test_create_skips_missing_source() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = BackupStore::at(dir.path().join("backups"));
+    let backup = store
+        .create(BackupKind::Profiles, &dir.path().join("missing.toml"))
+        .unwrap();
+    assert!(backup.is_none());
+    assert!(store.list().unwrap().is_empty());
+}
+
+#[test]
+fn test_retention_keeps_newest() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("profiles.toml");
+    let other = dir.path().join("config");
+    fs::write(&other, "Host a\n").unwrap();
+
+    let mut store = BackupStore::at(dir.path().join("backups"));
+    store.retention = 3;
+    store.create(BackupKind::SshConfig, &other).unwrap();
+    for i in 0..5 {
+        fs::write(&source, format!("# version {}\n", i)).unwrap();
+        store.create(BackupKind::Profiles, &source).unwrap();
+    }
+
+    let backups = store.list().unwrap();
+    assert_eq!(backups.len(), 4);
+    // Newest first; the other file's backup is not pruned.
+    assert_eq!(backups[0].read().unwrap(), "# version 4\n");
+    assert_eq!(backups[2].read().unwrap(), "# version 2\n");
+    assert_eq!(backups[3].kind, BackupKind::SshConfig);
+    // Sidecar files of pruned backups are gone too.
+    assert_eq!(fs::read_dir(&store.dir).unwrap().count(), 8);
+}
+
+#[test]
+fn test_find_by_prefix() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("profiles.toml");
+    fs::write(&source, "").unwrap();
+    let store = BackupStore::at(dir.path().join("backups"));
+    let first = store
+        .create(BackupKind::Profiles, &source)
+        .unwrap()
+        .unwrap();
+    let second = store
+        .create(BackupKind::Profiles, &source)
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(store.find(&first.id).unwrap().id, first.id);
+    assert_eq!(store.find(&second.id).unwrap().id, second.id);
+    assert!(store.find(&first.id[..8]).is_err());
+    assert!(store.find("19700101").is_err());
+}
+
+#[test]
+fn test_restore_backs_up_current_version() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("profiles.toml");
+    fs::write(&source, "[old]\n").unwrap();
+    let store = BackupStore::at(dir.path().join("backups"));
+    let backup = store
+        .create(BackupKind::Profiles, &source)
+        .unwrap()
+        .unwrap();
+
+    fs::write(&source, "[new]\n").unwrap();
+    store.restore(&backup).unwrap();
+
+    assert_eq!(fs::read_to_string(&source).unwrap(), "[old]\n");
+    let backups = store.list().unwrap();
+    assert_eq!(backups.len(), 2);
+    assert_eq!(backups[0].read().unwrap(), "[new]\n");
+}
+
+#[test]
+fn test_import_legacy() {
+    let dir = tempfile::tempdir().unwrap();
+    let profiles = dir.path().join(".guse-profiles.toml");
+    let ssh_config = dir.path().join("config");
+    fs::write(&profiles, "[new]\n").unwrap();
+    fs::write(dir.path().join(".guse-profiles.toml.1700000000"), "[old]\n").unwrap();
+    fs::write(
+        dir.path().join(".guse-profiles.toml.1600000000"),
+        "[older]\n",
+    )
+    .unwrap();
+    fs::write(dir.path().join(".guse-profiles.toml.lock"), "").unwrap();
+    fs::write(dir.path().join("config.config.bak"), "Host a\n").unwrap();
+    let store = BackupStore::at(dir.path().join("backups"));
+
+    let imported = store
+        .import_legacy(BackupKind::Profiles, &profiles)
+        .unwrap();
+    assert_eq!(imported.len(), 2);
+    let imported = store
+        .import_legacy(BackupKind::SshConfig, &ssh_config)
+        .unwrap();
+    assert_eq!(imported.len(), 1);
+    assert_eq!(imported[0].source, ssh_config);
+
+    let backups = store.list().unwrap();
+    assert_eq!(backups.len(), 3);
+    let old: Vec<_> = backups
+        .iter()
+        .filter(|b| b.kind == BackupKind::Profiles)
+        .map(|b| b.read().unwrap())
+        .collect();
+    assert_eq!(old, ["[old]\n", "[older]\n"]);
+    assert_eq!(backups[2].created.timestamp(), 1600000000);
+
+    // The old copies are moved, so a second run finds nothing.
+    assert!(!dir.path().join("config.config.bak").exists());
+    assert!(dir.path().join(".guse-profiles.toml.lock").exists());
+    assert!(store
+        .import_legacy(BackupKind::Profiles, &profiles)
+        .unwrap()
+        .is_empty());
+}
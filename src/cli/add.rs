@@ -1,9 +1,10 @@
 use clap::Parser;
 use colored::*;
 
-use crate::config::Config;
+use crate::config::{Config, SwitchStrategy, Transport};
 use crate::error::GuseError;
-use crate::utils::{backup_config_file, get_ssh_config_path, validate_email, validate_ssh_host};
+use crate::ssh::SshConfig;
+use crate::utils::{validate_email, validate_ssh_host};
 
 #[derive(Parser, Debug)]
 #[command(about = "Add a new Git profile")]
@@ -11,13 +12,43 @@ pub struct AddCommand {
     /// Name of the profile to add
     #[arg(help = "Name of the profile to add (e.g., personal, work)")]
     pub profile: String,
+
+    #[arg(long, value_enum, help = "How `guse switch` selects the SSH key")]
+    pub strategy: Option<SwitchStrategy>,
+
+    #[arg(
+        long = "url-pattern",
+        value_name = "PATTERN",
+        help = "Extra remote URL pattern for `guse url`, e.g. 'https://github.com/my-org/**' (repeatable)"
+    )]
+    pub url_patterns: Vec<String>,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "Transport of the profile's remotes (default: ssh)"
+    )]
+    pub transport: Option<Transport>,
+
+    #[arg(
+        long,
+        value_name = "USER",
+        help = "Login to embed in HTTPS remote URLs"
+    )]
+    pub https_user: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "HOST",
+        help = "Host for HTTPS remotes, when it is not the remote's own (e.g. github.com for remotes on an SSH alias)"
+    )]
+    pub https_host: Option<String>,
 }
 
 impl AddCommand {
     pub fn execute(&self, config: &Config) -> Result<(), GuseError> {
         use dialoguer::Input;
         use log::info;
-        use std::fs;
 
         info!("Starting new profile addition: {}", self.profile);
         println!(
@@ -37,54 +68,7 @@ impl AddCommand {
             .interact_text()?;
 
         // Get SSH host list
-        let ssh_config = get_ssh_config_path()?;
-
-        #[derive(Clone)]
-        struct SshHost {
-            name: String,
-            hostname: String,
-            user: String,
-            port: String,
-        }
-
-        let mut hosts = Vec::new();
-        let mut current_host: Option<SshHost> = None;
-
-        if let Ok(content) = fs::read_to_string(&ssh_config) {
-            for line in content.lines() {
-                let line = line.trim();
-
-                if line.starts_with("Host ") {
-                    // Save previous host info if exists
-                    if let Some(host) = current_host.take() {
-                        hosts.push(host);
-                    }
-
-                    // Start new host
-                    let name = line.split_whitespace().nth(1).unwrap_or("").to_string();
-                    current_host = Some(SshHost {
-                        name,
-                        hostname: String::new(),
-                        user: String::new(),
-                        port: String::new(),
-                    });
-                } else if let Some(ref mut host) = current_host {
-                    // Parse host info
-                    if line.starts_with("HostName ") {
-                        host.hostname = line.split_whitespace().nth(1).unwrap_or("").to_string();
-                    } else if line.starts_with("User ") {
-                        host.user = line.split_whitespace().nth(1).unwrap_or("").to_string();
-                    } else if line.starts_with("Port ") {
-                        host.port = line.split_whitespace().nth(1).unwrap_or("").to_string();
-                    }
-                }
-            }
-
-            // Save last host info
-            if let Some(host) = current_host {
-                hosts.push(host);
-            }
-        }
+        let hosts = SshConfig::load()?.hosts();
 
         // Select SSH host
         let ssh_host = if hosts.is_empty() {
@@ -95,25 +79,9 @@ impl AddCommand {
             use dialoguer::Select;
 
             // Convert host info to display format
-            let host_items: Vec<String> = hosts
-                .iter()
-                .map(|host| {
-                    let mut info = format!("{}", host.name);
-                    if !host.hostname.is_empty() {
-                        info.push_str(&format!(" ({})", host.hostname));
-                    }
-                    if !host.user.is_empty() {
-                        info.push_str(&format!(" - User: {}", host.user));
-                    }
-                    if !host.port.is_empty() {
-                        info.push_str(&format!(" - Port: {}", host.port));
-                    }
-                    info
-                })
-                .collect();
+            let mut items: Vec<String> = hosts.iter().map(|host| host.label()).collect();
 
             // Add "Manual Input" option
-            let mut items = host_items.clone();
             items.push("Manual Input".to_string());
 
             let selection = Select::new()
@@ -128,20 +96,42 @@ impl AddCommand {
                     .with_prompt("Enter SSH Host")
                     .interact_text()?
             } else {
-                hosts[selection].name.clone()
+                hosts[selection].alias.clone()
+            }
+        };
+
+        let strategy = match self.strategy {
+            Some(strategy) => strategy,
+            None => {
+                let items: Vec<_> = SwitchStrategy::ALL
+                    .iter()
+                    .map(|s| format!("{} - {}", s, s.description()))
+                    .collect();
+                let default_index = SwitchStrategy::ALL
+                    .iter()
+                    .position(|s| *s == SwitchStrategy::default())
+                    .unwrap_or(0);
+                let selection = dialoguer::Select::new()
+                    .with_prompt("Switch strategy")
+                    .items(&items)
+                    .default(default_index)
+                    .interact()?;
+                SwitchStrategy::ALL[selection]
             }
         };
 
         validate_email(&email)?;
         validate_ssh_host(&ssh_host)?;
 
-        // Backup configuration file
-        backup_config_file(&config.path)?;
-
         let profile = crate::config::Profile {
             name,
             email,
             ssh_host,
+            strategy,
+            url_patterns: self.url_patterns.clone(),
+            transport: self.transport.unwrap_or_default(),
+            https_user: self.https_user.clone(),
+            https_host: self.https_host.clone(),
         };
 
         config.add_profile(self.profile.clone(), profile)?;
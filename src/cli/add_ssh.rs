@@ -1,17 +1,46 @@
 use clap::Parser;
 use colored::*;
-use std::fs::OpenOptions;
-use std::io::Write;
 
+use crate::config::Config;
 use crate::error::GuseError;
+use crate::ssh::edit::SshConfigDocument;
+use crate::ssh::keygen::{self, KeyAlgorithm, KeyGenOptions};
+use crate::ssh::SshConfig;
 use crate::utils::get_ssh_config_path;
 
 #[derive(Parser, Debug)]
 #[command(about = "Add a new SSH host to ~/.ssh/config")]
-pub struct AddSshCommand;
+pub struct AddSshCommand {
+    #[arg(long, value_enum, help = "Algorithm for a newly generated key")]
+    pub key_type: Option<KeyAlgorithm>,
+
+    #[arg(
+        long,
+        help = "Key size in bits for a newly generated key (RSA and ECDSA only)"
+    )]
+    pub bits: Option<u32>,
+
+    #[arg(long, help = "Comment stored in a newly generated key")]
+    pub comment: Option<String>,
+
+    #[arg(
+        long,
+        help = "Profile that will use this host; its email becomes the default key comment"
+    )]
+    pub profile: Option<String>,
+
+    #[arg(long, help = "Generate the key without asking for a passphrase")]
+    pub no_passphrase: bool,
+
+    #[arg(
+        long,
+        help = "Generate the key with the external ssh-keygen binary instead of the built-in generator"
+    )]
+    pub ssh_keygen: bool,
+}
 
 impl AddSshCommand {
-    pub fn execute(&self) -> Result<(), GuseError> {
+    pub fn execute(&self, config: &Config) -> Result<(), GuseError> {
         use dialoguer::Input;
 
 
@@ -20,8 +49,16 @@ impl AddSshCommand {
         println!("\n{}", "🔑 Add SSH Host".cyan().bold());
         println!("{}", "=".repeat(40).cyan());
 
+        let existing = SshConfig::load()?.hosts();
         let host: String = Input::new()
             .with_prompt("Host alias (e.g., myserver)")
+            .validate_with(|input: &String| -> Result<(), String> {
+                if existing.iter().any(|h| &h.alias == input) {
+                    Err(format!("Host '{}' already exists", input))
+                } else {
+                    Ok(())
+                }
+            })
             .interact_text()?;
         let hostname: String = Input::new()
             .with_prompt("HostName (e.g., 192.168.0.1 or github.com)")
@@ -67,29 +104,9 @@ impl AddSshCommand {
                     .interact_text()?
             } else if identity_files[selection] == "Generate new key" {
                 // Use host alias as id_ prefix
-                let ssh_dir = shellexpand::tilde("~/.ssh").to_string();
-                let new_key_path = format!("{}/id_{}", ssh_dir, host);
+                let new_key_path = format!("~/.ssh/id_{}", host);
                 let expanded_new_key_path = shellexpand::tilde(&new_key_path).to_string();
-                if !std::path::Path::new(&expanded_new_key_path).exists() {
-                    println!("{} Generating SSH key...", "🔑".yellow());
-                    let output = std::process::Command::new("ssh-keygen")
-                        .arg("-t").arg("rsa")
-                        .arg("-b").arg("4096")
-                        .arg("-f").arg(&expanded_new_key_path)
-                        .arg("-N").arg("")
-                        .output();
-                    match output {
-                        Ok(out) if out.status.success() => {
-                            println!("{} SSH key generated: {}", "✅".green(), expanded_new_key_path);
-                        }
-                        Ok(out) => {
-                            eprintln!("{} ssh-keygen failed: {}", "❌".red(), String::from_utf8_lossy(&out.stderr));
-                        }
-                        Err(e) => {
-                            eprintln!("{} ssh-keygen error: {}", "❌".red(), e);
-                        }
-                    }
-                } else {
+                if std::path::Path::new(&expanded_new_key_path).exists() {
                     println!("{} Key already exists at: {}", "⚠️".yellow(), expanded_new_key_path);
                 }
                 new_key_path
@@ -103,41 +120,113 @@ impl AddSshCommand {
                 .interact_text()?
         };
 
-        // If SSH key does not exist, generate automatically
+        // If SSH key does not exist, generate it before touching the config
         let expanded_identity_file = shellexpand::tilde(&identity_file).to_string();
         if !std::path::Path::new(&expanded_identity_file).exists() {
-            println!("{} SSH key does not exist. Generating automatically...", "🔑".yellow());
-            let output = std::process::Command::new("ssh-keygen")
-                .arg("-t").arg("rsa")
-                .arg("-b").arg("4096")
-                .arg("-f").arg(&expanded_identity_file)
-                .arg("-N").arg("")
-                .output();
-            match output {
-                Ok(out) if out.status.success() => {
-                    println!("{} SSH key generated: {}", "✅".green(), expanded_identity_file);
-                }
-                Ok(out) => {
-                    eprintln!("{} ssh-keygen failed: {}", "❌".red(), String::from_utf8_lossy(&out.stderr));
-                }
-                Err(e) => {
-                    eprintln!("{} ssh-keygen error: {}", "❌".red(), e);
-                }
-            }
+            let options = self.key_options(config)?;
+            println!(
+                "{} Generating {} SSH key...",
+                "🔑".yellow(),
+                options.algorithm
+            );
+            keygen::generate(std::path::Path::new(&expanded_identity_file), &options)?;
+            println!(
+                "{} SSH key generated: {}",
+                "✅".green(),
+                expanded_identity_file
+            );
         }
 
-        let entry = format!(
-            "\nHost {}\n    HostName {}\n    User {}\n    Port {}\n    IdentityFile {}\n",
-            host, hostname, user, port, identity_file
-        );
+        let mut document = SshConfigDocument::load(&ssh_config)?;
+        document.insert_host(
+            &host,
+            &[
+                ("HostName", &hostname),
+                ("User", &user),
+                ("Port", &port),
+                ("IdentityFile", &identity_file),
+            ],
+        )?;
 
-        let mut file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&ssh_config)?;
-        file.write_all(entry.as_bytes())?;
+        document.save(&config.backup_store())?;
 
         println!("\n{} SSH host added to {}!", "✅".green(), ssh_config.display());
+        println!(
+            "{} Run `guse ssh pubkey {}` to print the public key to register with your Git provider.",
+            "💡".yellow(),
+            host
+        );
         Ok(())
     }
+
+    /// Collects key generation settings from flags, prompting for the rest.
+    fn key_options(&self, config: &Config) -> Result<KeyGenOptions, GuseError> {
+        use dialoguer::{Input, Password, Select};
+
+        let algorithm = match self.key_type {
+            Some(algorithm) => algorithm,
+            None => {
+                let selection = Select::new()
+                    .with_prompt("Key algorithm")
+                    .items(&KeyAlgorithm::ALL)
+                    .default(0)
+                    .interact()?;
+                KeyAlgorithm::ALL[selection]
+            }
+        };
+
+        let bits = match (self.bits, algorithm.default_bits()) {
+            (Some(bits), _) => Some(bits),
+            (None, Some(default)) => Some(
+                Input::<u32>::new()
+                    .with_prompt("Key size (bits)")
+                    .default(default)
+                    .interact_text()?,
+            ),
+            (None, None) => None,
+        };
+        if let Some(bits) = bits {
+            algorithm.validate_bits(bits)?;
+        }
+
+        let comment = match &self.comment {
+            Some(comment) => comment.clone(),
+            None => {
+                let default = match &self.profile {
+                    Some(name) => config
+                        .load_profiles()?
+                        .get(name)
+                        .ok_or_else(|| {
+                            GuseError::ConfigError(format!("Profile '{}' does not exist.", name))
+                        })?
+                        .email
+                        .clone(),
+                    None => String::new(),
+                };
+                Input::new()
+                    .with_prompt("Key comment")
+                    .default(default)
+                    .allow_empty(true)
+                    .interact_text()?
+            }
+        };
+
+        let passphrase = if self.no_passphrase {
+            String::new()
+        } else {
+            Password::new()
+                .with_prompt("Key passphrase (empty for none)")
+                .with_confirmation("Confirm passphrase", "Passphrases do not match")
+                .allow_empty_password(true)
+                .interact()?
+        };
+
+        Ok(KeyGenOptions {
+            algorithm,
+            bits,
+            passphrase,
+            comment,
+            use_ssh_keygen: self.ssh_keygen,
+        })
+    }
 }
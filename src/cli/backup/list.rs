@@ -0,0 +1,75 @@
+use clap::Parser;
+use colored::*;
+use prettytable::*;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::backup::{BackupKind, BackupStore};
+use crate::config::Config;
+use crate::error::GuseError;
+
+use super::display_path;
+
+#[derive(Parser, Debug)]
+#[command(about = "Show saved backups, newest first")]
+pub struct ListCommand;
+
+impl ListCommand {
+    pub fn execute(&self, store: &BackupStore) -> Result<(), GuseError> {
+        let backups = store.list()?;
+        if backups.is_empty() {
+            println!("{}", "❌ No backups found.".red().bold());
+            return Ok(());
+        }
+
+        println!("\n{}", "🗄️  Backups:".cyan().bold());
+        println!("{}", "=".repeat(40).cyan());
+
+        // Profile counts of the files as they are now, to compare against.
+        let mut current: HashMap<PathBuf, usize> = HashMap::new();
+
+        let mut table = Table::new();
+        table.add_row(row!["ID", "Created", "Type", "File", "Profiles"]);
+        for backup in &backups {
+            let profiles = match backup.kind {
+                BackupKind::Profiles => {
+                    let now = *current
+                        .entry(backup.source.clone())
+                        .or_insert_with(|| profile_count(&fs::read_to_string(&backup.source)));
+                    let then = profile_count(&backup.read());
+                    let diff = then as i64 - now as i64;
+                    if diff == 0 {
+                        then.to_string()
+                    } else {
+                        format!("{} ({:+} vs. now)", then, diff)
+                    }
+                }
+                BackupKind::SshConfig | BackupKind::GitConfig => "-".to_string(),
+            };
+            table.add_row(row![
+                backup.id,
+                backup.created.format("%Y-%m-%d %H:%M:%S"),
+                backup.kind,
+                display_path(&backup.source),
+                profiles
+            ]);
+        }
+        table.printstd();
+
+        println!("\n{} {}", "📁".blue(), display_path(&store.dir).dimmed());
+        println!(
+            "{}",
+            "To restore a backup, use: guse backup restore <id>".cyan()
+        );
+        Ok(())
+    }
+}
+
+fn profile_count<E>(contents: &Result<String, E>) -> usize {
+    contents
+        .as_ref()
+        .ok()
+        .and_then(|c| Config::parse_profiles(c).ok())
+        .map_or(0, |profiles| profiles.len())
+}
@@ -0,0 +1,72 @@
+pub mod list;
+pub mod restore;
+
+use clap::Parser;
+use colored::*;
+
+use crate::backup::{BackupKind, BackupStore};
+use crate::config::{Config, LEGACY_CONFIG_FILE};
+use crate::error::GuseError;
+use crate::utils::get_ssh_config_path;
+
+#[derive(Parser, Debug)]
+#[command(about = "List and restore backups of files guse has changed")]
+pub struct BackupCommand {
+    #[command(subcommand)]
+    pub command: BackupCommands,
+}
+
+#[derive(Parser, Debug)]
+pub enum BackupCommands {
+    #[command(name = "list", about = "Show saved backups, newest first")]
+    List(list::ListCommand),
+
+    #[command(name = "restore", about = "Restore a file from a backup")]
+    Restore(restore::RestoreCommand),
+}
+
+impl BackupCommand {
+    pub fn execute(&self, config: &Config) -> Result<(), GuseError> {
+        let store = config.backup_store();
+        import_legacy(config, &store)?;
+        match &self.command {
+            BackupCommands::List(cmd) => cmd.execute(&store),
+            BackupCommands::Restore(cmd) => cmd.execute(&store),
+        }
+    }
+}
+
+/// Moves the copies older releases kept next to the files they changed into
+/// `store`, so that they are listed and restored like the others.
+fn import_legacy(config: &Config, store: &BackupStore) -> Result<(), GuseError> {
+    let mut sources = vec![(BackupKind::Profiles, config.path.clone())];
+    if let Some(home) = dirs::home_dir() {
+        sources.push((BackupKind::Profiles, home.join(LEGACY_CONFIG_FILE)));
+    }
+    sources.push((BackupKind::SshConfig, get_ssh_config_path()?));
+
+    for (kind, source) in sources {
+        let imported = store.import_legacy(kind, &source)?;
+        if !imported.is_empty() {
+            println!(
+                "{} {}",
+                "🗄️".blue(),
+                format!(
+                    "Moved {} backup(s) of {} from an older guse release into the backup store.",
+                    imported.len(),
+                    display_path(&source)
+                )
+                .blue()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Shortens paths under the home directory to `~/...`.
+fn display_path(path: &std::path::Path) -> String {
+    match dirs::home_dir().and_then(|home| path.strip_prefix(home).ok().map(|p| p.to_owned())) {
+        Some(relative) => format!("~/{}", relative.display()),
+        None => path.display().to_string(),
+    }
+}
@@ -0,0 +1,110 @@
+use clap::Parser;
+use colored::*;
+use dialoguer::Confirm;
+use std::fs;
+
+use crate::backup::{Backup, BackupKind, BackupStore};
+use crate::config::Config;
+use crate::error::GuseError;
+
+use super::display_path;
+
+#[derive(Parser, Debug)]
+#[command(about = "Restore a file from a backup")]
+pub struct RestoreCommand {
+    /// Backup to restore
+    #[arg(
+        help = "ID of the backup to restore, as shown by `guse backup list`. A unique prefix is enough."
+    )]
+    pub id: String,
+
+    #[arg(short, long, help = "Do not ask for confirmation")]
+    pub yes: bool,
+}
+
+impl RestoreCommand {
+    pub fn execute(&self, store: &BackupStore) -> Result<(), GuseError> {
+        use log::info;
+
+        let backup = store.find(&self.id)?;
+
+        println!(
+            "\n{}",
+            format!(
+                "🗄️  Backup '{}' from {}",
+                backup.id,
+                backup.created.format("%Y-%m-%d %H:%M:%S")
+            )
+            .cyan()
+            .bold()
+        );
+        println!("{}", "=".repeat(40).cyan());
+        println!("File: {}", display_path(&backup.source));
+        if backup.kind == BackupKind::Profiles {
+            print_profile_changes(&backup)?;
+        }
+
+        if !self.yes
+            && !Confirm::new()
+                .with_prompt(format!(
+                    "Overwrite {} with this backup?",
+                    display_path(&backup.source)
+                ))
+                .default(false)
+                .interact()?
+        {
+            return Ok(());
+        }
+
+        info!(
+            "Restoring backup {} to {}",
+            backup.id,
+            backup.source.display()
+        );
+        store.restore(&backup)?;
+
+        println!(
+            "\n{}",
+            format!("✅ Restored {}", display_path(&backup.source))
+                .green()
+                .bold()
+        );
+        println!(
+            "{}",
+            "The replaced version was backed up as well; see `guse backup list`.".cyan()
+        );
+        Ok(())
+    }
+}
+
+/// Shows which profiles the restore brings back, drops or changes.
+fn print_profile_changes(backup: &Backup) -> Result<(), GuseError> {
+    let restored = Config::parse_profiles(&backup.read()?)?;
+    let current = match fs::read_to_string(&backup.source) {
+        Ok(contents) => Config::parse_profiles(&contents).unwrap_or_default(),
+        Err(_) => Default::default(),
+    };
+
+    let mut names: Vec<&String> = restored.keys().chain(current.keys()).collect();
+    names.sort();
+    names.dedup();
+
+    let mut changed = false;
+    for name in names {
+        let line = match (restored.get(name), current.get(name)) {
+            (Some(_), None) => format!("  + {}", name).green(),
+            (None, Some(_)) => format!("  - {}", name).red(),
+            (Some(a), Some(b)) if a != b => format!("  ~ {}", name).yellow(),
+            _ => continue,
+        };
+        if !changed {
+            println!("Profile changes:");
+            changed = true;
+        }
+        println!("{}", line);
+    }
+    if !changed {
+        println!("{}", "Profiles are identical to the current file.".dimmed());
+    }
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use clap::Parser;
+use colored::*;
+
+use crate::config::Config;
+use crate::error::GuseError;
+
+#[derive(Parser, Debug)]
+#[command(about = "Set the profile pickers start on")]
+pub struct DefaultCommand {
+    /// Profile to use as the default; shows the current one when omitted
+    #[arg(
+        help = "Profile preselected in repositories not switched yet. Shows the current default when omitted."
+    )]
+    pub profile: Option<String>,
+
+    #[arg(long, conflicts_with = "profile", help = "Remove the default profile")]
+    pub clear: bool,
+}
+
+impl DefaultCommand {
+    pub fn execute(&self, config: &Config) -> Result<(), GuseError> {
+        use log::info;
+
+        if self.clear {
+            config.set_default_profile(None)?;
+            info!("Default profile cleared");
+            println!("{}", "✅ Default profile cleared".green().bold());
+            return Ok(());
+        }
+
+        let Some(profile) = &self.profile else {
+            match config.load()?.settings.default_profile {
+                Some(name) => println!(
+                    "{} {}",
+                    "⭐".blue().bold(),
+                    format!("Default profile: '{}'", name).blue()
+                ),
+                None => println!("{}", "No default profile is set.".yellow()),
+            }
+            return Ok(());
+        };
+
+        if !config.load_profiles()?.contains_key(profile) {
+            println!(
+                "{}",
+                format!("❌ Profile '{}' not found.", profile).red().bold()
+            );
+            return Ok(());
+        }
+
+        config.set_default_profile(Some(profile))?;
+        info!("Default profile set: {}", profile);
+        println!(
+            "{}",
+            format!("✅ '{}' is now the default profile", profile)
+                .green()
+                .bold()
+        );
+        Ok(())
+    }
+}
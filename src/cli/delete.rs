@@ -2,9 +2,12 @@ use clap::Parser;
 use colored::*;
 use dialoguer::Select;
 
+use crate::cli::dir::remove_profile_rules;
+use crate::cli::preselect_profile;
 use crate::config::Config;
 use crate::error::GuseError;
-use crate::utils::backup_config_file;
+use crate::git::backend::GitBackend;
+use crate::git::Git;
 
 #[derive(Parser, Debug)]
 #[command(about = "Delete an existing Git profile")]
@@ -18,24 +21,21 @@ pub struct DeleteCommand {
 }
 
 impl DeleteCommand {
-    pub fn execute(&self, config: &Config) -> Result<(), GuseError> {
+    pub fn execute<B: GitBackend>(&self, config: &Config, git: &Git<B>) -> Result<(), GuseError> {
         use log::info;
 
-        let profiles: Vec<_> = config.load_profiles()?.into_iter().collect();
-        if profiles.is_empty() {
+        // Every entry is offered, including templates and profiles with a
+        // broken `extends`, which other commands skip.
+        let file = config.load()?;
+        let settings = file.settings;
+        let mut profile_names: Vec<String> = file.profiles.into_keys().collect();
+        if profile_names.is_empty() {
             println!("{}", "❌ No profiles found.".red().bold());
             return Ok(());
         }
+        settings.sort_names(&mut profile_names);
 
-        let profile_names: Vec<String> = profiles.iter().map(|(name, _)| name.clone()).collect();
-        let selection = if self.profile.is_none() {
-            Select::new()
-                .with_prompt("Select profile to delete")
-                .items(&profile_names)
-                .default(0)
-                .interact()?
-        } else {
-            let profile_name = self.profile.as_ref().unwrap();
+        let selection = if let Some(profile_name) = &self.profile {
             match profile_names.iter().position(|x| x == profile_name) {
                 Some(idx) => idx,
                 None => {
@@ -48,6 +48,12 @@ impl DeleteCommand {
                     return Ok(());
                 }
             }
+        } else {
+            Select::new()
+                .with_prompt("Select profile to delete")
+                .items(&profile_names)
+                .default(preselect_profile(config, &settings, &profile_names, git))
+                .interact()?
         };
 
         let profile_to_delete = &profile_names[selection];
@@ -60,9 +66,35 @@ impl DeleteCommand {
         );
         println!("{}", "=".repeat(40).red());
 
-        backup_config_file(&config.path)?;
         config.delete_profile(profile_to_delete)?;
 
+        // Rules for a deleted profile would keep including its identity
+        match remove_profile_rules(config, profile_to_delete) {
+            Ok(removed) => {
+                for rule in removed {
+                    let target = match (rule.gitdir(), rule.url_pattern()) {
+                        (Some(dir), _) => format!("directory {}", dir),
+                        (_, Some(pattern)) => format!("URL pattern {}", pattern),
+                        _ => rule.condition.clone(),
+                    };
+                    println!(
+                        "{} {}",
+                        "🧹".blue(),
+                        format!("Removed the rule for {}", target).blue()
+                    );
+                }
+            }
+            Err(e) => println!(
+                "{} {}",
+                "⚠️".yellow().bold(),
+                format!(
+                    "Could not remove the directory and URL rules of this profile: {}. See `guse dir list` and `guse url list`.",
+                    e
+                )
+                .yellow()
+            ),
+        }
+
         info!("Profile deletion completed: {}", profile_to_delete);
         println!(
             "\n{}",
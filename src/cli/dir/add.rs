@@ -0,0 +1,90 @@
+use clap::Parser;
+use colored::*;
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::error::GuseError;
+use crate::git::include::{gitdir_condition, IncludeRule};
+
+use super::{absolute_dir, load_global_config, write_fragment};
+
+#[derive(Parser, Debug)]
+#[command(about = "Use a profile for every repository below a directory")]
+pub struct AddCommand {
+    /// Directory whose repositories get the profile
+    #[arg(help = "Directory whose repositories get the profile (e.g., ~/work)")]
+    pub dir: PathBuf,
+
+    /// Name of the profile to use
+    #[arg(help = "Name of the profile to use (e.g., work)")]
+    pub profile: String,
+}
+
+impl AddCommand {
+    pub fn execute(&self, config: &Config) -> Result<(), GuseError> {
+        use log::info;
+
+        let profiles = config.load_profiles()?;
+        let Some(profile) = profiles.get(&self.profile) else {
+            println!(
+                "{}",
+                format!("❌ Profile '{}' not found.", self.profile)
+                    .red()
+                    .bold()
+            );
+            return Ok(());
+        };
+
+        let dir = absolute_dir(&self.dir)?;
+        if !dir.is_dir() {
+            println!(
+                "{} {}",
+                "⚠️".yellow().bold(),
+                format!("{} does not exist yet.", dir.display()).yellow()
+            );
+        }
+
+        let fragment = write_fragment(&self.profile, profile)?;
+        info!("Wrote {}", fragment.display());
+
+        let mut document = load_global_config()?;
+        let condition = gitdir_condition(&dir);
+        let previous = document
+            .rules
+            .iter()
+            .find(|r| r.condition == condition)
+            .and_then(|r| r.profile());
+        document.set_rule(IncludeRule {
+            condition,
+            path: fragment,
+        });
+        document.save(&config.backup_store())?;
+
+        if let Some(previous) = previous.filter(|p| *p != self.profile) {
+            println!(
+                "{} {}",
+                "ℹ️".blue().bold(),
+                format!("Replaced profile '{}' for this directory.", previous).blue()
+            );
+        }
+        println!(
+            "{}",
+            format!(
+                "✅ Repositories below {} now use profile '{}'",
+                dir.display(),
+                self.profile
+            )
+            .green()
+            .bold()
+        );
+        println!(
+            "{}",
+            format!(
+                "Values set with `guse switch` inside a repository still take precedence over {}.",
+                document.path.display()
+            )
+            .dimmed()
+        );
+        Ok(())
+    }
+}
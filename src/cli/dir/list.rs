@@ -0,0 +1,52 @@
+use clap::Parser;
+use colored::*;
+use prettytable::*;
+
+use crate::config::Config;
+use crate::error::GuseError;
+
+use super::load_global_config;
+
+#[derive(Parser, Debug)]
+#[command(about = "Show directories with a profile")]
+pub struct ListCommand;
+
+impl ListCommand {
+    pub fn execute(&self, config: &Config) -> Result<(), GuseError> {
+        let document = load_global_config()?;
+        let rules: Vec<_> = document
+            .rules
+            .iter()
+            .filter_map(|rule| rule.gitdir().map(|dir| (dir, rule)))
+            .collect();
+        if rules.is_empty() {
+            println!("{}", "❌ No directories configured.".red().bold());
+            return Ok(());
+        }
+
+        let profiles = config.load_profiles()?;
+
+        println!("\n{}", "📁 Directories:".cyan().bold());
+        println!("{}", "=".repeat(40).cyan());
+
+        let mut table = Table::new();
+        table.add_row(row!["Directory", "Profile", "Fragment"]);
+        for (dir, rule) in rules {
+            let profile = rule.profile().unwrap_or_default();
+            let profile = if profiles.contains_key(&profile) {
+                profile
+            } else {
+                format!("{} (missing)", profile)
+            };
+            let fragment = if rule.path.is_file() {
+                rule.path.display().to_string()
+            } else {
+                format!("{} (missing)", rule.path.display())
+            };
+            table.add_row(row![dir, profile, fragment]);
+        }
+        table.printstd();
+        println!();
+        Ok(())
+    }
+}
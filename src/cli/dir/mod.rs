@@ -0,0 +1,149 @@
+pub mod add;
+pub mod list;
+pub mod remove;
+
+use clap::Parser;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::{Config, Profile, SwitchStrategy};
+use crate::error::GuseError;
+use crate::git::include::{fragment_file_name, render_fragment, IncludeDocument, IncludeRule};
+use crate::git::{global_config_path, ssh_command};
+use crate::ssh::keys::private_key_path;
+use crate::ssh::SshConfig;
+use crate::utils::file::atomic_write;
+
+#[derive(Parser, Debug)]
+#[command(about = "Give every repository below a directory a profile's identity")]
+pub struct DirCommand {
+    #[command(subcommand)]
+    pub command: DirCommands,
+}
+
+#[derive(Parser, Debug)]
+pub enum DirCommands {
+    #[command(
+        name = "add",
+        about = "Use a profile for every repository below a directory"
+    )]
+    Add(add::AddCommand),
+
+    #[command(name = "list", about = "Show directories with a profile")]
+    List(list::ListCommand),
+
+    #[command(name = "remove", about = "Stop using a profile below a directory")]
+    Remove(remove::RemoveCommand),
+}
+
+impl DirCommand {
+    pub fn execute(&self, config: &Config) -> Result<(), GuseError> {
+        match &self.command {
+            DirCommands::Add(cmd) => cmd.execute(config),
+            DirCommands::List(cmd) => cmd.execute(config),
+            DirCommands::Remove(cmd) => cmd.execute(config),
+        }
+    }
+}
+
+/// Where the per-profile gitconfig fragments live, e.g.
+/// `~/.config/guse/gitconfig`.
+pub fn fragment_dir() -> PathBuf {
+    dirs::config_dir()
+        .map(|d| d.join("guse"))
+        .or_else(|| dirs::home_dir().map(|h| h.join(".guse")))
+        .unwrap_or_else(|| PathBuf::from(".guse"))
+        .join("gitconfig")
+}
+
+pub fn fragment_path(profile_name: &str) -> PathBuf {
+    fragment_dir().join(fragment_file_name(profile_name))
+}
+
+/// Writes the fragment for `profile_name`, pinning its key for profiles
+/// using the ssh-command strategy.
+pub fn write_fragment(profile_name: &str, profile: &Profile) -> Result<PathBuf, GuseError> {
+    if profile_name.contains(['/', '\\']) {
+        return Err(GuseError::ValidationError(format!(
+            "Profile name '{}' cannot be used as a file name",
+            profile_name
+        )));
+    }
+
+    let command = match profile.strategy {
+        SwitchStrategy::SshCommand => {
+            let key = SshConfig::load()?
+                .identity_paths(&profile.ssh_host)
+                .into_iter()
+                .next()
+                .map(|path| private_key_path(&path))
+                .ok_or_else(|| {
+                    GuseError::SshError(format!(
+                        "No IdentityFile found for SSH host '{}'",
+                        profile.ssh_host
+                    ))
+                })?;
+            Some(ssh_command(&key))
+        }
+        SwitchStrategy::Alias => None,
+    };
+
+    let path = fragment_path(profile_name);
+    fs::create_dir_all(fragment_dir())?;
+    atomic_write(
+        &path,
+        render_fragment(&profile.name, &profile.email, command.as_deref()),
+    )?;
+    Ok(path)
+}
+
+/// Rewrites the fragments in use after profiles changed. Profiles extending
+/// a changed one are covered too.
+pub fn refresh_fragments(config: &Config) -> Result<(), GuseError> {
+    for (name, profile) in config.load_profiles()? {
+        if fragment_path(&name).exists() {
+            write_fragment(&name, &profile)?;
+        }
+    }
+    Ok(())
+}
+
+/// Removes every directory and URL rule that uses `profile_name`, and its
+/// fragment. Returns the rules that were removed.
+pub fn remove_profile_rules(
+    config: &Config,
+    profile_name: &str,
+) -> Result<Vec<IncludeRule>, GuseError> {
+    let mut document = load_global_config()?;
+    let (removed, kept): (Vec<_>, Vec<_>) = document
+        .rules
+        .drain(..)
+        .partition(|r| r.profile().as_deref() == Some(profile_name));
+    document.rules = kept;
+    if !removed.is_empty() {
+        document.save(&config.backup_store())?;
+    }
+
+    let fragment = fragment_path(profile_name);
+    if fragment.exists() {
+        fs::remove_file(&fragment)?;
+    }
+    Ok(removed)
+}
+
+pub fn load_global_config() -> Result<IncludeDocument, GuseError> {
+    let path = global_config_path()
+        .ok_or_else(|| GuseError::GitError("Cannot determine the home directory".to_string()))?;
+    Ok(IncludeDocument::load(&path)?)
+}
+
+/// Absolute form of a directory given on the command line.
+pub fn absolute_dir(dir: &Path) -> Result<PathBuf, GuseError> {
+    let dir = PathBuf::from(shellexpand::tilde(&dir.to_string_lossy()).to_string());
+    let dir = if dir.is_absolute() {
+        dir
+    } else {
+        std::env::current_dir()?.join(dir)
+    };
+    Ok(fs::canonicalize(&dir).unwrap_or(dir))
+}
@@ -0,0 +1,52 @@
+use clap::Parser;
+use colored::*;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::error::GuseError;
+use crate::git::include::gitdir_condition;
+
+use super::{absolute_dir, load_global_config};
+
+#[derive(Parser, Debug)]
+#[command(about = "Stop using a profile below a directory")]
+pub struct RemoveCommand {
+    /// Directory given to `guse dir add`
+    #[arg(help = "Directory given to `guse dir add` (e.g., ~/work)")]
+    pub dir: PathBuf,
+}
+
+impl RemoveCommand {
+    pub fn execute(&self, config: &Config) -> Result<(), GuseError> {
+        let dir = absolute_dir(&self.dir)?;
+        let mut document = load_global_config()?;
+        let Some(removed) = document.remove_rule(&gitdir_condition(&dir)) else {
+            println!(
+                "{}",
+                format!("❌ No profile is set for {}.", dir.display())
+                    .red()
+                    .bold()
+            );
+            return Ok(());
+        };
+        document.save(&config.backup_store())?;
+
+        // The fragment goes once no rule includes it any more.
+        if !document.rules.iter().any(|r| r.path == removed.path) && removed.path.exists() {
+            fs::remove_file(&removed.path)?;
+        }
+
+        println!(
+            "{}",
+            format!(
+                "✅ Repositories below {} no longer use profile '{}'",
+                dir.display(),
+                removed.profile().unwrap_or_default()
+            )
+            .green()
+            .bold()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+use clap::Parser;
+use colored::*;
+use std::path::PathBuf;
+
+use crate::config::transfer::{self, Format};
+use crate::config::Config;
+use crate::error::GuseError;
+use crate::utils::file::atomic_write;
+
+#[derive(Parser, Debug)]
+#[command(about = "Export profiles to share them with another machine")]
+pub struct ExportCommand {
+    /// Profiles to export
+    #[arg(help = "Names of the profiles to export. Exports all profiles if none are given.")]
+    pub profiles: Vec<String>,
+
+    #[arg(
+        short,
+        long,
+        value_enum,
+        help = "Output format [default: from the --output extension, else toml]"
+    )]
+    pub format: Option<Format>,
+
+    #[arg(short, long, help = "Write to this file instead of standard output")]
+    pub output: Option<PathBuf>,
+}
+
+impl ExportCommand {
+    pub fn execute(&self, config: &Config) -> Result<(), GuseError> {
+        use log::info;
+
+        let profiles = config.load_profiles()?;
+        let mut selected: Vec<_> = if self.profiles.is_empty() {
+            profiles.iter().map(|(n, p)| (n.as_str(), p)).collect()
+        } else {
+            let mut selected = Vec::new();
+            for name in &self.profiles {
+                let Some(profile) = profiles.get(name) else {
+                    println!(
+                        "{}",
+                        format!("❌ Profile '{}' not found.", name).red().bold()
+                    );
+                    return Ok(());
+                };
+                selected.push((name.as_str(), profile));
+            }
+            selected
+        };
+        selected.sort_by_key(|(name, _)| *name);
+
+        let format = self
+            .format
+            .or_else(|| self.output.as_deref().and_then(Format::from_path))
+            .unwrap_or_default();
+        let exported = transfer::export(&selected, format)?;
+
+        let Some(output) = &self.output else {
+            print!("{}", exported);
+            return Ok(());
+        };
+        atomic_write(output, exported)?;
+        info!(
+            "Exported {} profile(s) to {}",
+            selected.len(),
+            output.display()
+        );
+        println!(
+            "{}",
+            format!(
+                "✅ Exported {} profile(s) to {}",
+                selected.len(),
+                output.display()
+            )
+            .green()
+            .bold()
+        );
+        Ok(())
+    }
+}
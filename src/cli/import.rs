@@ -0,0 +1,119 @@
+use clap::Parser;
+use colored::*;
+use dialoguer::Confirm;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config::transfer::{self, Format, ImportAction, MergeStrategy};
+use crate::config::Config;
+use crate::error::GuseError;
+use crate::ui::UI;
+use crate::utils::{validate_email, validate_ssh_host};
+
+#[derive(Parser, Debug)]
+#[command(about = "Import profiles exported with `guse export`")]
+pub struct ImportCommand {
+    /// File to import
+    #[arg(help = "File written by `guse export`, or another guse configuration file")]
+    pub file: PathBuf,
+
+    #[arg(
+        short,
+        long,
+        value_enum,
+        help = "Input format [default: from the file extension, else toml]"
+    )]
+    pub format: Option<Format>,
+
+    #[arg(
+        short,
+        long,
+        value_enum,
+        default_value_t = MergeStrategy::Skip,
+        help = "What to do when a profile with the same name already exists"
+    )]
+    pub strategy: MergeStrategy,
+
+    #[arg(short, long, help = "Do not ask for confirmation")]
+    pub yes: bool,
+}
+
+impl ImportCommand {
+    pub fn execute(&self, config: &Config) -> Result<(), GuseError> {
+        use log::info;
+
+        let contents = fs::read_to_string(&self.file).map_err(|e| {
+            GuseError::ConfigError(format!("Cannot read {}: {}", self.file.display(), e))
+        })?;
+        let format = self
+            .format
+            .or_else(|| Format::from_path(&self.file))
+            .unwrap_or_default();
+        let incoming = transfer::parse(&contents, format)?;
+        if incoming.is_empty() {
+            println!("{}", "❌ No profiles found in the file.".red().bold());
+            return Ok(());
+        }
+
+        // Reject the whole file before anything is written.
+        let mut names: Vec<&String> = incoming.keys().collect();
+        names.sort();
+        let mut invalid = Vec::new();
+        for name in names {
+            let profile = &incoming[name];
+            if let Err(e) =
+                validate_email(&profile.email).and_then(|_| validate_ssh_host(&profile.ssh_host))
+            {
+                invalid.push(format!("'{}': {}", name, e));
+            }
+        }
+        if !invalid.is_empty() {
+            for message in &invalid {
+                println!("{} {}", "❌".red(), message.red());
+            }
+            return Err(GuseError::ValidationError(format!(
+                "{} invalid profile(s) in {}; nothing was imported",
+                invalid.len(),
+                self.file.display()
+            )));
+        }
+
+        let plan = transfer::plan(&config.load_profiles()?, incoming.clone(), self.strategy);
+        UI::print_import_plan(&plan);
+
+        let changes = plan
+            .iter()
+            .filter(|i| !matches!(i.action, ImportAction::Skip | ImportAction::Unchanged))
+            .count();
+        if changes == 0 {
+            println!("{}", "Nothing to import.".yellow());
+            return Ok(());
+        }
+
+        if !self.yes
+            && !Confirm::new()
+                .with_prompt(format!("Import {} profile(s)?", changes))
+                .default(true)
+                .interact()?
+        {
+            return Ok(());
+        }
+
+        // Plan again under the lock in case the file changed meanwhile.
+        config.modify(|file| {
+            let plan = transfer::plan(&file.resolved_profiles(), incoming, self.strategy);
+            transfer::apply(file, &plan)
+        })?;
+
+        info!(
+            "Imported {} profile(s) from {}",
+            changes,
+            self.file.display()
+        );
+        println!(
+            "\n{}",
+            format!("✅ Imported {} profile(s)", changes).green().bold()
+        );
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
 use clap::Parser;
+use colored::*;
 
 use crate::config::Config;
 use crate::error::GuseError;
@@ -10,8 +11,21 @@ pub struct ListCommand;
 
 impl ListCommand {
     pub fn execute(&self, config: &Config) -> Result<(), GuseError> {
-        let profiles: Vec<_> = config.load_profiles()?.into_iter().collect();
-        UI::print_profiles(&profiles);
+        let file = config.load()?;
+        let profiles = file.settings.sort(file.resolved_profiles());
+        UI::print_profiles(
+            &profiles,
+            file.settings.default_profile.as_deref(),
+            &config.load_state(),
+        );
+
+        for (name, e) in file.broken_profiles() {
+            println!(
+                "{} {}",
+                "⚠️".yellow().bold(),
+                format!("Skipping profile '{}': {}", name, e).yellow()
+            );
+        }
         Ok(())
     }
 }
@@ -1,13 +1,30 @@
 pub mod add;
+pub mod add_ssh;
+pub mod backup;
+pub mod default;
 pub mod delete;
+pub mod dir;
+pub mod export;
+pub mod import;
 pub mod list;
 pub mod list_ssh;
+pub mod order;
+pub mod pin;
+pub mod remote;
 pub mod show;
+pub mod show_profile;
+pub mod ssh;
 pub mod switch;
 pub mod update;
-pub mod add_ssh;
+pub mod url;
 
 use clap::Parser;
+use std::path::PathBuf;
+
+use crate::config::pin::Pin;
+use crate::config::{Config, ConfigError, Settings};
+use crate::git::backend::GitBackend;
+use crate::git::{BackendKind, Git};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about = "Git Account Switcher")]
@@ -16,6 +33,23 @@ use clap::Parser;
     long_about = "A tool to easily switch between Git accounts. Manage multiple Git accounts and switch between them quickly."
 )]
 pub struct Args {
+    #[arg(
+        long,
+        global = true,
+        value_name = "PATH",
+        help = "Configuration file to use instead of $GUSE_CONFIG or ~/.config/guse/config.toml"
+    )]
+    pub config: Option<PathBuf>,
+
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        default_value_t,
+        help = "How guse reads and writes repository Git configuration"
+    )]
+    pub git_backend: BackendKind,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -40,6 +74,12 @@ pub enum Commands {
     #[command(name = "show", about = "Show current Git configuration")]
     Show(show::ShowCommand),
 
+    #[command(
+        name = "show-profile",
+        about = "Show a profile, optionally with inherited values resolved"
+    )]
+    ShowProfile(show_profile::ShowProfileCommand),
+
     #[command(name = "switch", about = "Switch to a different Git profile")]
     Switch(switch::SwitchCommand),
 
@@ -47,4 +87,68 @@ pub enum Commands {
     Update(update::UpdateCommand),
     #[command(name = "add-ssh", about = "Add a new SSH host to ~/.ssh/config")]
     AddSsh(add_ssh::AddSshCommand),
+
+    #[command(name = "ssh", about = "Manage hosts and keys in ~/.ssh")]
+    Ssh(ssh::SshCommand),
+
+    #[command(name = "export", about = "Export profiles as TOML, JSON or YAML")]
+    Export(export::ExportCommand),
+
+    #[command(name = "import", about = "Import profiles from an exported file")]
+    Import(import::ImportCommand),
+
+    #[command(
+        name = "dir",
+        about = "Give every repository below a directory a profile's identity"
+    )]
+    Dir(dir::DirCommand),
+
+    #[command(
+        name = "url",
+        about = "Pick a profile's identity from the repository's remote URL"
+    )]
+    Url(url::UrlCommand),
+
+    #[command(
+        name = "remote",
+        about = "Convert the repository's remote URLs between SSH and HTTPS"
+    )]
+    Remote(remote::RemoteCommand),
+
+    #[command(name = "pin", about = "Pin the current repository to a profile")]
+    Pin(pin::PinCommand),
+
+    #[command(name = "default", about = "Set the profile pickers start on")]
+    Default(default::DefaultCommand),
+
+    #[command(name = "order", about = "Set the order profiles are listed in")]
+    Order(order::OrderCommand),
+
+    #[command(name = "backup", about = "List and restore backups of changed files")]
+    Backup(backup::BackupCommand),
+}
+
+/// Where an interactive profile picker over `names` starts: the profile last
+/// used in the current repository, then the configured default.
+pub fn preselect_profile<B: GitBackend>(
+    config: &Config,
+    settings: &Settings,
+    names: &[String],
+    git: &Git<B>,
+) -> usize {
+    let repository = git.repo_root().ok();
+    config
+        .load_state()
+        .preselect(names, repository.as_deref(), settings)
 }
+
+/// The pin of the repository the current directory is in, if any.
+pub fn find_pin<B: GitBackend>(git: &Git<B>) -> Result<Option<Pin>, ConfigError> {
+    Pin::find(
+        git.repo_root().ok().as_deref(),
+        git.git_dir().ok().as_deref(),
+    )
+}
+
+#[cfg(test)]
+mod tests;
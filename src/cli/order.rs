@@ -0,0 +1,59 @@
+use clap::Parser;
+use colored::*;
+
+use crate::config::Config;
+use crate::error::GuseError;
+
+#[derive(Parser, Debug)]
+#[command(about = "Set the order profiles are listed in")]
+pub struct OrderCommand {
+    /// Profiles to list first, in this order
+    #[arg(
+        help = "Profiles to list first, in this order; the rest follow alphabetically. Shows the current order when omitted."
+    )]
+    pub profiles: Vec<String>,
+
+    #[arg(
+        long,
+        conflicts_with = "profiles",
+        help = "List all profiles alphabetically again"
+    )]
+    pub clear: bool,
+}
+
+impl OrderCommand {
+    pub fn execute(&self, config: &Config) -> Result<(), GuseError> {
+        use log::info;
+
+        if self.profiles.is_empty() && !self.clear {
+            self.print_order(config)?;
+            return Ok(());
+        }
+
+        let profiles = config.load_profiles()?;
+        if let Some(missing) = self.profiles.iter().find(|p| !profiles.contains_key(*p)) {
+            println!(
+                "{}",
+                format!("❌ Profile '{}' not found.", missing).red().bold()
+            );
+            return Ok(());
+        }
+
+        config.set_order(self.profiles.clone())?;
+        info!("Profile order set: {:?}", self.profiles);
+        println!("{}", "✅ Profile order updated".green().bold());
+        self.print_order(config)
+    }
+
+    fn print_order(&self, config: &Config) -> Result<(), GuseError> {
+        let (profiles, _) = config.load_sorted()?;
+        if profiles.is_empty() {
+            println!("{}", "❌ No profiles found.".red().bold());
+            return Ok(());
+        }
+        for (i, (name, _)) in profiles.iter().enumerate() {
+            println!("  {}. {}", i + 1, name);
+        }
+        Ok(())
+    }
+}
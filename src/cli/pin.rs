@@ -0,0 +1,110 @@
+use clap::Parser;
+use colored::*;
+
+use crate::cli::find_pin;
+use crate::config::pin::{Pin, GIT_DIR_PIN_FILE, PIN_FILE};
+use crate::config::Config;
+use crate::error::GuseError;
+use crate::git::backend::GitBackend;
+use crate::git::Git;
+
+#[derive(Parser, Debug)]
+#[command(about = "Pin the current repository to a profile")]
+pub struct PinCommand {
+    /// Profile to pin; shows the current pin when omitted
+    #[arg(help = "Profile to pin the repository to. Shows the current pin when omitted.")]
+    pub profile: Option<String>,
+
+    #[arg(
+        long,
+        help = "Write the pin into the Git directory instead of a .guse.toml that can be committed"
+    )]
+    pub local: bool,
+
+    #[arg(
+        long,
+        conflicts_with = "profile",
+        help = "Remove the pin files of this repository"
+    )]
+    pub remove: bool,
+}
+
+impl PinCommand {
+    pub fn execute<B: GitBackend>(&self, config: &Config, git: &Git<B>) -> Result<(), GuseError> {
+        let repo_root = git.repo_root()?;
+
+        if self.remove {
+            // Both go; removing only the one in effect would let the other take over
+            let removed = Pin::remove_all(Some(&repo_root), git.git_dir().ok().as_deref())?;
+            if removed.is_empty() {
+                println!("{}", "This repository is not pinned.".yellow());
+            }
+            for path in removed {
+                println!(
+                    "{}",
+                    format!("✅ Removed {}", path.display()).green().bold()
+                );
+            }
+            return Ok(());
+        }
+
+        let Some(profile) = &self.profile else {
+            match find_pin(git)? {
+                Some(pin) => println!(
+                    "{} {}",
+                    "📌".blue().bold(),
+                    format!(
+                        "Pinned to profile '{}' by {}",
+                        pin.profile,
+                        pin.source.display()
+                    )
+                    .blue()
+                ),
+                None => println!("{}", "This repository is not pinned.".yellow()),
+            }
+            return Ok(());
+        };
+
+        if !config.load_profiles()?.contains_key(profile) {
+            println!(
+                "{}",
+                format!("❌ Profile '{}' not found.", profile).red().bold()
+            );
+            return Ok(());
+        }
+
+        let path = if self.local {
+            git.git_dir()?.join(GIT_DIR_PIN_FILE)
+        } else {
+            repo_root.join(PIN_FILE)
+        };
+        let pin = Pin::write(&path, profile)?;
+        println!(
+            "{}",
+            format!(
+                "✅ Pinned this repository to '{}' in {}",
+                pin.profile,
+                pin.source.display()
+            )
+            .green()
+            .bold()
+        );
+
+        // A pin in the Git directory shadows the shared one.
+        if let Some(effective) = find_pin(git)? {
+            if effective.source != pin.source {
+                println!(
+                    "{} {}",
+                    "⚠️".yellow().bold(),
+                    format!(
+                        "{} takes precedence and pins '{}'.",
+                        effective.source.display(),
+                        effective.profile
+                    )
+                    .yellow()
+                );
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,97 @@
+use clap::Parser;
+use colored::*;
+
+use crate::config::{Config, Transport};
+use crate::error::GuseError;
+use crate::git::backend::GitBackend;
+use crate::git::remote_url::RemoteUrl;
+use crate::git::{Git, RemoteChange, RemoteOutcome};
+use crate::ui::UI;
+
+use super::convert_url;
+
+#[derive(Parser, Debug)]
+#[command(about = "Convert remote URLs between SSH and HTTPS")]
+pub struct ConvertCommand {
+    #[arg(long, value_enum, help = "Transport to convert the remotes to")]
+    pub to: Transport,
+
+    #[arg(
+        long,
+        help = "Profile whose SSH host and HTTPS user to use; by default the remotes keep their host"
+    )]
+    pub profile: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Remote to convert (repeatable); by default every remote"
+    )]
+    pub remote: Vec<String>,
+}
+
+impl ConvertCommand {
+    pub fn execute<B: GitBackend>(
+        &self,
+        config: &Config,
+        git: &mut Git<B>,
+    ) -> Result<(), GuseError> {
+        let profile = match &self.profile {
+            Some(name) => match config.load_profiles()?.remove(name) {
+                Some(profile) => Some(profile),
+                None => {
+                    println!(
+                        "{}",
+                        format!("❌ Profile '{}' not found.", name).red().bold()
+                    );
+                    return Ok(());
+                }
+            },
+            None => None,
+        };
+
+        let remotes = if self.remote.is_empty() {
+            git.remotes()?
+        } else {
+            self.remote.clone()
+        };
+
+        let mut changes = Vec::new();
+        for remote in &remotes {
+            let Some(old_url) = git.remote_url(remote)? else {
+                println!(
+                    "{} {}",
+                    "⚠️".yellow().bold(),
+                    format!("Remote '{}' not found.", remote).yellow()
+                );
+                continue;
+            };
+            let outcome = match RemoteUrl::parse(&old_url) {
+                Err(_) => RemoteOutcome::Skipped("unsupported URL".to_string()),
+                Ok(url) => match convert_url(&url, self.to, profile.as_ref()) {
+                    None => RemoteOutcome::Skipped("local path".to_string()),
+                    Some(target) if target == url => RemoteOutcome::Unchanged,
+                    Some(target) => RemoteOutcome::Updated(git.set_remote(remote, &target)?),
+                },
+            };
+            changes.push(RemoteChange {
+                remote: remote.clone(),
+                old_url,
+                outcome,
+            });
+        }
+
+        if changes.is_empty() {
+            println!("{}", "❌ No remotes found.".red().bold());
+            return Ok(());
+        }
+        println!(
+            "\n{}",
+            format!("✅ Remotes converted to {}:", self.to)
+                .green()
+                .bold()
+        );
+        UI::print_remote_changes(&changes);
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+pub mod convert;
+
+use clap::Parser;
+
+use crate::config::{Config, Profile, Transport};
+use crate::error::GuseError;
+use crate::git::backend::GitBackend;
+use crate::git::remote_url::RemoteUrl;
+use crate::git::Git;
+
+#[derive(Parser, Debug)]
+#[command(about = "Manage the repository's remote URLs")]
+pub struct RemoteCommand {
+    #[command(subcommand)]
+    pub command: RemoteCommands,
+}
+
+#[derive(Parser, Debug)]
+pub enum RemoteCommands {
+    #[command(name = "convert", about = "Convert remote URLs between SSH and HTTPS")]
+    Convert(convert::ConvertCommand),
+}
+
+impl RemoteCommand {
+    pub fn execute<B: GitBackend>(
+        &self,
+        config: &Config,
+        git: &mut Git<B>,
+    ) -> Result<(), GuseError> {
+        match &self.command {
+            RemoteCommands::Convert(cmd) => cmd.execute(config, git),
+        }
+    }
+}
+
+/// `url` over the transport `to`. With a profile, the remote goes through its
+/// SSH host alias, or over HTTPS to its `https_host`, logged in as its
+/// `https_user`. Otherwise the host stays the remote's own: an SSH
+/// `HostName` can be an SSH-only endpoint such as `ssh.github.com`, so it is
+/// never used for HTTPS. Local paths have no counterpart and yield `None`.
+pub fn convert_url(url: &RemoteUrl, to: Transport, profile: Option<&Profile>) -> Option<RemoteUrl> {
+    match to {
+        Transport::Ssh => url.to_ssh(profile.map_or(url.host.as_str(), |p| &p.ssh_host)),
+        Transport::Https => {
+            let host = profile
+                .and_then(|p| p.https_host.as_deref())
+                .unwrap_or(&url.host);
+            let user = match profile {
+                Some(profile) => profile.https_user.as_deref(),
+                // An SSH login such as `git` is not an HTTPS account.
+                None => url.user.as_deref().filter(|_| url.is_http()),
+            };
+            url.to_https(host, user)
+        }
+    }
+}
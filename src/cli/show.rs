@@ -1,17 +1,31 @@
 use clap::Parser;
 use colored::*;
+use std::ffi::OsString;
+use std::path::PathBuf;
 
+use crate::cli::find_pin;
+use crate::config::Config;
 use crate::error::GuseError;
-use crate::git::Git;
+use crate::git::backend::GitBackend;
+use crate::git::{identity_env_overrides, Git};
+use crate::ssh::agent::Agent;
+use crate::ssh::SshConfig;
 use crate::ui::UI;
 
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Default)]
 #[command(about = "Show current Git configuration")]
-pub struct ShowCommand;
+pub struct ShowCommand {
+    /// Agent socket to use instead of `SSH_AUTH_SOCK`.
+    #[arg(skip)]
+    pub agent_socket: Option<OsString>,
+
+    /// SSH config to read instead of `~/.ssh/config`.
+    #[arg(skip)]
+    pub ssh_config: Option<PathBuf>,
+}
 
 impl ShowCommand {
-    pub fn execute(&self) -> Result<(), GuseError> {
-        let git = Git::new();
+    pub fn execute<B: GitBackend>(&self, config: &Config, git: &Git<B>) -> Result<(), GuseError> {
         let current_config = git.get_current_config()?;
 
         if current_config.remote_url.is_empty() {
@@ -28,6 +42,145 @@ impl ShowCommand {
         }
 
         UI::print_current_config(&current_config);
+        self.print_precedence(git)?;
+        self.print_pin_state(config, git, &current_config.user_email)?;
+        self.print_agent_state(config)
+    }
+
+    /// Shows the identity at every config level and whether the environment
+    /// overrides all of them.
+    fn print_precedence<B: GitBackend>(&self, git: &Git<B>) -> Result<(), GuseError> {
+        let keys = ["user.name", "user.email"]
+            .into_iter()
+            .map(|key| Ok((key, git.config_origins(key)?)))
+            .collect::<Result<Vec<_>, GuseError>>()?;
+        UI::print_config_origins(&keys);
+
+        let overrides = identity_env_overrides();
+        for (name, key, value) in &overrides {
+            let role = if name.starts_with("GIT_AUTHOR") {
+                "author"
+            } else {
+                "committer"
+            };
+            println!(
+                "{} {}",
+                "⚠️".yellow().bold(),
+                format!(
+                    "{}='{}' is set and overrides {} as the {} of every commit.",
+                    name, value, key, role
+                )
+                .yellow()
+            );
+        }
+        if !overrides.is_empty() {
+            println!();
+        }
+        Ok(())
+    }
+
+    /// Warns when the repository is pinned to a profile with another email.
+    fn print_pin_state<B: GitBackend>(
+        &self,
+        config: &Config,
+        git: &Git<B>,
+        email: &str,
+    ) -> Result<(), GuseError> {
+        let Some(pin) = find_pin(git)? else {
+            return Ok(());
+        };
+        let source = pin.source.display();
+        match config.load_profiles()?.get(&pin.profile) {
+            None => println!(
+                "{} {}",
+                "⚠️".yellow().bold(),
+                format!(
+                    "Pinned to profile '{}' by {}, but no such profile exists.",
+                    pin.profile, source
+                )
+                .yellow()
+            ),
+            Some(profile) if profile.email != email => {
+                println!(
+                    "{} {}",
+                    "⚠️".yellow().bold(),
+                    format!(
+                        "Pinned to profile '{}' ({}) by {}, but user.email is {}.",
+                        pin.profile,
+                        profile.email,
+                        source,
+                        if email.is_empty() {
+                            "not set".to_string()
+                        } else {
+                            format!("'{}'", email)
+                        }
+                    )
+                    .yellow()
+                );
+                println!(
+                    "{}",
+                    "Run `guse switch` to apply the pinned profile.".yellow()
+                );
+            }
+            Some(_) => println!(
+                "{} {}",
+                "📌".blue().bold(),
+                format!("Pinned to profile '{}' by {}", pin.profile, source).blue()
+            ),
+        }
+        println!();
+        Ok(())
+    }
+
+    fn print_agent_state(&self, config: &Config) -> Result<(), GuseError> {
+        // An unreachable agent or unreadable ssh config must not fail `show`
+        let socket = self
+            .agent_socket
+            .clone()
+            .or_else(|| std::env::var_os("SSH_AUTH_SOCK"));
+        let state = Agent::connect_to(socket.as_deref())
+            .and_then(|mut agent| agent.identities())
+            .and_then(|identities| {
+                let ssh_config = match &self.ssh_config {
+                    Some(path) => SshConfig::load_from(path)?,
+                    None => SshConfig::load()?,
+                };
+                Ok((identities, ssh_config))
+            });
+        let (identities, ssh_config) = match state {
+            Ok(state) => state,
+            Err(e) => {
+                println!(
+                    "{} {}",
+                    "🔐 ssh-agent:".cyan().bold(),
+                    "not available".yellow()
+                );
+                log::info!("{}", e);
+                return Ok(());
+            }
+        };
+        let mut profiles: Vec<_> = config.load_profiles()?.into_iter().collect();
+        profiles.sort_by(|a, b| a.0.cmp(&b.0));
+        let profile_keys: Vec<_> = profiles
+            .iter()
+            .map(|(name, profile)| (name, ssh_config.public_keys(&profile.ssh_host)))
+            .collect();
+
+        let rows: Vec<_> = identities
+            .into_iter()
+            .map(|identity| {
+                let owners = profile_keys
+                    .iter()
+                    .filter(|(_, keys)| {
+                        keys.iter()
+                            .any(|(_, k)| k.key_data() == identity.public_key.key_data())
+                    })
+                    .map(|(name, _)| name.to_string())
+                    .collect();
+                (identity, owners)
+            })
+            .collect();
+        UI::print_agent_identities(&rows);
         Ok(())
     }
 }
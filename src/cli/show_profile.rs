@@ -0,0 +1,58 @@
+use clap::Parser;
+use colored::*;
+
+use crate::config::Config;
+use crate::error::GuseError;
+use crate::ui::UI;
+
+#[derive(Parser, Debug)]
+#[command(about = "Show a profile as stored, or with inherited values resolved")]
+pub struct ShowProfileCommand {
+    /// Name of the profile to show
+    #[arg(help = "Name of the profile to show (e.g., personal, work)")]
+    pub profile: String,
+
+    #[arg(
+        long,
+        help = "Show effective values after `extends`, with the profile each comes from"
+    )]
+    pub resolved: bool,
+}
+
+impl ShowProfileCommand {
+    pub fn execute(&self, config: &Config) -> Result<(), GuseError> {
+        let file = config.load()?;
+        let Some(entry) = file.profiles.get(&self.profile) else {
+            println!(
+                "{}",
+                format!("❌ Profile '{}' not found.", self.profile)
+                    .red()
+                    .bold()
+            );
+            return Ok(());
+        };
+
+        if !self.resolved {
+            UI::print_profile_entry(&self.profile, entry);
+            return Ok(());
+        }
+
+        let resolved = file.resolve(&self.profile)?;
+        UI::print_resolved_profile(&self.profile, &resolved);
+
+        let missing = resolved.missing_fields();
+        if !missing.is_empty() {
+            println!(
+                "{} {}",
+                "⚠️".yellow().bold(),
+                format!(
+                    "'{}' is a template (missing {}); it can be extended but not switched to.",
+                    self.profile,
+                    missing.join(", ")
+                )
+                .yellow()
+            );
+        }
+        Ok(())
+    }
+}
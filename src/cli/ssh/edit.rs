@@ -0,0 +1,163 @@
+use clap::Parser;
+use colored::*;
+use dialoguer::Input;
+
+use crate::config::Config;
+use crate::error::GuseError;
+use crate::ssh::SshConfig;
+
+use super::{open_document, select_host};
+
+#[derive(Parser, Debug)]
+#[command(about = "Change options of an existing SSH host")]
+pub struct EditCommand {
+    /// Host alias to edit
+    #[arg(
+        help = "Host alias to edit. If not provided, you will be prompted to select from configured hosts."
+    )]
+    #[arg(required = false)]
+    pub alias: Option<String>,
+
+    #[arg(long, help = "New HostName")]
+    pub hostname: Option<String>,
+
+    #[arg(long, help = "New User")]
+    pub user: Option<String>,
+
+    #[arg(long, help = "New Port")]
+    pub port: Option<u16>,
+
+    #[arg(long, help = "New IdentityFile")]
+    pub identity_file: Option<String>,
+}
+
+impl EditCommand {
+    pub fn execute(&self, config: &Config) -> Result<(), GuseError> {
+        use log::info;
+
+        let ssh_config = SshConfig::load()?;
+        let Some(host) = select_host(
+            &ssh_config,
+            self.alias.as_deref(),
+            "Select SSH host to edit",
+        )?
+        else {
+            return Ok(());
+        };
+
+        info!("Editing SSH host: {}", host.alias);
+        println!(
+            "\n{}",
+            format!("✏️  Editing SSH host '{}'", host.alias)
+                .cyan()
+                .bold()
+        );
+        println!("{}", "=".repeat(40).cyan());
+
+        let interactive = self.hostname.is_none()
+            && self.user.is_none()
+            && self.port.is_none()
+            && self.identity_file.is_none();
+
+        let changes: Vec<(&str, Option<String>, String)> = if interactive {
+            let prompt = |label: &str, current: Option<&str>| -> Result<String, GuseError> {
+                Ok(Input::new()
+                    .with_prompt(label)
+                    .default(current.unwrap_or_default().to_string())
+                    .allow_empty(true)
+                    .interact_text()?)
+            };
+            vec![
+                (
+                    "HostName",
+                    host.hostname.clone(),
+                    prompt("HostName", host.hostname.as_deref())?,
+                ),
+                (
+                    "User",
+                    host.user.clone(),
+                    prompt("User", host.user.as_deref())?,
+                ),
+                (
+                    "Port",
+                    host.port.clone(),
+                    prompt("Port", host.port.as_deref())?,
+                ),
+                (
+                    "IdentityFile",
+                    host.identity_files.first().cloned(),
+                    prompt(
+                        "IdentityFile",
+                        host.identity_files.first().map(String::as_str),
+                    )?,
+                ),
+            ]
+        } else {
+            [
+                ("HostName", host.hostname.clone(), self.hostname.clone()),
+                ("User", host.user.clone(), self.user.clone()),
+                ("Port", host.port.clone(), self.port.map(|p| p.to_string())),
+                (
+                    "IdentityFile",
+                    host.identity_files.first().cloned(),
+                    self.identity_file.clone(),
+                ),
+            ]
+            .into_iter()
+            .filter_map(|(keyword, current, new)| new.map(|new| (keyword, current, new)))
+            .collect()
+        };
+
+        let changes: Vec<(&str, String)> = changes
+            .into_iter()
+            .filter(|(_, current, new)| current.as_deref().unwrap_or_default() != new)
+            .map(|(keyword, _, new)| (keyword, new))
+            .collect();
+        for (keyword, new) in &changes {
+            if *keyword == "Port" && !new.is_empty() && new.parse::<u16>().is_err() {
+                return Err(GuseError::ValidationError(format!("Invalid port: {}", new)));
+            }
+        }
+
+        let mut document = open_document(&host)?;
+        if !changes.is_empty() {
+            // Editing a section shared with other aliases would change them too
+            let shared = document.split_host(&host.alias)?;
+            if !shared.is_empty() {
+                println!(
+                    "{} '{}' shared its section with {}; it now has a section of its own.",
+                    "⚠️".yellow().bold(),
+                    host.alias,
+                    shared.join(", ")
+                );
+            }
+        }
+
+        let mut changed = false;
+        for (keyword, new) in changes {
+            let value = (!new.is_empty()).then_some(new.as_str());
+            document.set_option(&host.alias, keyword, value)?;
+            changed = true;
+        }
+
+        if !changed {
+            println!("{}", "ℹ️  Nothing to change.".yellow());
+            return Ok(());
+        }
+
+        document.save(&config.backup_store())?;
+
+        info!("SSH host edit completed: {}", host.alias);
+        println!(
+            "\n{}",
+            format!(
+                "✅ SSH host '{}' updated in {}",
+                host.alias,
+                document.path.display()
+            )
+            .green()
+            .bold()
+        );
+        Ok(())
+    }
+}
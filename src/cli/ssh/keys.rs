@@ -0,0 +1,156 @@
+use clap::Parser;
+use colored::*;
+use prettytable::*;
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::error::GuseError;
+use crate::ssh::keys::{self, expand_identity_path, same_key, KeyInfo};
+use crate::ssh::SshConfig;
+use crate::utils::get_ssh_config_path;
+
+#[derive(Parser, Debug)]
+#[command(about = "List SSH keys with fingerprints and where they are used")]
+pub struct KeysCommand;
+
+impl KeysCommand {
+    pub fn execute(&self, config: &Config) -> Result<(), GuseError> {
+        let ssh_config = SshConfig::load()?;
+        let hosts = ssh_config.hosts();
+        let mut profiles: Vec<_> = config.load_profiles()?.into_iter().collect();
+        profiles.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let ssh_config_path = get_ssh_config_path()?;
+        let ssh_dir = ssh_config_path.parent().unwrap_or(&ssh_config_path);
+        let mut inventory = keys::scan(ssh_dir);
+
+        // Keys referenced from outside ~/.ssh, references to missing files,
+        // and paths that depend on values only known when connecting
+        let mut missing: Vec<(String, PathBuf)> = Vec::new();
+        let mut unresolved: Vec<(String, &str)> = Vec::new();
+        for host in &hosts {
+            for identity in &host.identity_files {
+                let Some(path) = expand_identity_path(identity, host) else {
+                    unresolved.push((host.alias.clone(), identity));
+                    continue;
+                };
+                if !path.exists() {
+                    missing.push((host.alias.clone(), path));
+                } else if !inventory.iter().any(|k| same_key(&k.path, &path)) {
+                    if let Ok(key) = KeyInfo::read(&path) {
+                        inventory.push(key);
+                    }
+                }
+            }
+        }
+
+        println!("\n{}", "🗝️  SSH Keys:".cyan().bold());
+        println!("{}", "=".repeat(40).cyan());
+
+        let mut table = Table::new();
+        table.add_row(row![
+            "Key",
+            "Type",
+            "Fingerprint",
+            "Comment",
+            "Modified",
+            "Hosts",
+            "Profiles"
+        ]);
+
+        let mut unused = Vec::new();
+        for key in &inventory {
+            let key_hosts: Vec<&str> = hosts
+                .iter()
+                .filter(|h| {
+                    h.identity_files.iter().any(|i| {
+                        expand_identity_path(i, h).is_some_and(|path| same_key(&path, &key.path))
+                    })
+                })
+                .map(|h| h.alias.as_str())
+                .collect();
+            let key_profiles: Vec<&str> = profiles
+                .iter()
+                .filter(|(_, p)| key_hosts.contains(&p.ssh_host.as_str()))
+                .map(|(name, _)| name.as_str())
+                .collect();
+
+            let mut name = match key.path.strip_prefix(ssh_dir) {
+                Ok(relative) => relative.display().to_string(),
+                Err(_) => key.path.display().to_string(),
+            };
+            if !key.has_private {
+                name.push_str(" (public only)");
+            } else if !key.has_public {
+                name.push_str(" (no .pub)");
+            }
+
+            let key_type = match key.bits {
+                Some(bits) => format!("{} {}", key.algorithm, bits),
+                None => key.algorithm.clone(),
+            };
+
+            let hosts_cell = if !key_hosts.is_empty() {
+                key_hosts.join("\n").normal()
+            } else if key.is_default_identity() {
+                "(default identity)".normal()
+            } else {
+                unused.push(name.clone());
+                "unused".yellow()
+            };
+
+            table.add_row(row![
+                name,
+                key_type,
+                key.fingerprint,
+                key.comment,
+                key.modified
+                    .map(|m| m.format("%Y-%m-%d").to_string())
+                    .unwrap_or_default(),
+                hosts_cell,
+                key_profiles.join("\n")
+            ]);
+        }
+
+        table.printstd();
+        println!();
+
+        if !unused.is_empty() {
+            println!(
+                "{} {}",
+                "⚠️".yellow().bold(),
+                "Keys not referenced by any Host block:".yellow()
+            );
+            for name in &unused {
+                println!("  {}", name);
+            }
+            println!();
+        }
+
+        if !missing.is_empty() {
+            println!(
+                "{} {}",
+                "❌".red().bold(),
+                "Hosts pointing to missing key files:".red()
+            );
+            for (alias, path) in &missing {
+                println!("  {} → {}", alias, path.display());
+            }
+            println!();
+        }
+
+        if !unresolved.is_empty() {
+            println!(
+                "{} {}",
+                "⚠️".yellow().bold(),
+                "Key paths that can only be resolved when connecting:".yellow()
+            );
+            for (alias, identity) in &unresolved {
+                println!("  {} → {} (unresolved)", alias, identity);
+            }
+            println!();
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+pub mod edit;
+pub mod keys;
+pub mod pubkey;
+pub mod remove;
+pub mod rename;
+
+use clap::Parser;
+use colored::*;
+use dialoguer::Select;
+
+use crate::config::Config;
+use crate::error::GuseError;
+use crate::ssh::edit::SshConfigDocument;
+use crate::ssh::{SshConfig, SshHost};
+
+#[derive(Parser, Debug)]
+#[command(about = "Manage hosts and keys in ~/.ssh")]
+pub struct SshCommand {
+    #[command(subcommand)]
+    pub command: SshCommands,
+}
+
+#[derive(Parser, Debug)]
+pub enum SshCommands {
+    #[command(name = "edit", about = "Change options of an existing SSH host")]
+    Edit(edit::EditCommand),
+
+    #[command(
+        name = "keys",
+        about = "List SSH keys with fingerprints and where they are used"
+    )]
+    Keys(keys::KeysCommand),
+
+    #[command(
+        name = "pubkey",
+        about = "Print the public key used for an SSH host or profile"
+    )]
+    Pubkey(pubkey::PubkeyCommand),
+
+    #[command(name = "remove", about = "Remove an SSH host")]
+    Remove(remove::RemoveCommand),
+
+    #[command(name = "rename", about = "Rename an SSH host alias")]
+    Rename(rename::RenameCommand),
+}
+
+impl SshCommand {
+    pub fn execute(&self, config: &Config) -> Result<(), GuseError> {
+        match &self.command {
+            SshCommands::Edit(cmd) => cmd.execute(config),
+            SshCommands::Keys(cmd) => cmd.execute(config),
+            SshCommands::Pubkey(cmd) => cmd.execute(config),
+            SshCommands::Remove(cmd) => cmd.execute(config),
+            SshCommands::Rename(cmd) => cmd.execute(config),
+        }
+    }
+}
+
+/// Looks up `alias`, or lets the user pick a host when it is not given.
+/// Returns `None` after printing a message when there is nothing to pick.
+fn select_host(
+    ssh_config: &SshConfig,
+    alias: Option<&str>,
+    prompt: &str,
+) -> Result<Option<SshHost>, GuseError> {
+    let hosts = ssh_config.hosts();
+    if hosts.is_empty() {
+        println!("{}", "❌ No SSH hosts found.".red().bold());
+        return Ok(None);
+    }
+
+    if let Some(alias) = alias {
+        let host = hosts.into_iter().find(|h| h.alias == alias);
+        if host.is_none() {
+            println!(
+                "{}",
+                format!("❌ SSH host '{}' not found.", alias).red().bold()
+            );
+        }
+        return Ok(host);
+    }
+
+    let items: Vec<String> = hosts.iter().map(|host| host.label()).collect();
+    let selection = Select::new()
+        .with_prompt(prompt)
+        .items(&items)
+        .default(0)
+        .interact()?;
+    Ok(hosts.into_iter().nth(selection))
+}
+
+/// Opens the file that declares `host`, which may be pulled in via `Include`.
+fn open_document(host: &SshHost) -> Result<SshConfigDocument, GuseError> {
+    Ok(SshConfigDocument::load(&host.source)?)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,157 @@
+use clap::{Parser, ValueEnum};
+use colored::*;
+use ssh_key::PublicKey;
+use std::path::PathBuf;
+
+use crate::config::{Config, ProfileMap};
+use crate::error::GuseError;
+use crate::ssh::keys::{load_public_key, read_public_key};
+use crate::ssh::SshConfig;
+use crate::utils::file::atomic_write;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Provider {
+    Github,
+    Gitlab,
+    Gitea,
+}
+
+impl Provider {
+    /// Guesses the provider from a HostName.
+    pub(super) fn detect(hostname: &str) -> Option<Self> {
+        let hostname = hostname.to_lowercase();
+        if hostname == "github.com" || hostname.ends_with(".github.com") {
+            Some(Provider::Github)
+        } else if hostname.contains("gitlab") {
+            Some(Provider::Gitlab)
+        } else if hostname.contains("gitea") || hostname == "codeberg.org" {
+            Some(Provider::Gitea)
+        } else {
+            None
+        }
+    }
+
+    fn display_name(&self) -> &'static str {
+        match self {
+            Provider::Github => "GitHub",
+            Provider::Gitlab => "GitLab",
+            Provider::Gitea => "Gitea",
+        }
+    }
+
+    /// Page where a new SSH key is registered.
+    pub(super) fn settings_url(&self, hostname: &str) -> String {
+        match self {
+            Provider::Github => "https://github.com/settings/ssh/new".to_string(),
+            Provider::Gitlab => format!("https://{}/-/user_settings/ssh_keys", hostname),
+            Provider::Gitea => format!("https://{}/user/settings/keys", hostname),
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Print the public key used for an SSH host or profile")]
+pub struct PubkeyCommand {
+    #[arg(help = "SSH host alias or guse profile name")]
+    pub target: String,
+
+    #[arg(short, long, help = "Also write the public key to this file")]
+    pub output: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "Provider to print registration details for (detected from HostName by default)"
+    )]
+    pub provider: Option<Provider>,
+
+    #[arg(long, help = "Title to register the key under")]
+    pub title: Option<String>,
+}
+
+impl PubkeyCommand {
+    pub fn execute(&self, config: &Config) -> Result<(), GuseError> {
+        use log::info;
+
+        let ssh_config = SshConfig::load()?;
+        let (alias, profile_name, identity) =
+            resolve_identity(&config.load_profiles()?, &ssh_config, &self.target)?;
+        let host = ssh_config.resolve(&alias);
+
+        info!("Reading public key for {}: {}", alias, identity.display());
+        let public_key = read_public_key(&identity)?;
+        let key = load_public_key(&identity)?;
+
+        println!(
+            "\n{}",
+            format!("🔑 Public key for '{}' ({})", alias, identity.display())
+                .cyan()
+                .bold()
+        );
+        println!("{}", "=".repeat(40).cyan());
+        println!("{}\n", public_key);
+
+        if let Some(output) = &self.output {
+            atomic_write(output, format!("{}\n", public_key))?;
+            println!("{} Written to {}\n", "✅".green(), output.display());
+        }
+
+        let hostname = host.hostname.clone().unwrap_or_else(|| alias.clone());
+        let Some(provider) = self.provider.or_else(|| Provider::detect(&hostname)) else {
+            return Ok(());
+        };
+
+        let title = self
+            .title
+            .clone()
+            .unwrap_or_else(|| key_title(&key, profile_name.as_deref(), &alias));
+        let key_body = PublicKey::new(key.key_data().clone(), "")
+            .to_openssh()
+            .map_err(|e| GuseError::SshError(format!("Cannot encode public key: {}", e)))?;
+
+        println!(
+            "{}",
+            format!("🌐 Register this key on {}:", provider.display_name())
+                .cyan()
+                .bold()
+        );
+        println!("  URL:   {}", provider.settings_url(&hostname));
+        println!("  Title: {}", title);
+        println!("  Key:   {}", key_body);
+        println!();
+
+        Ok(())
+    }
+}
+
+/// Finds the first existing IdentityFile for `target`. A profile name
+/// resolves through its ssh_host; anything else is taken as a host alias.
+/// Returns the alias, the profile name if one matched, and the key path.
+pub(super) fn resolve_identity(
+    profiles: &ProfileMap,
+    ssh_config: &SshConfig,
+    target: &str,
+) -> Result<(String, Option<String>, PathBuf), GuseError> {
+    let (alias, profile_name) = match profiles.get(target) {
+        Some(profile) => (profile.ssh_host.clone(), Some(target.to_string())),
+        None => (target.to_string(), None),
+    };
+
+    let Some(identity) = ssh_config.identity_paths(&alias).into_iter().next() else {
+        return Err(GuseError::SshError(format!(
+            "No existing IdentityFile configured for SSH host '{}'",
+            alias
+        )));
+    };
+    Ok((alias, profile_name, identity))
+}
+
+/// Title to register a key under: its comment, else the profile or alias.
+pub(super) fn key_title(key: &PublicKey, profile: Option<&str>, alias: &str) -> String {
+    let comment = key.comment().trim();
+    if !comment.is_empty() {
+        comment.to_string()
+    } else {
+        profile.unwrap_or(alias).to_string()
+    }
+}
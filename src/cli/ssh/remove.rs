@@ -0,0 +1,77 @@
+use clap::Parser;
+use colored::*;
+use dialoguer::Confirm;
+
+use crate::config::Config;
+use crate::error::GuseError;
+use crate::ssh::SshConfig;
+
+use super::{open_document, select_host};
+
+#[derive(Parser, Debug)]
+#[command(about = "Remove an SSH host")]
+pub struct RemoveCommand {
+    /// Host alias to remove
+    #[arg(
+        help = "Host alias to remove. If not provided, you will be prompted to select from configured hosts."
+    )]
+    #[arg(required = false)]
+    pub alias: Option<String>,
+
+    #[arg(short, long, help = "Do not ask for confirmation")]
+    pub yes: bool,
+}
+
+impl RemoveCommand {
+    pub fn execute(&self, config: &Config) -> Result<(), GuseError> {
+        use log::info;
+
+        let ssh_config = SshConfig::load()?;
+        let Some(host) = select_host(
+            &ssh_config,
+            self.alias.as_deref(),
+            "Select SSH host to remove",
+        )?
+        else {
+            return Ok(());
+        };
+
+        let users: Vec<String> = config
+            .load_profiles()?
+            .into_iter()
+            .filter(|(_, profile)| profile.ssh_host == host.alias)
+            .map(|(name, _)| name)
+            .collect();
+        if !users.is_empty() {
+            println!(
+                "{} {}",
+                "⚠️".yellow().bold(),
+                format!("Used by profile(s): {}", users.join(", ")).yellow()
+            );
+        }
+
+        if !self.yes
+            && !Confirm::new()
+                .with_prompt(format!("Remove SSH host '{}'?", host.alias))
+                .default(false)
+                .interact()?
+        {
+            return Ok(());
+        }
+
+        info!("Removing SSH host: {}", host.alias);
+        let mut document = open_document(&host)?;
+        document.remove_host(&host.alias)?;
+
+        document.save(&config.backup_store())?;
+
+        info!("SSH host removal completed: {}", host.alias);
+        println!(
+            "\n{}",
+            format!("✅ SSH host '{}' removed", host.alias)
+                .green()
+                .bold()
+        );
+        Ok(())
+    }
+}
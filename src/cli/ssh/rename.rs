@@ -0,0 +1,91 @@
+use clap::Parser;
+use colored::*;
+
+use crate::cli::dir::{load_global_config, refresh_fragments};
+use crate::cli::url::{has_url_rules, sync_url_rules};
+use crate::config::Config;
+use crate::error::GuseError;
+use crate::ssh::SshConfig;
+use crate::utils::validate_ssh_host;
+
+use super::{open_document, select_host};
+
+#[derive(Parser, Debug)]
+#[command(about = "Rename an SSH host alias")]
+pub struct RenameCommand {
+    #[arg(help = "Current host alias")]
+    pub old: String,
+
+    #[arg(help = "New host alias")]
+    pub new: String,
+}
+
+impl RenameCommand {
+    pub fn execute(&self, config: &Config) -> Result<(), GuseError> {
+        use log::info;
+
+        validate_ssh_host(&self.new)?;
+
+        let ssh_config = SshConfig::load()?;
+        if ssh_config.hosts().iter().any(|h| h.alias == self.new) {
+            return Err(GuseError::ValidationError(format!(
+                "SSH host '{}' already exists.",
+                self.new
+            )));
+        }
+        let Some(host) = select_host(&ssh_config, Some(&self.old), "")? else {
+            return Ok(());
+        };
+
+        info!("Renaming SSH host: {} -> {}", self.old, self.new);
+        let mut document = open_document(&host)?;
+        document.rename_host(&self.old, &self.new)?;
+
+        document.save(&config.backup_store())?;
+
+        // Keep profiles pointing at the renamed alias. Entries are changed
+        // where they set the host, so inherited values follow along.
+        let mut renamed = Vec::new();
+        config.modify(|file| {
+            for (name, entry) in file.profiles.iter_mut() {
+                if entry.ssh_host.as_deref() == Some(self.old.as_str()) {
+                    entry.ssh_host = Some(self.new.clone());
+                    renamed.push(name.clone());
+                }
+            }
+            Ok(())
+        })?;
+        renamed.sort();
+        for name in renamed {
+            println!(
+                "{} {}",
+                "🔁".blue(),
+                format!("Profile '{}' now uses '{}'", name, self.new).blue()
+            );
+        }
+
+        // URL rules match remotes by host alias, so they follow the rename.
+        // Profiles inheriting the host are covered by resolving them again.
+        refresh_fragments(config)?;
+        let mut document = load_global_config()?;
+        let mut synced = false;
+        for (name, profile) in config.load_profiles()? {
+            if profile.ssh_host == self.new && has_url_rules(&document, &name) {
+                sync_url_rules(&mut document, &name, &profile)?;
+                synced = true;
+            }
+        }
+        if synced {
+            document.save(&config.backup_store())?;
+        }
+
+        info!("SSH host rename completed: {}", self.new);
+        println!(
+            "\n{}",
+            format!("✅ SSH host '{}' renamed to '{}'", self.old, self.new)
+                .green()
+                .bold()
+        );
+        Ok(())
+    }
+}
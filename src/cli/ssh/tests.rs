@@ -0,0 +1,99 @@
+use ssh_key::PublicKey;
+use std::fs;
+use std::path::Path;
+
+use crate::config::{Profile, ProfileMap, SwitchStrategy, Transport};
+use crate::ssh::SshConfig;
+
+use super::pubkey::{key_title, resolve_identity, Provider};
+
+const ED25519_PUB: &str = "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIKLuMI97tgrusx3czzFsOyAoBSubIPHlOpxhdai/3dln work@example.com";
+
+#[test]
+fn test_provider_detect() {
+    assert_eq!(Provider::detect("github.com"), Some(Provider::Github));
+    assert_eq!(Provider::detect("ssh.GitHub.com"), Some(Provider::Github));
+    assert_eq!(
+        Provider::detect("gitlab.example.com"),
+        Some(Provider::Gitlab)
+    );
+    assert_eq!(Provider::detect("codeberg.org"), Some(Provider::Gitea));
+    assert_eq!(Provider::detect("gitea.internal"), Some(Provider::Gitea));
+    assert_eq!(Provider::detect("notgithub.com"), None);
+    assert_eq!(Provider::detect("bitbucket.org"), None);
+}
+
+#[test]
+fn test_provider_settings_url() {
+    assert_eq!(
+        Provider::Github.settings_url("github.com"),
+        "https://github.com/settings/ssh/new"
+    );
+    assert_eq!(
+        Provider::Gitlab.settings_url("gitlab.example.com"),
+        "https://gitlab.example.com/-/user_settings/ssh_keys"
+    );
+    assert_eq!(
+        Provider::Gitea.settings_url("codeberg.org"),
+        "https://codeberg.org/user/settings/keys"
+    );
+}
+
+#[test]
+fn test_key_title() {
+    let key = PublicKey::from_openssh(ED25519_PUB).unwrap();
+    assert_eq!(
+        key_title(&key, Some("work"), "github-work"),
+        "work@example.com"
+    );
+
+    // Without a comment the profile, then the alias, is used
+    let key = PublicKey::new(key.key_data().clone(), "");
+    assert_eq!(key_title(&key, Some("work"), "github-work"), "work");
+    assert_eq!(key_title(&key, None, "github-work"), "github-work");
+}
+
+#[test]
+fn test_resolve_identity() {
+    let dir = tempfile::tempdir().unwrap();
+    let key = dir.path().join("id_work");
+    fs::write(dir.path().join("id_work.pub"), ED25519_PUB).unwrap();
+    let ssh_config = SshConfig::parse(
+        &format!(
+            "Host github-work\n  HostName github.com\n  IdentityFile {}/id_missing\n  IdentityFile {}\n",
+            dir.path().display(),
+            key.display()
+        ),
+        Path::new("config"),
+        dir.path(),
+    )
+    .unwrap();
+
+    let mut profiles = ProfileMap::new();
+    profiles.insert(
+        "work".to_string(),
+        Profile {
+            name: "Work".to_string(),
+            email: "work@example.com".to_string(),
+            ssh_host: "github-work".to_string(),
+            strategy: SwitchStrategy::Alias,
+            url_patterns: Vec::new(),
+            transport: Transport::Ssh,
+            https_user: None,
+            https_host: None,
+        },
+    );
+
+    // Profile → ssh_host → first IdentityFile that exists
+    let (alias, profile, identity) = resolve_identity(&profiles, &ssh_config, "work").unwrap();
+    assert_eq!(alias, "github-work");
+    assert_eq!(profile.as_deref(), Some("work"));
+    assert_eq!(identity, key);
+
+    // A host alias is used as is
+    let (alias, profile, _) = resolve_identity(&profiles, &ssh_config, "github-work").unwrap();
+    assert_eq!(alias, "github-work");
+    assert_eq!(profile, None);
+
+    assert!(resolve_identity(&profiles, &ssh_config, "unknown").is_err());
+}
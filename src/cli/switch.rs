@@ -1,10 +1,18 @@
 use clap::Parser;
 use colored::*;
 use dialoguer::Select;
+use std::path::Path;
 
-use crate::config::Config;
+use crate::cli::remote::convert_url;
+use crate::cli::{find_pin, preselect_profile};
+use crate::config::{Config, Profile, SwitchStrategy, Transport};
 use crate::error::GuseError;
-use crate::git::Git;
+use crate::git::backend::{ConfigScope, GitBackend};
+use crate::git::remote_url::RemoteUrl;
+use crate::git::{Git, RemoteChange, RemoteOutcome, PROVIDER_HOSTS};
+use crate::ssh::agent::Agent;
+use crate::ssh::keys::private_key_path;
+use crate::ssh::SshConfig;
 use crate::ui::UI;
 
 #[derive(Parser, Debug)]
@@ -12,32 +20,110 @@ use crate::ui::UI;
 pub struct SwitchCommand {
     /// Name of the profile to switch to
     #[arg(
-        help = "Name of the profile to switch to (e.g., personal, work). If not provided, you will be prompted to select from available profiles."
+        help = "Name of the profile to switch to (e.g., personal, work). If not provided, the profile the repository is pinned to is used, or you will be prompted to select from available profiles."
     )]
     #[arg(required = false)]
     pub profile: Option<String>,
+
+    #[arg(long, help = "Load the profile's SSH key into ssh-agent")]
+    pub agent: bool,
+
+    #[arg(
+        long,
+        help = "Like --agent, and also remove keys of other guse profiles from ssh-agent"
+    )]
+    pub agent_exclusive: bool,
+
+    #[arg(
+        long,
+        help = "Switch even if the repository is pinned to a different profile"
+    )]
+    pub force: bool,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        default_value = "origin",
+        help = "Remote to point at the profile's SSH host (repeatable)"
+    )]
+    pub remote: Vec<String>,
+
+    #[arg(long, conflicts_with = "remote", help = "Update every remote")]
+    pub all_remotes: bool,
+
+    #[arg(
+        long,
+        help = "Only update remotes on a known provider host or the SSH host of a guse profile"
+    )]
+    pub known_hosts_only: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["worktree", "remote", "all_remotes", "known_hosts_only", "recurse_submodules", "force"],
+        help = "Set the identity in ~/.gitconfig for every repository; remotes are left alone"
+    )]
+    pub global: bool,
+
+    #[arg(
+        long,
+        help = "Set the identity in the current worktree's config instead of the repository's"
+    )]
+    pub worktree: bool,
+
+    #[arg(long, help = "Also switch every checked-out submodule, recursively")]
+    pub recurse_submodules: bool,
 }
 
 impl SwitchCommand {
-    pub fn execute(&self, config: &Config) -> Result<(), GuseError> {
+    pub fn execute<B: GitBackend>(
+        &self,
+        config: &Config,
+        git: &mut Git<B>,
+        ssh_config: &SshConfig,
+    ) -> Result<(), GuseError> {
         use log::info;
 
-        let mut git = Git::new();
-        let profiles: Vec<_> = config.load_profiles()?.into_iter().collect();
+        let (profiles, settings) = config.load_sorted()?;
         if profiles.is_empty() {
             println!("{}", "❌ No profiles found.".red().bold());
             return Ok(());
         }
 
         let profile_names: Vec<String> = profiles.iter().map(|(name, _)| name.clone()).collect();
-        let selection = if self.profile.is_none() {
-            Select::new()
-                .with_prompt("Select profile to switch to")
-                .items(&profile_names)
-                .default(0)
-                .interact()?
-        } else {
-            let profile_name = self.profile.as_ref().unwrap();
+        // A global identity is not tied to the repository or its pin.
+        let pin = if self.global { None } else { find_pin(git)? };
+        let requested = match (&self.profile, &pin) {
+            (Some(profile_name), Some(pin)) if *profile_name != pin.profile && !self.force => {
+                println!(
+                    "{}",
+                    format!(
+                        "❌ This repository is pinned to profile '{}' by {}. Use --force to switch to '{}' anyway.",
+                        pin.profile,
+                        pin.source.display(),
+                        profile_name
+                    )
+                    .red()
+                    .bold()
+                );
+                return Ok(());
+            }
+            (Some(profile_name), _) => Some(profile_name),
+            (None, Some(pin)) => {
+                println!(
+                    "{} {}",
+                    "📌".blue().bold(),
+                    format!(
+                        "Using profile '{}' pinned by {}",
+                        pin.profile,
+                        pin.source.display()
+                    )
+                    .blue()
+                );
+                Some(&pin.profile)
+            }
+            (None, None) => None,
+        };
+        let selection = if let Some(profile_name) = requested {
             match profile_names.iter().position(|x| x == profile_name) {
                 Some(idx) => idx,
                 None => {
@@ -50,6 +136,12 @@ impl SwitchCommand {
                     return Ok(());
                 }
             }
+        } else {
+            Select::new()
+                .with_prompt("Select profile to switch to")
+                .items(&profile_names)
+                .default(preselect_profile(config, &settings, &profile_names, git))
+                .interact()?
         };
 
         let profile_to_switch = &profile_names[selection];
@@ -72,17 +164,30 @@ impl SwitchCommand {
             "Changing Git configuration...".blue()
         );
 
-        git.set_config(&profile_data.name, &profile_data.email)?;
+        let scope = self.scope();
+        apply_identity(git, scope, &profile_data, ssh_config)?;
+        warn_overridden(git, scope);
 
-        // 원격 저장소 정보가 있는 경우에만 remote URL을 변경
-        match git.parse_origin_url() {
-            Ok((github_user, repo_name)) => {
-                git.set_remote(&profile_data.ssh_host, &github_user, &repo_name)?;
-                info!("Git account switch completed");
-                println!("\n{}", "✅ Git account switch completed:".green().bold());
-                UI::print_profile_table(&profile_data, &github_user, &repo_name);
+        if self.global {
+            info!("Global Git profile switch completed");
+            println!(
+                "\n{}",
+                "✅ Global Git profile switch completed:".green().bold()
+            );
+            UI::print_profile_table(&profile_data);
+        } else {
+            let rules = RemoteRules {
+                profile: &profile_data,
+                known_hosts: self
+                    .known_hosts_only
+                    .then(|| known_hosts(&profiles, ssh_config)),
+                ssh_config,
+            };
+            let mut changes = self.update_remotes(git, &rules, Path::new(""))?;
+            if self.recurse_submodules {
+                self.switch_submodules(git, scope, &rules, Path::new(""), &mut changes)?;
             }
-            Err(_e) => {
+            if changes.is_empty() {
                 println!(
                     "{} {}",
                     "⚠️".yellow().bold(),
@@ -95,10 +200,319 @@ impl SwitchCommand {
                 println!("{}", "  git remote add origin <repository-url>".cyan());
                 info!("Git profile switch completed (without remote update)");
                 println!("\n{}", "✅ Git profile switch completed:".green().bold());
-                UI::print_profile_table(&profile_data, "N/A", "N/A");
+                UI::print_profile_table(&profile_data);
+            } else {
+                info!("Git account switch completed");
+                println!("\n{}", "✅ Git account switch completed:".green().bold());
+                UI::print_profile_table(&profile_data);
+                UI::print_remote_changes(&changes);
             }
         }
 
+        // The history only steers pickers, so failing to save it is not fatal.
+        let repository = if self.global {
+            None
+        } else {
+            git.repo_root().ok()
+        };
+        if let Err(e) = config.record_switch(repository.as_deref(), profile_to_switch) {
+            log::warn!("Cannot record profile usage: {}", e);
+        }
+
+        if self.agent || self.agent_exclusive {
+            self.sync_agent(&profiles, profile_to_switch, &profile_data, ssh_config)?;
+        }
+
         Ok(())
     }
+
+    /// Points the requested remotes at the profile, reporting what happened
+    /// to each one that exists. Remote names are prefixed with `location`,
+    /// the submodule path, unless it is empty.
+    fn update_remotes<B: GitBackend>(
+        &self,
+        git: &mut Git<B>,
+        rules: &RemoteRules,
+        location: &Path,
+    ) -> Result<Vec<RemoteChange>, GuseError> {
+        // 원격 저장소 정보가 있는 경우에만 remote URL을 변경
+        let requested_remotes = if self.all_remotes {
+            git.remotes()?
+        } else {
+            self.remote.clone()
+        };
+        let mut changes = Vec::new();
+        for remote in &requested_remotes {
+            let Some(old_url) = git.remote_url(remote)? else {
+                println!(
+                    "{} {}",
+                    "⚠️".yellow().bold(),
+                    format!("Remote '{}' not found.", labelled(location, remote)).yellow()
+                );
+                continue;
+            };
+            let outcome = match rules.target(&old_url) {
+                Ok(target) => RemoteOutcome::Updated(git.set_remote(remote, &target)?),
+                Err(outcome) => outcome,
+            };
+            changes.push(RemoteChange {
+                remote: labelled(location, remote),
+                old_url,
+                outcome,
+            });
+        }
+
+        Ok(changes)
+    }
+
+    /// Applies the profile to every checked-out submodule of `git` and to
+    /// their own submodules, and points the `submodule.<name>.url` Git uses
+    /// for new clones at the same host.
+    fn switch_submodules<B: GitBackend>(
+        &self,
+        git: &mut Git<B>,
+        scope: ConfigScope,
+        rules: &RemoteRules,
+        location: &Path,
+        changes: &mut Vec<RemoteChange>,
+    ) -> Result<(), GuseError> {
+        for submodule in git.submodules()? {
+            if let Some(old_url) = git.submodule_url(&submodule.name)? {
+                let outcome = match rules.target(&old_url) {
+                    Ok(target) => {
+                        RemoteOutcome::Updated(git.set_submodule_url(&submodule.name, &target)?)
+                    }
+                    Err(outcome) => outcome,
+                };
+                changes.push(RemoteChange {
+                    remote: labelled(location, &format!("submodule.{}.url", submodule.name)),
+                    old_url,
+                    outcome,
+                });
+            }
+
+            let path = location.join(&submodule.path);
+            let Some(mut checkout) = git.open_submodule(&submodule)? else {
+                println!(
+                    "{} {}",
+                    "⚠️".yellow().bold(),
+                    format!(
+                        "Submodule '{}' is not checked out; run `git submodule update --init` first.",
+                        path.display()
+                    )
+                    .yellow()
+                );
+                continue;
+            };
+            log::info!("Switching submodule '{}'", path.display());
+            apply_identity(&mut checkout, scope, rules.profile, rules.ssh_config)?;
+            changes.extend(self.update_remotes(&mut checkout, rules, &path)?);
+            self.switch_submodules(&mut checkout, scope, rules, &path, changes)?;
+        }
+        Ok(())
+    }
+
+    /// The config file the identity is written to.
+    fn scope(&self) -> ConfigScope {
+        if self.global {
+            ConfigScope::Global
+        } else if self.worktree {
+            ConfigScope::Worktree
+        } else {
+            ConfigScope::Local
+        }
+    }
+
+    /// Makes sure ssh-agent offers the profile's key, optionally dropping keys
+    /// that belong to other profiles so the provider cannot pick the wrong one.
+    fn sync_agent(
+        &self,
+        profiles: &[(String, Profile)],
+        profile_name: &str,
+        profile: &Profile,
+        ssh_config: &SshConfig,
+    ) -> Result<(), GuseError> {
+        use dialoguer::Password;
+        use log::info;
+        use ssh_key::PrivateKey;
+
+        let mut agent = Agent::connect()?;
+
+        let target_keys = ssh_config.public_keys(&profile.ssh_host);
+        if target_keys.is_empty() {
+            println!(
+                "{} {}",
+                "⚠️".yellow().bold(),
+                format!("No IdentityFile found for SSH host '{}'.", profile.ssh_host).yellow()
+            );
+        }
+
+        for (path, public_key) in &target_keys {
+            if agent.contains(public_key)? {
+                info!("Key already in ssh-agent: {}", path.display());
+                continue;
+            }
+
+            let mut key = PrivateKey::read_openssh_file(path).map_err(|e| {
+                GuseError::SshError(format!("Cannot read {}: {}", path.display(), e))
+            })?;
+            if key.is_encrypted() {
+                let passphrase = Password::new()
+                    .with_prompt(format!("Passphrase for {}", path.display()))
+                    .interact()?;
+                key = key.decrypt(passphrase).map_err(|_| {
+                    GuseError::SshError(format!("Incorrect passphrase for {}", path.display()))
+                })?;
+            }
+            agent.add(&key)?;
+            println!(
+                "{} {}",
+                "🔐".blue().bold(),
+                format!("Added {} to ssh-agent", path.display()).blue()
+            );
+        }
+
+        if !self.agent_exclusive {
+            return Ok(());
+        }
+
+        for (name, other) in profiles.iter().filter(|(name, _)| name != profile_name) {
+            for (path, public_key) in ssh_config.public_keys(&other.ssh_host) {
+                // A key shared with the target profile stays loaded.
+                if target_keys
+                    .iter()
+                    .any(|(_, k)| k.key_data() == public_key.key_data())
+                {
+                    continue;
+                }
+                if agent.contains(&public_key)? {
+                    agent.remove(&public_key)?;
+                    println!(
+                        "{} {}",
+                        "🔐".blue().bold(),
+                        format!(
+                            "Removed {} (profile '{}') from ssh-agent",
+                            path.display(),
+                            name
+                        )
+                        .blue()
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Warns when a file with higher precedence than `scope` sets the identity,
+/// so that the values just written do not apply here.
+fn warn_overridden<B: GitBackend>(git: &Git<B>, scope: ConfigScope) {
+    for key in ["user.name", "user.email"] {
+        let Ok(values) = git.config_origins(key) else {
+            continue;
+        };
+        if let Some(winner) = values.last().filter(|v| v.scope > scope) {
+            println!(
+                "{} {}",
+                "⚠️".yellow().bold(),
+                format!(
+                    "{} is also set in {} ({}), which takes precedence here.",
+                    key,
+                    winner.origin.display(),
+                    winner.scope
+                )
+                .yellow()
+            );
+        }
+    }
+}
+
+/// Sets the profile's identity in `scope`, along with the SSH key its
+/// strategy needs.
+fn apply_identity<B: GitBackend>(
+    git: &mut Git<B>,
+    scope: ConfigScope,
+    profile: &Profile,
+    ssh_config: &SshConfig,
+) -> Result<(), GuseError> {
+    git.set_config(scope, &profile.name, &profile.email)?;
+
+    match profile.strategy {
+        SwitchStrategy::SshCommand => {
+            let key = ssh_config
+                .identity_paths(&profile.ssh_host)
+                .into_iter()
+                .next()
+                .map(|path| private_key_path(&path))
+                .ok_or_else(|| {
+                    GuseError::SshError(format!(
+                        "No IdentityFile found for SSH host '{}'",
+                        profile.ssh_host
+                    ))
+                })?;
+            git.set_ssh_command(scope, &key)?;
+        }
+        // A key pinned by an earlier switch would override the alias.
+        SwitchStrategy::Alias => git.clear_ssh_command(scope)?,
+    }
+    Ok(())
+}
+
+/// What rewriting a remote for a profile depends on besides its URL.
+struct RemoteRules<'a> {
+    profile: &'a Profile,
+    /// Hosts remotes may be rewritten on, with `--known-hosts-only`.
+    known_hosts: Option<Vec<String>>,
+    ssh_config: &'a SshConfig,
+}
+
+impl RemoteRules<'_> {
+    /// Where `url` should point: at the profile's SSH host when the strategy
+    /// relies on the URL, or at its HTTPS account when the profile uses
+    /// HTTPS. URLs it cannot or should not rewrite yield what to report
+    /// instead.
+    fn target(&self, url: &str) -> Result<RemoteUrl, RemoteOutcome> {
+        let profile = self.profile;
+        let Ok(parsed) = RemoteUrl::parse(url) else {
+            return Err(RemoteOutcome::Skipped("unsupported URL".to_string()));
+        };
+        if let Some(known_hosts) = &self.known_hosts {
+            let host = parsed.hostname();
+            if !known_hosts.iter().any(|k| k.eq_ignore_ascii_case(host)) {
+                return Err(RemoteOutcome::Skipped("unknown host".to_string()));
+            }
+        }
+        if profile.transport == Transport::Ssh && profile.strategy != SwitchStrategy::Alias {
+            return Err(RemoteOutcome::Unchanged);
+        }
+        let Some(target) = convert_url(&parsed, profile.transport, Some(profile)) else {
+            return Err(RemoteOutcome::Skipped("local path".to_string()));
+        };
+        if target == parsed {
+            return Err(RemoteOutcome::Unchanged);
+        }
+        Ok(target)
+    }
+}
+
+/// `name` prefixed with the submodule path it belongs to, if any.
+fn labelled(location: &Path, name: &str) -> String {
+    if location.as_os_str().is_empty() {
+        name.to_string()
+    } else {
+        format!("{}: {}", location.display(), name)
+    }
+}
+
+/// Hosts `--known-hosts-only` rewrites remotes on: the big providers, the
+/// SSH host of every profile, and the HostName each of those resolves to.
+fn known_hosts(profiles: &[(String, Profile)], ssh_config: &SshConfig) -> Vec<String> {
+    let mut hosts: Vec<String> = PROVIDER_HOSTS.iter().map(|h| h.to_string()).collect();
+    for (_, profile) in profiles {
+        hosts.push(profile.ssh_host.clone());
+        if let Some(hostname) = ssh_config.resolve(&profile.ssh_host).hostname {
+            hosts.push(hostname);
+        }
+    }
+    hosts
 }
@@ -0,0 +1,579 @@
+use std::fs;
+
+use crate::backup::BackupStore;
+use crate::cli::default::DefaultCommand;
+use crate::cli::order::OrderCommand;
+use crate::cli::remote::convert::ConvertCommand;
+use crate::cli::show::ShowCommand;
+use crate::cli::switch::SwitchCommand;
+use crate::cli::update::UpdateCommand;
+use crate::cli::url::url_patterns;
+use crate::config::{Config, Profile, SwitchStrategy, Transport};
+use crate::git::backend::ConfigScope;
+use crate::git::config_file::{ConfigSet, IncludeContext};
+use crate::git::include::hasconfig_condition;
+use crate::git::memory::MemoryBackend;
+use crate::git::native::NativeBackend;
+use crate::git::Git;
+use crate::ssh::SshConfig;
+
+fn config_in(dir: &std::path::Path) -> Config {
+    let config = Config {
+        path: dir.join("config.toml"),
+        backups: BackupStore::at(dir.join("backups")),
+        state: dir.join("state.toml"),
+    };
+    config
+        .add_profile(
+            "work".to_string(),
+            Profile {
+                name: "Work".to_string(),
+                email: "work@example.com".to_string(),
+                ssh_host: "github-work".to_string(),
+                strategy: SwitchStrategy::Alias,
+                url_patterns: Vec::new(),
+                transport: Transport::Ssh,
+                https_user: None,
+                https_host: None,
+            },
+        )
+        .unwrap();
+    config
+}
+
+fn switch(profile: &str, force: bool) -> SwitchCommand {
+    SwitchCommand {
+        profile: Some(profile.to_string()),
+        agent: false,
+        agent_exclusive: false,
+        force,
+        remote: vec!["origin".to_string()],
+        all_remotes: false,
+        known_hosts_only: false,
+        global: false,
+        worktree: false,
+        recurse_submodules: false,
+    }
+}
+
+/// `show` without the user's ssh-agent and `~/.ssh/config`.
+fn show(dir: &std::path::Path) -> ShowCommand {
+    ShowCommand {
+        agent_socket: Some(dir.join("agent.sock").into()),
+        ssh_config: Some(dir.join("ssh_config")),
+    }
+}
+
+/// The SSH config `show` reads, in place of `~/.ssh/config`.
+fn ssh_config(dir: &std::path::Path) -> SshConfig {
+    SshConfig::load_from(&dir.join("ssh_config")).unwrap()
+}
+
+#[test]
+fn test_switch_on_memory_backend() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = config_in(dir.path());
+    let repo = dir.path().join("repo");
+    let mut git = Git::with_backend(
+        MemoryBackend::new(&repo)
+            .with_config(
+                ConfigScope::Local,
+                "core.sshCommand",
+                "ssh -i /old -o IdentitiesOnly=yes",
+            )
+            .with_remote("origin", "git@github.com:me/project.git"),
+    );
+
+    switch("work", false)
+        .execute(&config, &mut git, &ssh_config(dir.path()))
+        .unwrap();
+
+    let current = git.get_current_config().unwrap();
+    assert_eq!(current.user_name, "Work");
+    assert_eq!(current.user_email, "work@example.com");
+    assert_eq!(current.remote_url, "git@github-work:me/project.git");
+    assert_eq!(current.ssh_command, "");
+    let state = config.load_state();
+    assert_eq!(
+        state.repositories[repo.to_string_lossy().as_ref()].profile,
+        "work"
+    );
+
+    show(dir.path()).execute(&config, &git).unwrap();
+}
+
+#[test]
+fn test_switch_respects_pin() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = config_in(dir.path());
+    let mut other = config.load_profiles().unwrap()["work"].clone();
+    other.email = "me@example.com".to_string();
+    config.add_profile("personal".to_string(), other).unwrap();
+
+    let repo = dir.path().join("repo");
+    fs::create_dir(&repo).unwrap();
+    fs::write(repo.join(".guse.toml"), "profile = \"work\"\n").unwrap();
+    let mut git = Git::with_backend(MemoryBackend::new(&repo));
+
+    switch("personal", false)
+        .execute(&config, &mut git, &ssh_config(dir.path()))
+        .unwrap();
+    assert_eq!(
+        git.get_current_config().unwrap().user_email,
+        "",
+        "pinned switch went through"
+    );
+
+    switch("personal", true)
+        .execute(&config, &mut git, &ssh_config(dir.path()))
+        .unwrap();
+    assert_eq!(
+        git.get_current_config().unwrap().user_email,
+        "me@example.com"
+    );
+
+    // Without a profile the pinned one is applied.
+    let pinned = SwitchCommand {
+        profile: None,
+        ..switch("", false)
+    };
+    pinned
+        .execute(&config, &mut git, &ssh_config(dir.path()))
+        .unwrap();
+    assert_eq!(
+        git.get_current_config().unwrap().user_email,
+        "work@example.com"
+    );
+}
+
+fn fork_backend(repo: &std::path::Path) -> MemoryBackend {
+    MemoryBackend::new(repo)
+        .with_remote("origin", "git@github.com:me/project.git")
+        .with_remote("upstream", "https://github.com/corp/project.git")
+        .with_remote("mirror", "git@git.internal.example:corp/project.git")
+}
+
+#[test]
+fn test_switch_selected_remotes() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = config_in(dir.path());
+    let repo = dir.path().join("repo");
+
+    let mut git = Git::with_backend(fork_backend(&repo));
+    let command = SwitchCommand {
+        remote: vec!["upstream".to_string(), "missing".to_string()],
+        ..switch("work", false)
+    };
+    command
+        .execute(&config, &mut git, &ssh_config(dir.path()))
+        .unwrap();
+    let url = |git: &Git<MemoryBackend>, remote: &str| git.remote_url(remote).unwrap().unwrap();
+    assert_eq!(url(&git, "origin"), "git@github.com:me/project.git");
+    assert_eq!(url(&git, "upstream"), "git@github-work:corp/project.git");
+
+    let mut git = Git::with_backend(fork_backend(&repo));
+    let command = SwitchCommand {
+        all_remotes: true,
+        known_hosts_only: true,
+        ..switch("work", false)
+    };
+    command
+        .execute(&config, &mut git, &ssh_config(dir.path()))
+        .unwrap();
+    assert_eq!(url(&git, "origin"), "git@github-work:me/project.git");
+    assert_eq!(url(&git, "upstream"), "git@github-work:corp/project.git");
+    assert_eq!(
+        url(&git, "mirror"),
+        "git@git.internal.example:corp/project.git"
+    );
+
+    let mut git = Git::with_backend(fork_backend(&repo));
+    let command = SwitchCommand {
+        all_remotes: true,
+        ..switch("work", false)
+    };
+    command
+        .execute(&config, &mut git, &ssh_config(dir.path()))
+        .unwrap();
+    assert_eq!(url(&git, "mirror"), "git@github-work:corp/project.git");
+}
+
+#[test]
+fn test_switch_to_https_profile() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = config_in(dir.path());
+    config
+        .add_profile(
+            "oss".to_string(),
+            Profile {
+                name: "OSS".to_string(),
+                email: "oss@example.com".to_string(),
+                ssh_host: "github.com".to_string(),
+                strategy: SwitchStrategy::Alias,
+                url_patterns: Vec::new(),
+                transport: Transport::Https,
+                https_user: Some("me-oss".to_string()),
+                https_host: None,
+            },
+        )
+        .unwrap();
+    let repo = dir.path().join("repo");
+    // SSH reaches github.com through another endpoint; HTTPS must not.
+    fs::write(
+        dir.path().join("ssh_config"),
+        "Host github.com\n  HostName ssh.github.com\n  Port 443\n",
+    )
+    .unwrap();
+
+    let mut git = Git::with_backend(fork_backend(&repo));
+    let command = SwitchCommand {
+        all_remotes: true,
+        known_hosts_only: true,
+        ..switch("oss", false)
+    };
+    command
+        .execute(&config, &mut git, &ssh_config(dir.path()))
+        .unwrap();
+    let url = |git: &Git<MemoryBackend>, remote: &str| git.remote_url(remote).unwrap().unwrap();
+    assert_eq!(
+        url(&git, "origin"),
+        "https://me-oss@github.com/me/project.git"
+    );
+    assert_eq!(
+        url(&git, "upstream"),
+        "https://me-oss@github.com/corp/project.git"
+    );
+    assert_eq!(
+        url(&git, "mirror"),
+        "git@git.internal.example:corp/project.git"
+    );
+
+    // Without a profile, conversion keeps each remote's host.
+    let command = ConvertCommand {
+        to: Transport::Ssh,
+        profile: None,
+        remote: Vec::new(),
+    };
+    command.execute(&config, &mut git).unwrap();
+    assert_eq!(url(&git, "origin"), "git@github.com:me/project.git");
+    assert_eq!(
+        url(&git, "mirror"),
+        "git@git.internal.example:corp/project.git"
+    );
+
+    let command = ConvertCommand {
+        to: Transport::Https,
+        profile: None,
+        remote: vec!["mirror".to_string()],
+    };
+    command.execute(&config, &mut git).unwrap();
+    assert_eq!(url(&git, "origin"), "git@github.com:me/project.git");
+    assert_eq!(
+        url(&git, "mirror"),
+        "https://git.internal.example/corp/project.git"
+    );
+}
+
+#[test]
+fn test_switch_to_https_host() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = config_in(dir.path());
+    let mut profile = config.load_profiles().unwrap()["work"].clone();
+    profile.transport = Transport::Https;
+    profile.https_user = Some("me-work".to_string());
+    profile.https_host = Some("github.com".to_string());
+    config.update_profile("work", profile).unwrap();
+    fs::write(
+        dir.path().join("ssh_config"),
+        "Host github-work\n  HostName ssh.github.com\n",
+    )
+    .unwrap();
+
+    // Remotes on the SSH alias go to the profile's HTTPS host.
+    let mut git = Git::with_backend(
+        MemoryBackend::new(dir.path().join("repo"))
+            .with_remote("origin", "git@github-work:me/project.git"),
+    );
+    switch("work", false)
+        .execute(&config, &mut git, &ssh_config(dir.path()))
+        .unwrap();
+    assert_eq!(
+        git.remote_url("origin").unwrap().as_deref(),
+        Some("https://me-work@github.com/me/project.git")
+    );
+}
+
+#[test]
+fn test_switch_scopes() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = config_in(dir.path());
+    let repo = dir.path().join("repo");
+    fs::create_dir(&repo).unwrap();
+    // The pin does not apply to the global identity.
+    fs::write(repo.join(".guse.toml"), "profile = \"personal\"\n").unwrap();
+    let mut git = Git::with_backend(fork_backend(&repo));
+
+    let global = SwitchCommand {
+        global: true,
+        ..switch("work", false)
+    };
+    global
+        .execute(&config, &mut git, &ssh_config(dir.path()))
+        .unwrap();
+    let get = |git: &Git<MemoryBackend>, scope| {
+        let values = git.config_origins("user.email").unwrap();
+        values
+            .into_iter()
+            .find(|v| v.scope == scope)
+            .and_then(|v| v.value)
+    };
+    assert_eq!(
+        get(&git, ConfigScope::Global).as_deref(),
+        Some("work@example.com")
+    );
+    assert_eq!(get(&git, ConfigScope::Local), None);
+    assert_eq!(
+        git.remote_url("origin").unwrap().as_deref(),
+        Some("git@github.com:me/project.git")
+    );
+    assert!(config.load_state().repositories.is_empty());
+
+    let worktree = SwitchCommand {
+        worktree: true,
+        ..switch("work", true)
+    };
+    worktree
+        .execute(&config, &mut git, &ssh_config(dir.path()))
+        .unwrap();
+    assert_eq!(
+        get(&git, ConfigScope::Worktree).as_deref(),
+        Some("work@example.com")
+    );
+    assert_eq!(get(&git, ConfigScope::Local), None);
+
+    show(dir.path()).execute(&config, &git).unwrap();
+}
+
+#[test]
+fn test_switch_ssh_command_uses_given_ssh_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = config_in(dir.path());
+    let mut profile = config.load_profiles().unwrap()["work"].clone();
+    profile.strategy = SwitchStrategy::SshCommand;
+    config.update_profile("work", profile).unwrap();
+    let key = dir.path().join("id_work");
+    fs::write(&key, "").unwrap();
+    fs::write(
+        dir.path().join("ssh_config"),
+        format!(
+            "Host github-work\n  HostName github.com\n  IdentityFile {}\n",
+            key.display()
+        ),
+    )
+    .unwrap();
+
+    let mut git = Git::with_backend(fork_backend(&dir.path().join("repo")));
+    switch("work", false)
+        .execute(&config, &mut git, &ssh_config(dir.path()))
+        .unwrap();
+    let ssh_command = git.get_current_config().unwrap().ssh_command;
+    assert!(ssh_command.contains(&key.display().to_string()));
+    // The remote keeps its host; the key is picked by core.sshCommand.
+    assert_eq!(
+        git.remote_url("origin").unwrap().as_deref(),
+        Some("git@github.com:me/project.git")
+    );
+}
+
+#[test]
+fn test_switch_recurse_submodules() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = config_in(dir.path());
+    let root = dir.path().canonicalize().unwrap().join("super");
+    let git_dir = root.join(".git");
+    let module_dir = git_dir.join("modules/lib");
+    fs::create_dir_all(&module_dir).unwrap();
+    fs::create_dir_all(root.join("libs/lib")).unwrap();
+    fs::create_dir_all(root.join("libs/missing")).unwrap();
+    fs::write(git_dir.join("HEAD"), "ref: refs/heads/main\n").unwrap();
+    fs::write(
+        git_dir.join("config"),
+        "[remote \"origin\"]\n\turl = git@github.com:me/super.git\n\
+         [submodule \"lib\"]\n\turl = git@github.com:me/lib.git\n",
+    )
+    .unwrap();
+    fs::write(
+        root.join(".gitmodules"),
+        "[submodule \"lib\"]\n\tpath = libs/lib\n\turl = git@github.com:me/lib.git\n\
+         [submodule \"missing\"]\n\tpath = libs/missing\n\turl = ../missing.git\n",
+    )
+    .unwrap();
+    fs::write(module_dir.join("HEAD"), "ref: refs/heads/main\n").unwrap();
+    fs::write(
+        module_dir.join("config"),
+        "[remote \"origin\"]\n\turl = git@github.com:me/lib.git\n",
+    )
+    .unwrap();
+    fs::write(
+        root.join("libs/lib/.git"),
+        "gitdir: ../../.git/modules/lib\n",
+    )
+    .unwrap();
+
+    // Git cannot parse this global file; it must not stop the switch.
+    let global = dir.path().join(".gitconfig");
+    fs::write(&global, "[user\n").unwrap();
+
+    let mut git = Git::with_backend(NativeBackend::with_paths(
+        &root,
+        &dir.path().join("gitconfig"),
+        &global,
+    ));
+    let command = SwitchCommand {
+        recurse_submodules: true,
+        ..switch("work", false)
+    };
+    command
+        .execute(&config, &mut git, &ssh_config(dir.path()))
+        .unwrap();
+
+    let superproject = fs::read_to_string(git_dir.join("config")).unwrap();
+    assert!(superproject.contains("url = git@github-work:me/super.git"));
+    assert!(superproject.contains("url = git@github-work:me/lib.git"));
+    let submodule = fs::read_to_string(module_dir.join("config")).unwrap();
+    assert!(submodule.contains("url = git@github-work:me/lib.git"));
+    assert!(submodule.contains("email = work@example.com"));
+    // .gitmodules is shared through the repository and stays as it is.
+    let gitmodules = fs::read_to_string(root.join(".gitmodules")).unwrap();
+    assert!(gitmodules.contains("git@github.com:me/lib.git"));
+
+    show(dir.path()).execute(&config, &git).unwrap();
+}
+
+#[test]
+fn test_default_and_order_commands() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = config_in(dir.path());
+    let mut home = config.load_profiles().unwrap()["work"].clone();
+    home.email = "home@example.com".to_string();
+    config.add_profile("home".to_string(), home).unwrap();
+
+    let default = |profile: Option<&str>, clear| DefaultCommand {
+        profile: profile.map(str::to_string),
+        clear,
+    };
+    default(Some("home"), false).execute(&config).unwrap();
+    assert_eq!(
+        config.load().unwrap().settings.default_profile.as_deref(),
+        Some("home")
+    );
+    // An unknown profile leaves the setting alone
+    default(Some("missing"), false).execute(&config).unwrap();
+    default(None, false).execute(&config).unwrap();
+    assert_eq!(
+        config.load().unwrap().settings.default_profile.as_deref(),
+        Some("home")
+    );
+    default(None, true).execute(&config).unwrap();
+    assert_eq!(config.load().unwrap().settings.default_profile, None);
+
+    let order = |profiles: &[&str], clear| OrderCommand {
+        profiles: profiles.iter().map(|p| p.to_string()).collect(),
+        clear,
+    };
+    order(&["work", "home"], false).execute(&config).unwrap();
+    assert_eq!(config.load().unwrap().settings.order, ["work", "home"]);
+    order(&["work", "missing"], false).execute(&config).unwrap();
+    assert_eq!(config.load().unwrap().settings.order, ["work", "home"]);
+    order(&[], true).execute(&config).unwrap();
+    assert!(config.load().unwrap().settings.order.is_empty());
+}
+
+#[test]
+fn test_url_patterns_for_https_profile() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut profile = config_in(dir.path()).load_profiles().unwrap()["work"].clone();
+    assert_eq!(
+        url_patterns(&profile),
+        ["git@github-work:*/**", "ssh://git@github-work/**"]
+    );
+
+    // Without an HTTPS host the alias is used, never its HostName.
+    profile.transport = Transport::Https;
+    assert_eq!(
+        url_patterns(&profile)[2..],
+        ["https://*@github-work/**", "https://github-work/**"]
+    );
+
+    profile.https_host = Some("github.com".to_string());
+    assert_eq!(
+        url_patterns(&profile)[2..],
+        ["https://*@github.com/**", "https://github.com/**"]
+    );
+
+    // With a login, only remotes logged in as it select the profile.
+    profile.https_user = Some("me-work".to_string());
+    let patterns = url_patterns(&profile);
+    assert_eq!(patterns[2..], ["https://me-work@github.com/**"]);
+
+    // Git picks the profile for HTTPS remotes on its host and login only.
+    let global = dir.path().join("global");
+    let fragment = dir.path().join("work.gitconfig");
+    fs::write(&fragment, "[user]\n\temail = work@example.com\n").unwrap();
+    let rules: String = patterns
+        .iter()
+        .map(|p| {
+            format!(
+                "[includeIf \"{}\"]\n\tpath = {}\n",
+                hasconfig_condition(p),
+                fragment.display()
+            )
+        })
+        .collect();
+    fs::write(&global, rules).unwrap();
+    let local = dir.path().join("local");
+    let email = |url: &str| {
+        fs::write(&local, format!("[remote \"origin\"]\n\turl = {}\n", url)).unwrap();
+        let files = [
+            (ConfigScope::Global, global.clone()),
+            (ConfigScope::Local, local.clone()),
+        ];
+        let set = ConfigSet::load(&files, &IncludeContext::default()).unwrap();
+        set.get("user.email").and_then(|v| v.value.clone())
+    };
+    assert_eq!(
+        email("https://me-work@github.com/corp/project.git").as_deref(),
+        Some("work@example.com")
+    );
+    assert_eq!(
+        email("git@github-work:corp/project.git").as_deref(),
+        Some("work@example.com")
+    );
+    // Another login on the same host belongs to another profile.
+    assert_eq!(email("https://me@github.com/corp/project.git"), None);
+    assert_eq!(email("https://github.com/corp/project.git"), None);
+    assert_eq!(email("https://gitlab.com/corp/project.git"), None);
+}
+
+#[test]
+fn test_update_https_user() {
+    let update = |https_user: Option<&str>| UpdateCommand {
+        profile: Some("work".to_string()),
+        strategy: None,
+        url_patterns: None,
+        transport: None,
+        https_user: https_user.map(str::to_string),
+        https_host: None,
+    };
+    let current = Some("me".to_string());
+    assert_eq!(
+        update(None).https_user(current.clone()).as_deref(),
+        Some("me")
+    );
+    assert_eq!(
+        update(Some("work")).https_user(current.clone()).as_deref(),
+        Some("work")
+    );
+    // `--https-user ''` removes the login.
+    assert_eq!(update(Some("")).https_user(current), None);
+}
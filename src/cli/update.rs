@@ -2,9 +2,15 @@ use clap::Parser;
 use colored::*;
 use dialoguer::{Input, Select};
 
-use crate::config::Config;
+use crate::cli::dir::{load_global_config, refresh_fragments};
+use crate::cli::preselect_profile;
+use crate::cli::url::{has_url_rules, sync_url_rules};
+use crate::config::{Config, SwitchStrategy, Transport};
 use crate::error::GuseError;
-use crate::utils::{backup_config_file, get_ssh_config_path, validate_email, validate_ssh_host};
+use crate::git::backend::GitBackend;
+use crate::git::Git;
+use crate::ssh::SshConfig;
+use crate::utils::{validate_email, validate_ssh_host};
 
 #[derive(Parser, Debug)]
 #[command(about = "Update an existing Git profile")]
@@ -15,28 +21,58 @@ pub struct UpdateCommand {
     )]
     #[arg(required = false)]
     pub profile: Option<String>,
+
+    #[arg(long, value_enum, help = "How `guse switch` selects the SSH key")]
+    pub strategy: Option<SwitchStrategy>,
+
+    #[arg(
+        long = "url-pattern",
+        value_name = "PATTERN",
+        help = "Replace the extra remote URL patterns for `guse url` (repeatable)"
+    )]
+    pub url_patterns: Option<Vec<String>>,
+
+    #[arg(long, value_enum, help = "Transport of the profile's remotes")]
+    pub transport: Option<Transport>,
+
+    #[arg(
+        long,
+        value_name = "USER",
+        help = "Login to embed in HTTPS remote URLs; an empty value removes it"
+    )]
+    pub https_user: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "HOST",
+        help = "Host for HTTPS remotes, when it is not the remote's own; an empty value removes it"
+    )]
+    pub https_host: Option<String>,
 }
 
 impl UpdateCommand {
-    pub fn execute(&self, config: &Config) -> Result<(), GuseError> {
+    /// The HTTPS login after this update: `--https-user` when given, with an
+    /// empty value clearing it, otherwise the `current` one.
+    pub(super) fn https_user(&self, current: Option<String>) -> Option<String> {
+        replace_or_clear(&self.https_user, current)
+    }
+
+    /// The HTTPS host after this update, like [`UpdateCommand::https_user`].
+    pub(super) fn https_host(&self, current: Option<String>) -> Option<String> {
+        replace_or_clear(&self.https_host, current)
+    }
+
+    pub fn execute<B: GitBackend>(&self, config: &Config, git: &Git<B>) -> Result<(), GuseError> {
         use log::info;
-        use std::fs;
 
-        let profiles: Vec<_> = config.load_profiles()?.into_iter().collect();
+        let (profiles, settings) = config.load_sorted()?;
         if profiles.is_empty() {
             println!("{}", "❌ No profiles found.".red().bold());
             return Ok(());
         }
 
         let profile_names: Vec<String> = profiles.iter().map(|(name, _)| name.clone()).collect();
-        let selection = if self.profile.is_none() {
-            Select::new()
-                .with_prompt("Select profile to update")
-                .items(&profile_names)
-                .default(0)
-                .interact()?
-        } else {
-            let profile_name = self.profile.as_ref().unwrap();
+        let selection = if let Some(profile_name) = &self.profile {
             match profile_names.iter().position(|x| x == profile_name) {
                 Some(idx) => idx,
                 None => {
@@ -49,6 +85,12 @@ impl UpdateCommand {
                     return Ok(());
                 }
             }
+        } else {
+            Select::new()
+                .with_prompt("Select profile to update")
+                .items(&profile_names)
+                .default(preselect_profile(config, &settings, &profile_names, git))
+                .interact()?
         };
 
         let profile_to_update = &profile_names[selection];
@@ -74,54 +116,8 @@ impl UpdateCommand {
             .interact_text()?;
 
         // Get SSH host list
-        let ssh_config = get_ssh_config_path()?;
-
-        #[derive(Clone)]
-        struct SshHost {
-            name: String,
-            hostname: String,
-            user: String,
-            port: String,
-        }
-
-        let mut hosts = Vec::new();
-        let mut current_host: Option<SshHost> = None;
-
-        if let Ok(content) = fs::read_to_string(&ssh_config) {
-            for line in content.lines() {
-                let line = line.trim();
-
-                if line.starts_with("Host ") {
-                    // Save previous host info if exists
-                    if let Some(host) = current_host.take() {
-                        hosts.push(host);
-                    }
-
-                    // Start new host
-                    let name = line.split_whitespace().nth(1).unwrap_or("").to_string();
-                    current_host = Some(SshHost {
-                        name,
-                        hostname: String::new(),
-                        user: String::new(),
-                        port: String::new(),
-                    });
-                } else if let Some(ref mut host) = current_host {
-                    // Parse host info
-                    if line.starts_with("HostName ") {
-                        host.hostname = line.split_whitespace().nth(1).unwrap_or("").to_string();
-                    } else if line.starts_with("User ") {
-                        host.user = line.split_whitespace().nth(1).unwrap_or("").to_string();
-                    } else if line.starts_with("Port ") {
-                        host.port = line.split_whitespace().nth(1).unwrap_or("").to_string();
-                    }
-                }
-            }
-
-            // Save last host info
-            if let Some(host) = current_host {
-                hosts.push(host);
-            }
-        }
+        let ssh_config = SshConfig::load()?;
+        let hosts = ssh_config.hosts();
 
         // Select SSH host
         let ssh_host = if hosts.is_empty() {
@@ -131,31 +127,15 @@ impl UpdateCommand {
                 .interact_text()?
         } else {
             // Convert host info to display format
-            let host_items: Vec<String> = hosts
-                .iter()
-                .map(|host| {
-                    let mut info = format!("{}", host.name);
-                    if !host.hostname.is_empty() {
-                        info.push_str(&format!(" ({})", host.hostname));
-                    }
-                    if !host.user.is_empty() {
-                        info.push_str(&format!(" - User: {}", host.user));
-                    }
-                    if !host.port.is_empty() {
-                        info.push_str(&format!(" - Port: {}", host.port));
-                    }
-                    info
-                })
-                .collect();
+            let mut items: Vec<String> = hosts.iter().map(|host| host.label()).collect();
 
             // Add "Manual Input" option
-            let mut items = host_items.clone();
             items.push("Manual Input".to_string());
 
             // Find index of existing SSH host
             let default_index = hosts
                 .iter()
-                .position(|h| h.name == existing_profile.ssh_host)
+                .position(|h| h.alias == existing_profile.ssh_host)
                 .unwrap_or(0);
 
             let selection = Select::new()
@@ -171,23 +151,56 @@ impl UpdateCommand {
                     .default(existing_profile.ssh_host.clone())
                     .interact_text()?
             } else {
-                hosts[selection].name.clone()
+                hosts[selection].alias.clone()
+            }
+        };
+
+        let strategy = match self.strategy {
+            Some(strategy) => strategy,
+            None => {
+                let items: Vec<_> = SwitchStrategy::ALL
+                    .iter()
+                    .map(|s| format!("{} - {}", s, s.description()))
+                    .collect();
+                let default_index = SwitchStrategy::ALL
+                    .iter()
+                    .position(|s| *s == existing_profile.strategy)
+                    .unwrap_or(0);
+                let selection = Select::new()
+                    .with_prompt("Switch strategy")
+                    .items(&items)
+                    .default(default_index)
+                    .interact()?;
+                SwitchStrategy::ALL[selection]
             }
         };
 
         validate_email(&email)?;
         validate_ssh_host(&ssh_host)?;
 
-        // Backup configuration file
-        backup_config_file(&config.path)?;
-
         let profile = crate::config::Profile {
             name,
             email,
             ssh_host,
+            strategy,
+            url_patterns: self
+                .url_patterns
+                .clone()
+                .unwrap_or(existing_profile.url_patterns),
+            transport: self.transport.unwrap_or(existing_profile.transport),
+            https_user: self.https_user(existing_profile.https_user),
+            https_host: self.https_host(existing_profile.https_host),
         };
 
-        config.update_profile(profile_to_update, profile)?;
+        config.update_profile(profile_to_update, profile.clone())?;
+        refresh_fragments(config)?;
+
+        // URL rules follow the profile's host and patterns.
+        let mut document = load_global_config()?;
+        if has_url_rules(&document, profile_to_update) {
+            sync_url_rules(&mut document, profile_to_update, &profile)?;
+            document.save(&config.backup_store())?;
+        }
 
         info!("Profile update completed: {}", profile_to_update);
         println!(
@@ -200,3 +213,13 @@ impl UpdateCommand {
         Ok(())
     }
 }
+
+/// `flag` when given, with an empty value clearing the setting, otherwise
+/// `current`.
+fn replace_or_clear(flag: &Option<String>, current: Option<String>) -> Option<String> {
+    match flag {
+        Some(value) if value.is_empty() => None,
+        Some(value) => Some(value.clone()),
+        None => current,
+    }
+}
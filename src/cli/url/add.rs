@@ -0,0 +1,57 @@
+use clap::Parser;
+use colored::*;
+
+use crate::cli::dir::load_global_config;
+use crate::config::Config;
+use crate::error::GuseError;
+
+use super::{check_git_version, sync_url_rules, url_patterns};
+
+#[derive(Parser, Debug)]
+#[command(about = "Use a profile in every repository whose remote matches its URL patterns")]
+pub struct AddCommand {
+    /// Name of the profile
+    #[arg(help = "Name of the profile (e.g., work)")]
+    pub profile: String,
+}
+
+impl AddCommand {
+    pub fn execute(&self, config: &Config) -> Result<(), GuseError> {
+        let profiles = config.load_profiles()?;
+        let Some(profile) = profiles.get(&self.profile) else {
+            println!(
+                "{}",
+                format!("❌ Profile '{}' not found.", self.profile)
+                    .red()
+                    .bold()
+            );
+            return Ok(());
+        };
+        check_git_version()?;
+
+        let mut document = load_global_config()?;
+        let taken = sync_url_rules(&mut document, &self.profile, profile)?;
+        document.save(&config.backup_store())?;
+
+        for (pattern, other) in taken.iter().filter(|(_, other)| *other != self.profile) {
+            println!(
+                "{} {}",
+                "⚠️".yellow().bold(),
+                format!("'{}' was used by profile '{}'.", pattern, other).yellow()
+            );
+        }
+        println!(
+            "{}",
+            format!(
+                "✅ Repositories with a remote matching these patterns now use profile '{}':",
+                self.profile
+            )
+            .green()
+            .bold()
+        );
+        for pattern in url_patterns(profile) {
+            println!("  {}", pattern.cyan());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,46 @@
+use clap::Parser;
+use colored::*;
+use prettytable::*;
+
+use crate::cli::dir::load_global_config;
+use crate::config::Config;
+use crate::error::GuseError;
+
+#[derive(Parser, Debug)]
+#[command(about = "Show URL patterns with a profile")]
+pub struct ListCommand;
+
+impl ListCommand {
+    pub fn execute(&self, config: &Config) -> Result<(), GuseError> {
+        let document = load_global_config()?;
+        let rules: Vec<_> = document
+            .rules
+            .iter()
+            .filter_map(|rule| rule.url_pattern().map(|pattern| (pattern, rule)))
+            .collect();
+        if rules.is_empty() {
+            println!("{}", "❌ No URL rules configured.".red().bold());
+            return Ok(());
+        }
+
+        let profiles = config.load_profiles()?;
+
+        println!("\n{}", "🔗 URL rules:".cyan().bold());
+        println!("{}", "=".repeat(40).cyan());
+
+        let mut table = Table::new();
+        table.add_row(row!["Remote URL", "Profile"]);
+        for (pattern, rule) in rules {
+            let profile = rule.profile().unwrap_or_default();
+            let profile = if profiles.contains_key(&profile) && rule.path.is_file() {
+                profile
+            } else {
+                format!("{} (missing)", profile)
+            };
+            table.add_row(row![pattern, profile]);
+        }
+        table.printstd();
+        println!();
+        Ok(())
+    }
+}
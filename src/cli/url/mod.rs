@@ -0,0 +1,141 @@
+pub mod add;
+pub mod list;
+pub mod remove;
+
+use clap::Parser;
+use std::fs;
+
+use crate::config::{Config, Profile, Transport};
+use crate::error::GuseError;
+use crate::git;
+use crate::git::include::{
+    fragment_profile, hasconfig_condition, host_url_patterns, https_url_patterns, IncludeDocument,
+    IncludeRule, HASCONFIG_MIN_VERSION,
+};
+
+use super::dir::write_fragment;
+
+#[derive(Parser, Debug)]
+#[command(about = "Pick a profile's identity from the repository's remote URL")]
+pub struct UrlCommand {
+    #[command(subcommand)]
+    pub command: UrlCommands,
+}
+
+#[derive(Parser, Debug)]
+pub enum UrlCommands {
+    #[command(
+        name = "add",
+        about = "Use a profile in every repository whose remote matches its URL patterns"
+    )]
+    Add(add::AddCommand),
+
+    #[command(name = "list", about = "Show URL patterns with a profile")]
+    List(list::ListCommand),
+
+    #[command(name = "remove", about = "Remove a profile's URL rules")]
+    Remove(remove::RemoveCommand),
+}
+
+impl UrlCommand {
+    pub fn execute(&self, config: &Config) -> Result<(), GuseError> {
+        match &self.command {
+            UrlCommands::Add(cmd) => cmd.execute(config),
+            UrlCommands::List(cmd) => cmd.execute(config),
+            UrlCommands::Remove(cmd) => cmd.execute(config),
+        }
+    }
+}
+
+/// Patterns selecting `profile`: those for its SSH host alias, then for
+/// HTTPS remotes on its `https_host`, or on the alias itself, if the profile
+/// uses HTTPS, then the ones it declares. The alias's `HostName` is not
+/// used, as it may only serve SSH.
+pub fn url_patterns(profile: &Profile) -> Vec<String> {
+    let mut patterns = host_url_patterns(&profile.ssh_host);
+    if profile.transport == Transport::Https {
+        let host = profile.https_host.as_deref().unwrap_or(&profile.ssh_host);
+        patterns.extend(https_url_patterns(host, profile.https_user.as_deref()));
+    }
+    for pattern in &profile.url_patterns {
+        if !patterns.contains(pattern) {
+            patterns.push(pattern.clone());
+        }
+    }
+    patterns
+}
+
+/// Fails unless the installed git understands `hasconfig:remote.*.url:`.
+pub fn check_git_version() -> Result<(), GuseError> {
+    let version = git::version()?;
+    if version < HASCONFIG_MIN_VERSION {
+        return Err(GuseError::GitError(format!(
+            "URL rules need Git {} or newer, but Git {} is installed; older versions ignore them. Use `guse dir add` instead.",
+            HASCONFIG_MIN_VERSION, version
+        )));
+    }
+    Ok(())
+}
+
+/// Whether `profile_name` has URL rules in `document`.
+pub fn has_url_rules(document: &IncludeDocument, profile_name: &str) -> bool {
+    document
+        .rules
+        .iter()
+        .any(|r| r.url_pattern().is_some() && r.profile().as_deref() == Some(profile_name))
+}
+
+/// Replaces the URL rules of `profile_name` with ones for its current
+/// patterns. Returns patterns taken over from other profiles, with the
+/// profile that had them.
+pub fn sync_url_rules(
+    document: &mut IncludeDocument,
+    profile_name: &str,
+    profile: &Profile,
+) -> Result<Vec<(String, String)>, GuseError> {
+    let fragment = write_fragment(profile_name, profile)?;
+    document.rules.retain(|r| {
+        r.url_pattern().is_none() || fragment_profile(&r.path).as_deref() != Some(profile_name)
+    });
+
+    let mut taken = Vec::new();
+    for pattern in url_patterns(profile) {
+        let condition = hasconfig_condition(&pattern);
+        if let Some(other) = document
+            .rules
+            .iter()
+            .find(|r| r.condition == condition)
+            .and_then(|r| r.profile())
+        {
+            taken.push((pattern.clone(), other));
+        }
+        document.set_rule(IncludeRule {
+            condition,
+            path: fragment.clone(),
+        });
+    }
+    Ok(taken)
+}
+
+/// Removes the URL rules of `profile_name`, deleting its fragment when no
+/// other rule uses it. Returns the patterns removed.
+pub fn remove_url_rules(
+    document: &mut IncludeDocument,
+    profile_name: &str,
+) -> Result<Vec<String>, GuseError> {
+    let (removed, kept): (Vec<_>, Vec<_>) = document
+        .rules
+        .drain(..)
+        .partition(|r| r.url_pattern().is_some() && r.profile().as_deref() == Some(profile_name));
+    document.rules = kept;
+
+    for rule in &removed {
+        if !document.rules.iter().any(|r| r.path == rule.path) && rule.path.exists() {
+            fs::remove_file(&rule.path)?;
+        }
+    }
+    Ok(removed
+        .iter()
+        .filter_map(|r| r.url_pattern().map(str::to_string))
+        .collect())
+}
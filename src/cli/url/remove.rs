@@ -0,0 +1,44 @@
+use clap::Parser;
+use colored::*;
+
+use crate::cli::dir::load_global_config;
+use crate::config::Config;
+use crate::error::GuseError;
+
+use super::remove_url_rules;
+
+#[derive(Parser, Debug)]
+#[command(about = "Remove a profile's URL rules")]
+pub struct RemoveCommand {
+    /// Name of the profile
+    #[arg(help = "Name of the profile whose URL rules to remove (e.g., work)")]
+    pub profile: String,
+}
+
+impl RemoveCommand {
+    pub fn execute(&self, config: &Config) -> Result<(), GuseError> {
+        let mut document = load_global_config()?;
+        let removed = remove_url_rules(&mut document, &self.profile)?;
+        if removed.is_empty() {
+            println!(
+                "{}",
+                format!("❌ Profile '{}' has no URL rules.", self.profile)
+                    .red()
+                    .bold()
+            );
+            return Ok(());
+        }
+        document.save(&config.backup_store())?;
+
+        println!(
+            "{}",
+            format!("✅ Removed URL rules of profile '{}':", self.profile)
+                .green()
+                .bold()
+        );
+        for pattern in removed {
+            println!("  {}", pattern.cyan());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,218 @@
+//! `extends` support: a profile may leave fields out and take them from the
+//! profile it extends, which may extend another one in turn. Entries that
+//! remain incomplete after resolution act as templates: they can be extended
+//! but are not offered for switching.
+
+use super::{
+    ConfigError, ConfigFile, Profile, ProfileEntry, ProfileMap, SwitchStrategy, Transport,
+};
+
+/// A resolved value and the profile it was taken from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resolved<T> {
+    pub value: T,
+    pub source: String,
+}
+
+/// The effective values of a profile, each with its origin.
+#[derive(Debug, Clone)]
+pub struct ResolvedProfile {
+    /// The profile itself followed by its ancestors.
+    pub chain: Vec<String>,
+    pub name: Option<Resolved<String>>,
+    pub email: Option<Resolved<String>>,
+    pub ssh_host: Option<Resolved<String>>,
+    /// `None` when no profile in the chain sets it, meaning the default.
+    pub strategy: Option<Resolved<SwitchStrategy>>,
+    /// `None` when no profile in the chain sets it, meaning no patterns.
+    pub url_patterns: Option<Resolved<Vec<String>>>,
+    /// `None` when no profile in the chain sets it, meaning SSH.
+    pub transport: Option<Resolved<Transport>>,
+    pub https_user: Option<Resolved<String>>,
+    pub https_host: Option<Resolved<String>>,
+}
+
+impl ResolvedProfile {
+    /// The complete profile, or `None` for a template with missing fields.
+    pub fn to_profile(&self) -> Option<Profile> {
+        Some(Profile {
+            name: self.name.as_ref()?.value.clone(),
+            email: self.email.as_ref()?.value.clone(),
+            ssh_host: self.ssh_host.as_ref()?.value.clone(),
+            strategy: self.strategy.as_ref().map(|s| s.value).unwrap_or_default(),
+            url_patterns: self
+                .url_patterns
+                .as_ref()
+                .map(|p| p.value.clone())
+                .unwrap_or_default(),
+            transport: self.transport.as_ref().map(|t| t.value).unwrap_or_default(),
+            https_user: self.https_user.as_ref().map(|u| u.value.clone()),
+            https_host: self.https_host.as_ref().map(|h| h.value.clone()),
+        })
+    }
+
+    /// Names of the required fields no profile in the chain sets.
+    pub fn missing_fields(&self) -> Vec<&'static str> {
+        let mut missing = Vec::new();
+        if self.name.is_none() {
+            missing.push("name");
+        }
+        if self.email.is_none() {
+            missing.push("email");
+        }
+        if self.ssh_host.is_none() {
+            missing.push("ssh_host");
+        }
+        missing
+    }
+}
+
+impl ConfigFile {
+    /// `name` followed by the profiles it extends, nearest first.
+    pub fn chain(&self, name: &str) -> Result<Vec<String>, ConfigError> {
+        let mut chain: Vec<String> = Vec::new();
+        let mut current = name.to_string();
+        loop {
+            let entry = self
+                .profiles
+                .get(&current)
+                .ok_or_else(|| match chain.last() {
+                    Some(child) => ConfigError(format!(
+                        "Profile '{}' extends '{}', which does not exist.",
+                        child, current
+                    )),
+                    None => ConfigError(format!("Profile '{}' does not exist.", current)),
+                })?;
+            if chain.contains(&current) {
+                chain.push(current);
+                return Err(ConfigError(format!(
+                    "Profile inheritance cycle: {}",
+                    chain.join(" -> ")
+                )));
+            }
+            chain.push(current);
+            match &entry.extends {
+                Some(parent) => current = parent.clone(),
+                None => return Ok(chain),
+            }
+        }
+    }
+
+    pub fn resolve(&self, name: &str) -> Result<ResolvedProfile, ConfigError> {
+        let chain = self.chain(name)?;
+        let entries: Vec<(&String, &ProfileEntry)> =
+            chain.iter().map(|n| (n, &self.profiles[n])).collect();
+
+        fn pick<T: Clone>(
+            entries: &[(&String, &ProfileEntry)],
+            field: impl Fn(&ProfileEntry) -> Option<&T>,
+        ) -> Option<Resolved<T>> {
+            entries.iter().find_map(|(name, entry)| {
+                field(entry).map(|value| Resolved {
+                    value: value.clone(),
+                    source: name.to_string(),
+                })
+            })
+        }
+
+        Ok(ResolvedProfile {
+            name: pick(&entries, |e| e.name.as_ref()),
+            email: pick(&entries, |e| e.email.as_ref()),
+            ssh_host: pick(&entries, |e| e.ssh_host.as_ref()),
+            strategy: pick(&entries, |e| e.strategy.as_ref()),
+            url_patterns: pick(&entries, |e| e.url_patterns.as_ref()),
+            transport: pick(&entries, |e| e.transport.as_ref()),
+            https_user: pick(&entries, |e| e.https_user.as_ref()),
+            https_host: pick(&entries, |e| e.https_host.as_ref()),
+            chain,
+        })
+    }
+
+    /// Every complete profile with inheritance applied. Templates are left
+    /// out, and so are profiles whose `extends` chain is broken, so that one
+    /// bad entry does not stop the others from working. See
+    /// [`ConfigFile::broken_profiles`] for reporting the skipped ones.
+    pub fn resolved_profiles(&self) -> ProfileMap {
+        let mut names: Vec<&String> = self.profiles.keys().collect();
+        names.sort();
+
+        let mut profiles = ProfileMap::new();
+        for name in names {
+            match self.resolve(name).map(|r| r.to_profile()) {
+                Ok(Some(profile)) => {
+                    profiles.insert(name.clone(), profile);
+                }
+                Ok(None) => log::info!(
+                    "Profile '{}' is incomplete; treating it as a template",
+                    name
+                ),
+                Err(e) => log::warn!("Skipping profile '{}': {}", name, e),
+            }
+        }
+        profiles
+    }
+
+    /// The profiles whose `extends` chain cannot be resolved, with the reason,
+    /// in name order.
+    pub fn broken_profiles(&self) -> Vec<(String, ConfigError)> {
+        let mut names: Vec<&String> = self.profiles.keys().collect();
+        names.sort();
+
+        names
+            .into_iter()
+            .filter_map(|name| self.resolve(name).err().map(|e| (name.clone(), e)))
+            .collect()
+    }
+
+    /// Stores `profile` under `name`. An existing `extends` is kept, and only
+    /// the values that differ from what would be inherited are written.
+    pub fn set_profile(&mut self, name: &str, profile: Profile) -> Result<(), ConfigError> {
+        let extends = self.profiles.get(name).and_then(|e| e.extends.clone());
+        let Some(parent) = &extends else {
+            self.profiles
+                .insert(name.to_string(), ProfileEntry::from(profile));
+            return Ok(());
+        };
+
+        let inherited = self.resolve(parent)?;
+        let own = |value: String, inherited: &Option<Resolved<String>>| {
+            (inherited.as_ref().map(|r| &r.value) != Some(&value)).then_some(value)
+        };
+        let inherited_strategy = inherited
+            .strategy
+            .as_ref()
+            .map(|s| s.value)
+            .unwrap_or_default();
+        let inherited_patterns = inherited
+            .url_patterns
+            .as_ref()
+            .map(|p| p.value.clone())
+            .unwrap_or_default();
+        let inherited_transport = inherited
+            .transport
+            .as_ref()
+            .map(|t| t.value)
+            .unwrap_or_default();
+        let inherited_https_user = inherited.https_user.as_ref().map(|u| &u.value);
+        let inherited_https_host = inherited.https_host.as_ref().map(|h| &h.value);
+
+        let entry = ProfileEntry {
+            name: own(profile.name, &inherited.name),
+            email: own(profile.email, &inherited.email),
+            ssh_host: own(profile.ssh_host, &inherited.ssh_host),
+            strategy: (profile.strategy != inherited_strategy).then_some(profile.strategy),
+            url_patterns: (profile.url_patterns != inherited_patterns)
+                .then_some(profile.url_patterns),
+            transport: (profile.transport != inherited_transport).then_some(profile.transport),
+            https_user: profile
+                .https_user
+                .filter(|user| inherited_https_user != Some(user)),
+            https_host: profile
+                .https_host
+                .filter(|host| inherited_https_host != Some(host)),
+            extends,
+        };
+        self.profiles.insert(name.to_string(), entry);
+        Ok(())
+    }
+}
@@ -1,10 +1,18 @@
-use lazy_static::lazy_static;
+pub mod inherit;
+pub mod pin;
+pub mod state;
+pub mod transfer;
+
+use colored::*;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::ffi::OsString;
 use std::fs;
-use std::path::PathBuf;
-use std::sync::Mutex;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::path::{Path, PathBuf};
+
+use crate::backup::{BackupError, BackupKind, BackupStore, DEFAULT_RETENTION};
+use crate::utils::file::{atomic_write, FileLock};
+use state::State;
 
 #[derive(Debug)]
 pub struct ConfigError(pub String);
@@ -23,97 +31,528 @@ impl From<std::io::Error> for ConfigError {
     }
 }
 
+impl From<BackupError> for ConfigError {
+    fn from(err: BackupError) -> Self {
+        ConfigError(err.to_string())
+    }
+}
+
 impl From<toml::ser::Error> for ConfigError {
     fn from(err: toml::ser::Error) -> Self {
         ConfigError(format!("TOML Serialization Error: {}", err))
     }
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+/// How `guse switch` makes Git use the profile's SSH key.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum SwitchStrategy {
+    /// Rewrite the remote URL to `git@<ssh_host>:user/repo.git`.
+    #[default]
+    Alias,
+    /// Set `core.sshCommand` to the host's key and leave the remote URL alone.
+    SshCommand,
+}
+
+impl SwitchStrategy {
+    pub const ALL: [SwitchStrategy; 2] = [SwitchStrategy::Alias, SwitchStrategy::SshCommand];
+
+    pub fn is_alias(&self) -> bool {
+        *self == SwitchStrategy::Alias
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            SwitchStrategy::Alias => "Rewrite remote URL to the SSH host alias",
+            SwitchStrategy::SshCommand => "Set core.sshCommand, keep the remote URL",
+        }
+    }
+}
+
+impl std::fmt::Display for SwitchStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SwitchStrategy::Alias => write!(f, "alias"),
+            SwitchStrategy::SshCommand => write!(f, "ssh-command"),
+        }
+    }
+}
+
+/// How the profile reaches its remotes.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Transport {
+    /// SSH through the profile's `ssh_host`.
+    #[default]
+    Ssh,
+    /// HTTPS, with `https_user` as the login embedded in the URL.
+    Https,
+}
+
+impl Transport {
+    pub fn is_ssh(&self) -> bool {
+        *self == Transport::Ssh
+    }
+}
+
+impl std::fmt::Display for Transport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Transport::Ssh => write!(f, "ssh"),
+            Transport::Https => write!(f, "https"),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct Profile {
     pub name: String,
     pub email: String,
     pub ssh_host: String,
+    #[serde(default, skip_serializing_if = "SwitchStrategy::is_alias")]
+    pub strategy: SwitchStrategy,
+    /// Remote URL patterns, besides those for `ssh_host`, that select this
+    /// profile through `guse url`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub url_patterns: Vec<String>,
+    #[serde(default, skip_serializing_if = "Transport::is_ssh")]
+    pub transport: Transport,
+    /// The login put into HTTPS remote URLs, as in `https://user@host/...`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub https_user: Option<String>,
+    /// The host HTTPS remotes go to when it is not the remote's own host,
+    /// e.g. `github.com` for remotes on an SSH alias.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub https_host: Option<String>,
 }
 
+/// Profiles with every field resolved, keyed by profile name.
 pub type ProfileMap = HashMap<String, Profile>;
 
-lazy_static! {
-    static ref CONFIG_LOCK: Mutex<()> = Mutex::new(());
+/// A profile as written in the configuration file. Fields that are left out
+/// are inherited from the profile named by `extends`; see [`inherit`].
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct ProfileEntry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssh_host: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<SwitchStrategy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url_patterns: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transport: Option<Transport>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub https_user: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub https_host: Option<String>,
+}
+
+impl From<Profile> for ProfileEntry {
+    fn from(profile: Profile) -> Self {
+        Self {
+            extends: None,
+            name: Some(profile.name),
+            email: Some(profile.email),
+            ssh_host: Some(profile.ssh_host),
+            strategy: (!profile.strategy.is_alias()).then_some(profile.strategy),
+            url_patterns: (!profile.url_patterns.is_empty()).then_some(profile.url_patterns),
+            transport: (!profile.transport.is_ssh()).then_some(profile.transport),
+            https_user: profile.https_user,
+            https_host: profile.https_host,
+        }
+    }
+}
+
+/// Schema version written to new files. Bump it when a change would make
+/// older guse releases misread the file.
+pub const CONFIG_VERSION: u32 = 1;
+
+/// File name used before the XDG location was adopted.
+pub const LEGACY_CONFIG_FILE: &str = ".guse-profiles.toml";
+
+/// Picks the configuration file, first match wins:
+///
+/// 1. the `--config` flag
+/// 2. `$GUSE_CONFIG`
+/// 3. `$XDG_CONFIG_HOME/guse/config.toml` (`~/.config/guse/config.toml` when unset)
+///    if it exists
+/// 4. `~/.guse-profiles.toml` if it exists, for installs that predate step 3
+/// 5. the XDG path from step 3, for new installs
+pub fn config_path(
+    flag: Option<&Path>,
+    env: Option<OsString>,
+    xdg_config_home: Option<OsString>,
+    home: Option<PathBuf>,
+) -> Option<PathBuf> {
+    let expand = |p: &Path| PathBuf::from(shellexpand::tilde(&p.to_string_lossy()).to_string());
+
+    if let Some(path) = flag {
+        return Some(expand(path));
+    }
+    if let Some(path) = env.filter(|p| !p.is_empty()) {
+        return Some(expand(Path::new(&path)));
+    }
+
+    // Relative XDG values are invalid per the spec and ignored.
+    let xdg = xdg_config_home
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| home.as_ref().map(|h| h.join(".config")))
+        .map(|dir| dir.join("guse").join("config.toml"));
+    if xdg.as_ref().is_some_and(|p| p.exists()) {
+        return xdg;
+    }
+
+    let legacy = home.map(|h| h.join(LEGACY_CONFIG_FILE));
+    if legacy.as_ref().is_some_and(|p| p.exists()) {
+        return legacy;
+    }
+    xdg
+}
+
+/// Options that apply to all profiles.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct Settings {
+    /// Profiles listed first, in this order. The rest follow alphabetically.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub order: Vec<String>,
+    /// Profile pickers start on when none was used in the repository yet.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_profile: Option<String>,
+    /// Backups kept per file; [`DEFAULT_RETENTION`] when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backup_retention: Option<usize>,
+}
+
+impl Settings {
+    /// Sorts `profiles` by [`Settings::order`], then by name.
+    pub fn sort(&self, profiles: ProfileMap) -> Vec<(String, Profile)> {
+        let mut profiles: Vec<_> = profiles.into_iter().collect();
+        profiles.sort_by(|(a, _), (b, _)| self.compare(a, b));
+        profiles
+    }
+
+    /// Sorts profile names the same way as [`Settings::sort`].
+    pub fn sort_names(&self, names: &mut [String]) {
+        names.sort_by(|a, b| self.compare(a, b));
+    }
+
+    fn compare(&self, a: &str, b: &str) -> std::cmp::Ordering {
+        let rank = |name: &str| {
+            self.order
+                .iter()
+                .position(|n| n == name)
+                .unwrap_or(self.order.len())
+        };
+        rank(a).cmp(&rank(b)).then(a.cmp(b))
+    }
+}
+
+/// Everything stored in the configuration file.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ConfigFile {
+    pub version: u32,
+    #[serde(default)]
+    pub settings: Settings,
+    #[serde(default)]
+    pub profiles: HashMap<String, ProfileEntry>,
+}
+
+impl Default for ConfigFile {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            settings: Settings::default(),
+            profiles: HashMap::new(),
+        }
+    }
+}
+
+impl ConfigFile {
+    /// Parses the versioned layout, or the legacy one where profiles were the
+    /// top-level tables. The flag is `true` for the legacy layout. Only an
+    /// integer `version` marks a versioned file, so a legacy profile named
+    /// `version` is still read as one.
+    pub fn parse(contents: &str) -> Result<(Self, bool), ConfigError> {
+        let parse_error = |e: toml::de::Error| ConfigError(format!("TOML Parsing Error: {}", e));
+        let table: toml::Table = toml::from_str(contents).map_err(parse_error)?;
+
+        let Some(version) = table.get("version").and_then(|v| v.as_integer()) else {
+            let profiles = table.try_into().map_err(parse_error)?;
+            return Ok((
+                Self {
+                    profiles,
+                    ..Default::default()
+                },
+                true,
+            ));
+        };
+
+        match version {
+            v if v > CONFIG_VERSION as i64 => Err(ConfigError(format!(
+                "written by a newer guse (schema version {}, this release supports up to {}). Please upgrade guse.",
+                v, CONFIG_VERSION
+            ))),
+            v if v >= 1 => Ok((table.try_into().map_err(parse_error)?, false)),
+            v => Err(ConfigError(format!("Invalid configuration version: {}", v))),
+        }
+    }
 }
 
 pub struct Config {
     pub path: PathBuf,
+    pub backups: BackupStore,
+    /// Where usage history is kept; see [`state`].
+    pub state: PathBuf,
 }
 
 impl Config {
-    pub fn new() -> Self {
-        let path = dirs::home_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join(".guse-profiles.toml");
-        Self { path }
+    /// Uses the file found by [`config_path`], with `flag` being the value of
+    /// the global `--config` option.
+    pub fn resolve(flag: Option<&Path>) -> Result<Self, ConfigError> {
+        let path = config_path(
+            flag,
+            std::env::var_os("GUSE_CONFIG"),
+            std::env::var_os("XDG_CONFIG_HOME"),
+            dirs::home_dir(),
+        )
+        .ok_or_else(|| {
+            ConfigError(
+                "Cannot determine where to store the configuration. Set GUSE_CONFIG or pass --config.".to_string(),
+            )
+        })?;
+        log::info!("Using configuration file {}", path.display());
+        // A file chosen explicitly keeps its backups beside it, apart from
+        // those of the usual configuration.
+        let explicit =
+            flag.is_some() || std::env::var_os("GUSE_CONFIG").is_some_and(|p| !p.is_empty());
+        let backups = if explicit {
+            BackupStore::at(path.with_extension("backups"))
+        } else {
+            BackupStore::new()
+        };
+        Ok(Self {
+            path,
+            backups,
+            state: State::default_path(),
+        })
     }
 
-    pub fn load_profiles(&self) -> Result<ProfileMap, ConfigError> {
+    /// The backup store, keeping as many backups per file as
+    /// [`Settings::backup_retention`] asks for. The default applies while the
+    /// configuration cannot be read, so that a broken file can be restored.
+    pub fn backup_store(&self) -> BackupStore {
+        let retention = match self.load() {
+            Ok(file) => file.settings.backup_retention,
+            Err(e) => {
+                log::warn!("Using the default backup retention: {}", e);
+                None
+            }
+        };
+        self.backups_with(retention)
+    }
+
+    fn backups_with(&self, retention: Option<usize>) -> BackupStore {
+        BackupStore {
+            // Keeping none would drop the copy just taken.
+            retention: retention.unwrap_or(DEFAULT_RETENTION).max(1),
+            ..self.backups.clone()
+        }
+    }
+
+    /// Reads the configuration file. A file in the legacy layout is rewritten
+    /// in the current one, after backing it up.
+    pub fn load(&self) -> Result<ConfigFile, ConfigError> {
         if !self.path.exists() {
-            return Ok(HashMap::new());
+            return Ok(ConfigFile::default());
         }
 
-        let contents = fs::read_to_string(&self.path)
-            .map_err(|e| ConfigError(format!("Cannot read configuration file: {}", e)))?;
+        let (file, legacy) = self.read()?;
+        if !legacy {
+            return Ok(file);
+        }
+
+        let _lock = self.lock()?;
+        // Another process may have migrated it while we waited for the lock.
+        let (file, legacy) = self.read()?;
+        if legacy {
+            self.save(&file)?;
+            log::info!(
+                "Migrated {} to schema version {}",
+                self.path.display(),
+                CONFIG_VERSION
+            );
+            println!(
+                "{} {}",
+                "🔁".blue(),
+                format!(
+                    "Migrated {} to the versioned format (schema version {}). The previous file was backed up; see `guse backup list`.",
+                    self.path.display(),
+                    CONFIG_VERSION
+                )
+                .blue()
+            );
+        }
+        Ok(file)
+    }
+
+    pub fn load_profiles(&self) -> Result<ProfileMap, ConfigError> {
+        Ok(self.load()?.resolved_profiles())
+    }
 
-        toml::from_str(&contents).map_err(|e| ConfigError(format!("TOML Parsing Error: {}", e)))
+    /// Resolved profiles in display order, with the settings that ordered them.
+    pub fn load_sorted(&self) -> Result<(Vec<(String, Profile)>, Settings), ConfigError> {
+        let file = self.load()?;
+        let profiles = file.settings.sort(file.resolved_profiles());
+        Ok((profiles, file.settings))
     }
 
-    pub fn save_profiles(&self, profiles: &ProfileMap) -> Result<(), ConfigError> {
-        self.backup()?;
+    pub fn load_state(&self) -> State {
+        State::load(&self.state)
+    }
 
-        let _lock = CONFIG_LOCK
-            .lock()
-            .map_err(|_| ConfigError("Failed to acquire configuration file lock".to_string()))?;
+    /// Remembers a switch to `profile`, in `repository` when there is one.
+    pub fn record_switch(
+        &self,
+        repository: Option<&Path>,
+        profile: &str,
+    ) -> Result<(), ConfigError> {
+        State::record(&self.state, repository, profile)
+    }
 
-        let updated = toml::to_string_pretty(profiles)?;
-        fs::write(&self.path, updated)?;
+    /// Resolved profiles from file contents in either layout, without migrating.
+    pub fn parse_profiles(contents: &str) -> Result<ProfileMap, ConfigError> {
+        Ok(ConfigFile::parse(contents)?.0.resolved_profiles())
+    }
+
+    /// Takes the cross-process lock guarding read-modify-write cycles on the
+    /// configuration file.
+    pub fn lock(&self) -> Result<FileLock, ConfigError> {
+        if let Some(lock) = FileLock::try_acquire(&self.path)? {
+            return Ok(lock);
+        }
+        log::info!("Waiting for another guse process to release the configuration lock");
+        Ok(FileLock::acquire(&self.path)?)
+    }
+
+    /// Writes `file` atomically in the current layout. Callers that loaded it
+    /// first should hold [`Config::lock`] across both steps.
+    pub fn save(&self, file: &ConfigFile) -> Result<(), ConfigError> {
+        self.backups_with(file.settings.backup_retention)
+            .create(BackupKind::Profiles, &self.path)?;
+
+        let file = ConfigFile {
+            version: CONFIG_VERSION,
+            ..file.clone()
+        };
+        let updated = toml::to_string_pretty(&file)?;
+        atomic_write(&self.path, updated)?;
 
         Ok(())
     }
 
     pub fn add_profile(&self, name: String, profile: Profile) -> Result<(), ConfigError> {
-        let mut profiles = self.load_profiles()?;
-        profiles.insert(name, profile);
-        self.save_profiles(&profiles)?;
-        Ok(())
+        self.modify(|file| file.set_profile(&name, profile))
     }
 
     pub fn update_profile(&self, name: &str, profile: Profile) -> Result<(), ConfigError> {
-        let mut profiles = self.load_profiles()?;
-        if !profiles.contains_key(name) {
-            return Err(ConfigError(format!("Profile '{}' does not exist.", name)));
-        }
-        profiles.insert(name.to_string(), profile);
-        self.save_profiles(&profiles)?;
-        Ok(())
+        self.modify(|file| {
+            if !file.profiles.contains_key(name) {
+                return Err(ConfigError(format!("Profile '{}' does not exist.", name)));
+            }
+            file.set_profile(name, profile)
+        })
     }
 
     pub fn delete_profile(&self, name: &str) -> Result<(), ConfigError> {
-        let mut profiles = self.load_profiles()?;
-        if !profiles.contains_key(name) {
-            return Err(ConfigError(format!("Profile '{}' does not exist.", name)));
-        }
-        profiles.remove(name);
-        self.save_profiles(&profiles)?;
-        Ok(())
+        self.modify(|file| {
+            if !file.profiles.contains_key(name) {
+                return Err(ConfigError(format!("Profile '{}' does not exist.", name)));
+            }
+            let mut children: Vec<&str> = file
+                .profiles
+                .iter()
+                .filter(|(_, entry)| entry.extends.as_deref() == Some(name))
+                .map(|(child, _)| child.as_str())
+                .collect();
+            if !children.is_empty() {
+                children.sort();
+                return Err(ConfigError(format!(
+                    "Profile '{}' is extended by {}; change those profiles first.",
+                    name,
+                    children.join(", ")
+                )));
+            }
+            file.profiles.remove(name);
+            file.settings.order.retain(|n| n != name);
+            if file.settings.default_profile.as_deref() == Some(name) {
+                file.settings.default_profile = None;
+            }
+            Ok(())
+        })
     }
 
-    fn backup(&self) -> Result<(), ConfigError> {
-        if self.path.exists() {
-            let timestamp = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs();
-            let backup_path = self.path.with_extension(format!("toml.{}", timestamp));
-            fs::copy(&self.path, backup_path)?;
-        }
-        Ok(())
+    /// Sets [`Settings::default_profile`], or clears it with `None`.
+    pub fn set_default_profile(&self, name: Option<&str>) -> Result<(), ConfigError> {
+        self.modify(|file| {
+            if let Some(name) = name {
+                if !file.resolved_profiles().contains_key(name) {
+                    return Err(ConfigError(format!("Profile '{}' does not exist.", name)));
+                }
+            }
+            file.settings.default_profile = name.map(str::to_string);
+            Ok(())
+        })
+    }
+
+    /// Sets [`Settings::order`]. An empty list restores alphabetical order.
+    pub fn set_order(&self, names: Vec<String>) -> Result<(), ConfigError> {
+        self.modify(|file| {
+            let profiles = file.resolved_profiles();
+            for (i, name) in names.iter().enumerate() {
+                if !profiles.contains_key(name) {
+                    return Err(ConfigError(format!("Profile '{}' does not exist.", name)));
+                }
+                if names[..i].contains(name) {
+                    return Err(ConfigError(format!(
+                        "Profile '{}' is listed more than once.",
+                        name
+                    )));
+                }
+            }
+            file.settings.order = names;
+            Ok(())
+        })
+    }
+
+    /// Loads, changes and saves the file while holding the lock.
+    pub fn modify<F>(&self, change: F) -> Result<(), ConfigError>
+    where
+        F: FnOnce(&mut ConfigFile) -> Result<(), ConfigError>,
+    {
+        let _lock = self.lock()?;
+        let mut file = if self.path.exists() {
+            self.read()?.0
+        } else {
+            ConfigFile::default()
+        };
+        change(&mut file)?;
+        self.save(&file)
+    }
+
+    fn read(&self) -> Result<(ConfigFile, bool), ConfigError> {
+        let contents = fs::read_to_string(&self.path)
+            .map_err(|e| ConfigError(format!("Cannot read configuration file: {}", e)))?;
+        ConfigFile::parse(&contents)
+            .map_err(|e| ConfigError(format!("{}: {}", self.path.display(), e)))
     }
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,89 @@
+//! Pin files tie a repository to a profile. `.guse.toml` in the work tree
+//! can be committed and shared; `guse` inside the Git directory stays local
+//! to one clone and takes precedence.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::ConfigError;
+use crate::utils::file::atomic_write;
+
+/// Pin file at the top of the work tree.
+pub const PIN_FILE: &str = ".guse.toml";
+
+/// Pin file inside the Git directory, never committed.
+pub const GIT_DIR_PIN_FILE: &str = "guse";
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+struct PinFile {
+    profile: String,
+}
+
+/// The profile a repository is pinned to and the file that says so.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pin {
+    pub profile: String,
+    pub source: PathBuf,
+}
+
+impl Pin {
+    /// Looks for `<git_dir>/guse`, then `<work_tree>/.guse.toml`.
+    pub fn find(
+        work_tree: Option<&Path>,
+        git_dir: Option<&Path>,
+    ) -> Result<Option<Self>, ConfigError> {
+        let candidates = [
+            git_dir.map(|d| d.join(GIT_DIR_PIN_FILE)),
+            work_tree.map(|d| d.join(PIN_FILE)),
+        ];
+        for path in candidates.into_iter().flatten() {
+            if path.is_file() {
+                return Self::read(&path).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Removes both pin files, returning the paths that existed.
+    pub fn remove_all(
+        work_tree: Option<&Path>,
+        git_dir: Option<&Path>,
+    ) -> Result<Vec<PathBuf>, ConfigError> {
+        let candidates = [
+            git_dir.map(|d| d.join(GIT_DIR_PIN_FILE)),
+            work_tree.map(|d| d.join(PIN_FILE)),
+        ];
+        let mut removed = Vec::new();
+        for path in candidates.into_iter().flatten() {
+            if path.is_file() {
+                fs::remove_file(&path)?;
+                removed.push(path);
+            }
+        }
+        Ok(removed)
+    }
+
+    pub fn read(path: &Path) -> Result<Self, ConfigError> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| ConfigError(format!("Cannot read {}: {}", path.display(), e)))?;
+        let file: PinFile = toml::from_str(&contents)
+            .map_err(|e| ConfigError(format!("{}: {}", path.display(), e)))?;
+        Ok(Self {
+            profile: file.profile,
+            source: path.to_path_buf(),
+        })
+    }
+
+    /// Writes a pin to `profile` at `path`.
+    pub fn write(path: &Path, profile: &str) -> Result<Self, ConfigError> {
+        let file = PinFile {
+            profile: profile.to_string(),
+        };
+        atomic_write(path, toml::to_string(&file)?)?;
+        Ok(Self {
+            profile: file.profile,
+            source: path.to_path_buf(),
+        })
+    }
+}
@@ -0,0 +1,98 @@
+//! Usage history kept apart from the configuration file, so that switching
+//! profiles neither rewrites the user's file nor fills the backup store.
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::{ConfigError, Settings};
+use crate::utils::file::{atomic_write, FileLock};
+
+/// A switch to `profile` at `at`.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Usage {
+    pub profile: String,
+    pub at: DateTime<Local>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct State {
+    /// The most recent switch to each profile, in any repository.
+    #[serde(default)]
+    pub profiles: BTreeMap<String, DateTime<Local>>,
+    /// The most recent switch in each repository, keyed by its work tree.
+    #[serde(default)]
+    pub repositories: BTreeMap<String, Usage>,
+}
+
+impl State {
+    /// `$XDG_STATE_HOME/guse/state.toml`, or the data directory on platforms
+    /// without a state directory.
+    pub fn default_path() -> PathBuf {
+        dirs::state_dir()
+            .or_else(dirs::data_local_dir)
+            .map(|d| d.join("guse"))
+            .or_else(|| dirs::home_dir().map(|h| h.join(".guse")))
+            .unwrap_or_else(|| PathBuf::from(".guse"))
+            .join("state.toml")
+    }
+
+    /// Reads the state file. A missing or unreadable file yields an empty
+    /// state, as losing the history must not break any command.
+    pub fn load(path: &Path) -> Self {
+        let Ok(contents) = fs::read_to_string(path) else {
+            return Self::default();
+        };
+        toml::from_str(&contents).unwrap_or_else(|e| {
+            log::warn!("Ignoring unreadable state file {}: {}", path.display(), e);
+            Self::default()
+        })
+    }
+
+    /// Records a switch to `profile`, in `repository` when there is one.
+    pub fn record(
+        path: &Path,
+        repository: Option<&Path>,
+        profile: &str,
+    ) -> Result<(), ConfigError> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let _lock = FileLock::acquire(path)?;
+        let mut state = Self::load(path);
+        let at = Local::now();
+        state.profiles.insert(profile.to_string(), at);
+        if let Some(repository) = repository {
+            state.repositories.insert(
+                repository.to_string_lossy().to_string(),
+                Usage {
+                    profile: profile.to_string(),
+                    at,
+                },
+            );
+        }
+        atomic_write(path, toml::to_string_pretty(&state)?)?;
+        Ok(())
+    }
+
+    /// Index into `names` a profile picker starts on: the profile last used
+    /// in `repository`, then the configured default, then the first one.
+    /// Names that no longer exist are passed over.
+    pub fn preselect(
+        &self,
+        names: &[String],
+        repository: Option<&Path>,
+        settings: &Settings,
+    ) -> usize {
+        let last_used = repository
+            .and_then(|r| self.repositories.get(r.to_string_lossy().as_ref()))
+            .map(|usage| usage.profile.as_str());
+        [last_used, settings.default_profile.as_deref()]
+            .into_iter()
+            .flatten()
+            .find_map(|name| names.iter().position(|n| n == name))
+            .unwrap_or(0)
+    }
+}
@@ -0,0 +1,621 @@
+use crate::backup::BackupStore;
+use crate::config::pin::{Pin, GIT_DIR_PIN_FILE, PIN_FILE};
+use crate::config::state::State;
+use crate::config::{
+    Config, ConfigFile, Profile, ProfileMap, Settings, SwitchStrategy, Transport, CONFIG_VERSION,
+};
+use std::fs;
+
+const LEGACY: &str =
+    "[work]\nname = \"Work\"\nemail = \"work@example.com\"\nssh_host = \"github-work\"\n";
+
+fn profile(name: &str, strategy: SwitchStrategy) -> Profile {
+    Profile {
+        name: name.to_string(),
+        email: format!("{}@example.com", name.to_lowercase()),
+        ssh_host: format!("github-{}", name.to_lowercase()),
+        strategy,
+        url_patterns: Vec::new(),
+        transport: Transport::Ssh,
+        https_user: None,
+        https_host: None,
+    }
+}
+
+fn config_in(dir: &std::path::Path) -> Config {
+    Config {
+        path: dir.join("profiles.toml"),
+        backups: BackupStore::at(dir.join("backups")),
+        state: dir.join("state.toml"),
+    }
+}
+
+#[test]
+fn test_strategy_defaults_to_alias() {
+    let (file, legacy) = ConfigFile::parse(LEGACY).unwrap();
+    assert!(legacy);
+    let profiles = file.resolved_profiles();
+    assert_eq!(profiles["work"].strategy, SwitchStrategy::Alias);
+}
+
+#[test]
+fn test_strategy_round_trip() {
+    let mut file = ConfigFile::default();
+    file.set_profile("work", profile("Work", SwitchStrategy::SshCommand))
+        .unwrap();
+    file.set_profile("personal", profile("Me", SwitchStrategy::Alias))
+        .unwrap();
+
+    let serialized = toml::to_string_pretty(&file).unwrap();
+    assert!(serialized.contains("strategy = \"ssh-command\""));
+    // The default is left out so existing files stay unchanged.
+    assert_eq!(serialized.matches("strategy").count(), 1);
+
+    let (parsed, legacy) = ConfigFile::parse(&serialized).unwrap();
+    assert!(!legacy);
+    assert_eq!(parsed, file);
+}
+
+#[test]
+fn test_transport_round_trip() {
+    let mut file = ConfigFile::default();
+    file.set_profile(
+        "oss",
+        Profile {
+            transport: Transport::Https,
+            https_user: Some("me".to_string()),
+            https_host: Some("github.com".to_string()),
+            ..profile("OSS", SwitchStrategy::Alias)
+        },
+    )
+    .unwrap();
+    file.set_profile("work", profile("Work", SwitchStrategy::Alias))
+        .unwrap();
+
+    let serialized = toml::to_string_pretty(&file).unwrap();
+    assert!(serialized.contains("transport = \"https\""));
+    assert!(serialized.contains("https_user = \"me\""));
+    assert!(serialized.contains("https_host = \"github.com\""));
+    assert_eq!(serialized.matches("transport").count(), 1);
+
+    let (parsed, _) = ConfigFile::parse(&serialized).unwrap();
+    let profiles = parsed.resolved_profiles();
+    assert_eq!(profiles["oss"].transport, Transport::Https);
+    assert_eq!(profiles["work"].transport, Transport::Ssh);
+    assert_eq!(profiles["work"].https_user, None);
+    assert_eq!(profiles["oss"].https_host.as_deref(), Some("github.com"));
+    assert_eq!(profiles["work"].https_host, None);
+}
+
+#[test]
+fn test_versioned_layout() {
+    let contents = format!(
+        "version = {}\n\n[settings]\n\n[profiles.work]\nname = \"Work\"\nemail = \"work@example.com\"\nssh_host = \"github-work\"\n",
+        CONFIG_VERSION
+    );
+    let (file, legacy) = ConfigFile::parse(&contents).unwrap();
+    assert!(!legacy);
+    assert_eq!(file.profiles.len(), 1);
+    assert_eq!(
+        file.profiles["work"].ssh_host.as_deref(),
+        Some("github-work")
+    );
+
+    // Empty files are legacy files without profiles.
+    let (file, legacy) = ConfigFile::parse("").unwrap();
+    assert!(legacy && file.profiles.is_empty());
+}
+
+#[test]
+fn test_newer_version_is_rejected() {
+    let error = ConfigFile::parse(&format!("version = {}\n", CONFIG_VERSION + 1)).unwrap_err();
+    assert!(error.to_string().contains("Please upgrade guse"));
+    assert!(ConfigFile::parse("version = \"one\"\n").is_err());
+}
+
+#[test]
+fn test_legacy_profile_named_version() {
+    let (file, legacy) = ConfigFile::parse(
+        "[version]\nname = \"Release\"\nemail = \"release@example.com\"\nssh_host = \"github-release\"\n",
+    )
+    .unwrap();
+    assert!(legacy);
+    assert_eq!(file.profiles["version"].name.as_deref(), Some("Release"));
+    assert_eq!(file.version, CONFIG_VERSION);
+}
+
+#[test]
+fn test_legacy_file_is_migrated_with_backup() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = config_in(dir.path());
+    fs::write(&config.path, LEGACY).unwrap();
+
+    let profiles = config.load_profiles().unwrap();
+    assert_eq!(profiles["work"].name, "Work");
+
+    let migrated = fs::read_to_string(&config.path).unwrap();
+    assert!(migrated.starts_with(&format!("version = {}\n", CONFIG_VERSION)));
+    assert!(migrated.contains("[profiles.work]"));
+
+    let backups = config.backups.list().unwrap();
+    assert_eq!(backups.len(), 1);
+    assert_eq!(backups[0].read().unwrap(), LEGACY);
+
+    // Loading again leaves the file alone.
+    config.load_profiles().unwrap();
+    assert_eq!(config.backups.list().unwrap().len(), 1);
+}
+
+#[test]
+fn test_backup_retention_setting() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = config_in(dir.path());
+    config
+        .modify(|file| {
+            file.settings.backup_retention = Some(2);
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(config.backup_store().retention, 2);
+
+    for name in ["Work", "Home", "Oss", "Me"] {
+        config
+            .add_profile(name.to_lowercase(), profile(name, SwitchStrategy::Alias))
+            .unwrap();
+    }
+    assert_eq!(config.backups.list().unwrap().len(), 2);
+
+    // An explicitly chosen file keeps its backups beside it.
+    let path = dir.path().join("team.toml");
+    let config = Config::resolve(Some(&path)).unwrap();
+    assert_eq!(config.backups.dir, dir.path().join("team.backups"));
+    assert_eq!(
+        config.backup_store().retention,
+        crate::backup::DEFAULT_RETENTION
+    );
+}
+
+#[test]
+fn test_modify_keeps_settings_and_profiles() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = config_in(dir.path());
+
+    config
+        .add_profile("work".to_string(), profile("Work", SwitchStrategy::Alias))
+        .unwrap();
+    config
+        .add_profile("me".to_string(), profile("Me", SwitchStrategy::Alias))
+        .unwrap();
+    assert!(config
+        .update_profile("missing", profile("X", SwitchStrategy::Alias))
+        .is_err());
+    config.delete_profile("work").unwrap();
+
+    let file = config.load().unwrap();
+    assert_eq!(file.version, CONFIG_VERSION);
+    assert_eq!(file.profiles.keys().collect::<Vec<_>>(), vec!["me"]);
+}
+
+#[test]
+fn test_config_path_precedence() {
+    use crate::config::config_path;
+    use std::path::Path;
+
+    let dir = tempfile::tempdir().unwrap();
+    let home = dir.path().join("home");
+    let xdg = dir.path().join("xdg");
+    let path = |flag: Option<&Path>, env: Option<&str>, xdg: Option<&Path>| {
+        config_path(
+            flag,
+            env.map(Into::into),
+            xdg.map(|p| p.as_os_str().to_owned()),
+            Some(home.clone()),
+        )
+        .unwrap()
+    };
+
+    // Nothing exists yet: new installs use the XDG location.
+    assert_eq!(
+        path(None, None, None),
+        home.join(".config/guse/config.toml")
+    );
+    assert_eq!(path(None, None, Some(&xdg)), xdg.join("guse/config.toml"));
+    assert_eq!(
+        path(None, None, Some(Path::new("relative"))),
+        home.join(".config/guse/config.toml")
+    );
+
+    // An existing legacy file is still picked up...
+    fs::create_dir_all(&home).unwrap();
+    fs::write(home.join(".guse-profiles.toml"), LEGACY).unwrap();
+    assert_eq!(
+        path(None, None, Some(&xdg)),
+        home.join(".guse-profiles.toml")
+    );
+
+    // ...until the XDG file exists.
+    fs::create_dir_all(xdg.join("guse")).unwrap();
+    fs::write(xdg.join("guse/config.toml"), "").unwrap();
+    assert_eq!(path(None, None, Some(&xdg)), xdg.join("guse/config.toml"));
+
+    // The environment variable and the flag override everything.
+    assert_eq!(
+        path(None, Some("/tmp/env.toml"), Some(&xdg)),
+        Path::new("/tmp/env.toml")
+    );
+    assert_eq!(
+        path(None, Some(""), Some(&xdg)),
+        xdg.join("guse/config.toml")
+    );
+    assert_eq!(
+        path(
+            Some(Path::new("/tmp/flag.toml")),
+            Some("/tmp/env.toml"),
+            None
+        ),
+        Path::new("/tmp/flag.toml")
+    );
+}
+
+#[test]
+fn test_export_round_trip_in_every_format() {
+    use crate::config::transfer::{self, Format};
+
+    let work = profile("Work", SwitchStrategy::SshCommand);
+    let me = profile("Me", SwitchStrategy::Alias);
+    let selected = vec![("work", &work), ("me", &me)];
+
+    for format in [Format::Toml, Format::Json, Format::Yaml] {
+        let exported = transfer::export(&selected, format).unwrap();
+        let parsed = transfer::parse(&exported, format).unwrap();
+        assert_eq!(parsed.len(), 2, "{:?}", format);
+        assert_eq!(parsed["work"], work, "{:?}", format);
+        assert_eq!(parsed["me"], me, "{:?}", format);
+    }
+}
+
+#[test]
+fn test_import_accepts_legacy_and_rejects_newer_files() {
+    use crate::config::transfer::{self, Format};
+
+    let profiles = transfer::parse(LEGACY, Format::Toml).unwrap();
+    assert_eq!(profiles["work"].ssh_host, "github-work");
+
+    let json =
+        r#"{"work": {"name": "Work", "email": "work@example.com", "ssh_host": "github-work"}}"#;
+    assert_eq!(transfer::parse(json, Format::Json).unwrap().len(), 1);
+
+    let newer = format!("version: {}\nprofiles: {{}}\n", CONFIG_VERSION + 1);
+    assert!(transfer::parse(&newer, Format::Yaml).is_err());
+    assert!(transfer::parse("[1, 2]", Format::Json).is_err());
+}
+
+#[test]
+fn test_import_plan_strategies() {
+    use crate::config::transfer::{self, ImportAction, MergeStrategy};
+    use crate::config::ProfileMap;
+
+    let mut existing = ProfileMap::new();
+    existing.insert("work".to_string(), profile("Work", SwitchStrategy::Alias));
+    existing.insert("me".to_string(), profile("Me", SwitchStrategy::Alias));
+
+    let mut incoming = ProfileMap::new();
+    incoming.insert("work".to_string(), profile("Other", SwitchStrategy::Alias));
+    incoming.insert(
+        "work-2".to_string(),
+        profile("Work2", SwitchStrategy::Alias),
+    );
+    incoming.insert("me".to_string(), profile("Me", SwitchStrategy::Alias));
+
+    let actions = |strategy| {
+        transfer::plan(&existing, incoming.clone(), strategy)
+            .into_iter()
+            .map(|i| (i.name, i.action))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        actions(MergeStrategy::Skip),
+        vec![
+            ("me".to_string(), ImportAction::Unchanged),
+            ("work".to_string(), ImportAction::Skip),
+            ("work-2".to_string(), ImportAction::Add),
+        ]
+    );
+    assert_eq!(
+        actions(MergeStrategy::Overwrite)[1],
+        ("work".to_string(), ImportAction::Overwrite)
+    );
+    // The renamed profile does not take a name another import needs.
+    assert_eq!(
+        actions(MergeStrategy::Rename)[1],
+        (
+            "work-3".to_string(),
+            ImportAction::Rename("work".to_string())
+        )
+    );
+
+    let mut file = ConfigFile::default();
+    for (name, profile) in &existing {
+        file.set_profile(name, profile.clone()).unwrap();
+    }
+    transfer::apply(
+        &mut file,
+        &transfer::plan(&existing, incoming.clone(), MergeStrategy::Rename),
+    )
+    .unwrap();
+    let profiles = file.resolved_profiles();
+    assert_eq!(profiles.len(), 4);
+    assert_eq!(profiles["work"].name, "Work");
+    assert_eq!(profiles["work-3"].name, "Other");
+}
+
+const INHERITANCE: &str = r#"version = 1
+
+[profiles.base]
+name = "Jane Doe"
+strategy = "ssh-command"
+
+[profiles.client-a]
+extends = "base"
+email = "jane@client-a.com"
+ssh_host = "github-client-a"
+url_patterns = ["https://github.com/client-a/**"]
+
+[profiles.client-b]
+extends = "client-a"
+email = "jane@client-b.com"
+"#;
+
+#[test]
+fn test_profile_inheritance() {
+    let (file, _) = ConfigFile::parse(INHERITANCE).unwrap();
+    let profiles = file.resolved_profiles();
+
+    // `base` has no email or host, so it is only a template.
+    assert_eq!(profiles.len(), 2);
+    assert!(!profiles.contains_key("base"));
+
+    let client_b = &profiles["client-b"];
+    assert_eq!(client_b.name, "Jane Doe");
+    assert_eq!(client_b.email, "jane@client-b.com");
+    assert_eq!(client_b.ssh_host, "github-client-a");
+    assert_eq!(client_b.strategy, SwitchStrategy::SshCommand);
+    assert_eq!(client_b.url_patterns, ["https://github.com/client-a/**"]);
+
+    let resolved = file.resolve("client-b").unwrap();
+    assert_eq!(resolved.chain, vec!["client-b", "client-a", "base"]);
+    assert_eq!(resolved.name.unwrap().source, "base");
+    assert_eq!(resolved.email.unwrap().source, "client-b");
+    assert_eq!(resolved.ssh_host.unwrap().source, "client-a");
+
+    let base = file.resolve("base").unwrap();
+    assert_eq!(base.missing_fields(), vec!["email", "ssh_host"]);
+}
+
+#[test]
+fn test_inheritance_errors() {
+    let cycle = "version = 1\n[profiles.a]\nextends = \"b\"\n[profiles.b]\nextends = \"a\"\n";
+    let (file, _) = ConfigFile::parse(cycle).unwrap();
+    let error = file.resolve("a").unwrap_err().to_string();
+    assert!(error.contains("a -> b -> a"), "{}", error);
+    assert!(file.resolved_profiles().is_empty());
+
+    let missing = "version = 1\n[profiles.a]\nextends = \"nope\"\n";
+    let (file, _) = ConfigFile::parse(missing).unwrap();
+    assert!(file
+        .resolve("a")
+        .unwrap_err()
+        .to_string()
+        .contains("'nope'"));
+}
+
+#[test]
+fn test_export_flattens_inheritance() {
+    use crate::config::transfer::{self, Format};
+
+    let (file, _) = ConfigFile::parse(INHERITANCE).unwrap();
+    let profiles = file.resolved_profiles();
+    let exported = transfer::export(&[("client-b", &profiles["client-b"])], Format::Toml).unwrap();
+    assert!(!exported.contains("extends"));
+    assert!(exported.contains("name = \"Jane Doe\""));
+    assert!(exported.contains("ssh_host = \"github-client-a\""));
+
+    // The export imports on its own, without the profiles it extended
+    let parsed = transfer::parse(&exported, Format::Toml).unwrap();
+    assert_eq!(parsed["client-b"], profiles["client-b"]);
+}
+
+#[test]
+fn test_broken_profile_does_not_block_others() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = config_in(dir.path());
+    fs::write(
+        &config.path,
+        "version = 1\n[profiles.work]\nname = \"Work\"\nemail = \"work@example.com\"\nssh_host = \"github-work\"\n\n[profiles.broken]\nextends = \"nope\"\n",
+    )
+    .unwrap();
+
+    let profiles = config.load_profiles().unwrap();
+    assert_eq!(profiles.keys().collect::<Vec<_>>(), vec!["work"]);
+
+    // The skipped profile is handed back for the caller to report
+    let broken = config.load().unwrap().broken_profiles();
+    assert_eq!(broken.len(), 1);
+    assert_eq!(broken[0].0, "broken");
+
+    // The broken profile can still be deleted
+    config.delete_profile("broken").unwrap();
+    assert!(!config.load().unwrap().profiles.contains_key("broken"));
+}
+
+#[test]
+fn test_set_profile_keeps_inheritance() {
+    let (mut file, _) = ConfigFile::parse(INHERITANCE).unwrap();
+    let mut profile = file.resolved_profiles()["client-a"].clone();
+    profile.email = "jane@new.com".to_string();
+    file.set_profile("client-a", profile).unwrap();
+
+    let entry = &file.profiles["client-a"];
+    assert_eq!(entry.extends.as_deref(), Some("base"));
+    assert_eq!(entry.email.as_deref(), Some("jane@new.com"));
+    // Values equal to the inherited ones are not copied into the entry.
+    assert_eq!(entry.name, None);
+    assert_eq!(entry.strategy, None);
+    assert_eq!(
+        entry.url_patterns.as_deref(),
+        Some(&["https://github.com/client-a/**".to_string()][..])
+    );
+}
+
+#[test]
+fn test_delete_refuses_extended_profile() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = config_in(dir.path());
+    fs::write(&config.path, INHERITANCE).unwrap();
+
+    let error = config.delete_profile("client-a").unwrap_err().to_string();
+    assert!(error.contains("client-b"), "{}", error);
+    config.delete_profile("client-b").unwrap();
+    config.delete_profile("client-a").unwrap();
+}
+
+#[test]
+fn test_profiles_sorted_by_order_then_name() {
+    let mut settings = Settings::default();
+    let profiles: ProfileMap = ["b", "c", "a", "work"]
+        .iter()
+        .map(|n| (n.to_string(), profile(n, SwitchStrategy::Alias)))
+        .collect();
+
+    let names = |sorted: Vec<(String, Profile)>| -> Vec<String> {
+        sorted.into_iter().map(|(name, _)| name).collect()
+    };
+    assert_eq!(
+        names(settings.sort(profiles.clone())),
+        ["a", "b", "c", "work"]
+    );
+
+    settings.order = vec!["work".to_string(), "c".to_string(), "gone".to_string()];
+    assert_eq!(names(settings.sort(profiles)), ["work", "c", "a", "b"]);
+}
+
+#[test]
+fn test_preselect_prefers_repository_then_default() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("state").join("state.toml");
+    let names: Vec<String> = ["a", "b", "c"].iter().map(|n| n.to_string()).collect();
+    let repo = std::path::Path::new("/src/project");
+    let mut settings = Settings::default();
+
+    assert_eq!(
+        State::load(&path).preselect(&names, Some(repo), &settings),
+        0
+    );
+    settings.default_profile = Some("c".to_string());
+    assert_eq!(
+        State::load(&path).preselect(&names, Some(repo), &settings),
+        2
+    );
+
+    State::record(&path, Some(repo), "b").unwrap();
+    State::record(&path, None, "a").unwrap();
+    let state = State::load(&path);
+    assert_eq!(state.preselect(&names, Some(repo), &settings), 1);
+    assert_eq!(state.preselect(&names, None, &settings), 2);
+    assert_eq!(state.repositories["/src/project"].profile, "b");
+    assert!(state.profiles.contains_key("a") && state.profiles.contains_key("b"));
+
+    // A profile that no longer exists falls through to the default.
+    let names: Vec<String> = ["a", "c"].iter().map(|n| n.to_string()).collect();
+    assert_eq!(state.preselect(&names, Some(repo), &settings), 1);
+}
+
+#[test]
+fn test_delete_clears_settings() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = config_in(dir.path());
+    config
+        .modify(|file| {
+            file.set_profile("work", profile("Work", SwitchStrategy::Alias))?;
+            file.set_profile("home", profile("Home", SwitchStrategy::Alias))?;
+            file.settings.order = vec!["work".to_string(), "home".to_string()];
+            file.settings.default_profile = Some("work".to_string());
+            Ok(())
+        })
+        .unwrap();
+
+    config.delete_profile("work").unwrap();
+    let settings = config.load().unwrap().settings;
+    assert_eq!(settings.order, ["home"]);
+    assert_eq!(settings.default_profile, None);
+}
+
+#[test]
+fn test_set_default_profile_and_order() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = config_in(dir.path());
+    for name in ["Work", "Home", "Oss"] {
+        config
+            .add_profile(name.to_lowercase(), profile(name, SwitchStrategy::Alias))
+            .unwrap();
+    }
+
+    config.set_default_profile(Some("home")).unwrap();
+    assert_eq!(
+        config.load().unwrap().settings.default_profile.as_deref(),
+        Some("home")
+    );
+    assert!(config.set_default_profile(Some("missing")).is_err());
+    config.set_default_profile(None).unwrap();
+    assert_eq!(config.load().unwrap().settings.default_profile, None);
+
+    config
+        .set_order(vec!["oss".to_string(), "work".to_string()])
+        .unwrap();
+    let (profiles, _) = config.load_sorted().unwrap();
+    let names: Vec<_> = profiles.iter().map(|(n, _)| n.as_str()).collect();
+    assert_eq!(names, ["oss", "work", "home"]);
+
+    assert!(config.set_order(vec!["missing".to_string()]).is_err());
+    assert!(config
+        .set_order(vec!["work".to_string(), "work".to_string()])
+        .is_err());
+    config.set_order(Vec::new()).unwrap();
+    assert!(config.load().unwrap().settings.order.is_empty());
+}
+
+#[test]
+fn test_pin_lookup_prefers_git_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    let work_tree = dir.path();
+    let git_dir = work_tree.join(".git");
+    fs::create_dir(&git_dir).unwrap();
+
+    assert_eq!(Pin::find(Some(work_tree), Some(&git_dir)).unwrap(), None);
+
+    let shared = Pin::write(&work_tree.join(PIN_FILE), "work").unwrap();
+    assert_eq!(
+        Pin::find(Some(work_tree), Some(&git_dir)).unwrap(),
+        Some(shared)
+    );
+
+    fs::write(git_dir.join(GIT_DIR_PIN_FILE), "profile = \"personal\"\n").unwrap();
+    let pin = Pin::find(Some(work_tree), Some(&git_dir)).unwrap().unwrap();
+    assert_eq!(pin.profile, "personal");
+    assert_eq!(pin.source, git_dir.join(GIT_DIR_PIN_FILE));
+
+    fs::write(git_dir.join(GIT_DIR_PIN_FILE), "name = \"personal\"\n").unwrap();
+    assert!(Pin::find(Some(work_tree), Some(&git_dir)).is_err());
+
+    // Removing takes both pins, so the shared one does not take over
+    let removed = Pin::remove_all(Some(work_tree), Some(&git_dir)).unwrap();
+    assert_eq!(
+        removed,
+        vec![git_dir.join(GIT_DIR_PIN_FILE), work_tree.join(PIN_FILE)]
+    );
+    assert_eq!(Pin::find(Some(work_tree), Some(&git_dir)).unwrap(), None);
+    assert!(Pin::remove_all(Some(work_tree), Some(&git_dir))
+        .unwrap()
+        .is_empty());
+}
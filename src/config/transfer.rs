@@ -0,0 +1,204 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use super::{ConfigError, ConfigFile, Profile, ProfileEntry, ProfileMap, CONFIG_VERSION};
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    Toml,
+    Json,
+    Yaml,
+}
+
+impl Format {
+    /// Guesses the format from a file extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "toml" => Some(Format::Toml),
+            "json" => Some(Format::Json),
+            "yaml" | "yml" => Some(Format::Yaml),
+            _ => None,
+        }
+    }
+}
+
+/// What to do with an imported profile whose name is already taken.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum MergeStrategy {
+    /// Keep the existing profile.
+    #[default]
+    Skip,
+    /// Replace the existing profile.
+    Overwrite,
+    /// Import under a new name such as `work-2`.
+    Rename,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportAction {
+    Add,
+    Overwrite,
+    Skip,
+    /// Imported under another name; holds the name used in the file.
+    Rename(String),
+    /// Same name and same values as the existing profile.
+    Unchanged,
+}
+
+impl std::fmt::Display for ImportAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportAction::Add => write!(f, "add"),
+            ImportAction::Overwrite => write!(f, "overwrite"),
+            ImportAction::Skip => write!(f, "skip"),
+            ImportAction::Rename(from) => write!(f, "rename (from '{}')", from),
+            ImportAction::Unchanged => write!(f, "unchanged"),
+        }
+    }
+}
+
+/// One line of an import plan: `profile` ends up as `name` unless skipped.
+#[derive(Debug, Clone)]
+pub struct ImportItem {
+    pub name: String,
+    pub profile: Profile,
+    pub action: ImportAction,
+}
+
+#[derive(Serialize)]
+struct ExportDocument<'a> {
+    version: u32,
+    profiles: BTreeMap<&'a str, &'a Profile>,
+}
+
+/// Serializes the given profiles in the same shape as the configuration
+/// file, without its settings. Profiles are written resolved, with inherited
+/// values filled in and no `extends`, so an export does not depend on
+/// profiles that were left out of it.
+pub fn export(profiles: &[(&str, &Profile)], format: Format) -> Result<String, ConfigError> {
+    let document = ExportDocument {
+        version: CONFIG_VERSION,
+        profiles: profiles.iter().copied().collect(),
+    };
+    let error = |e: String| ConfigError(format!("Cannot export profiles: {}", e));
+    match format {
+        Format::Toml => toml::to_string_pretty(&document).map_err(|e| error(e.to_string())),
+        Format::Json => serde_json::to_string_pretty(&document)
+            .map(|s| s + "\n")
+            .map_err(|e| error(e.to_string())),
+        Format::Yaml => serde_yaml::to_string(&document).map_err(|e| error(e.to_string())),
+    }
+}
+
+/// Reads profiles from an export, a configuration file, or a file in the
+/// legacy layout where profiles are the top-level entries. Inheritance within
+/// the file is resolved and templates are dropped.
+pub fn parse(contents: &str, format: Format) -> Result<ProfileMap, ConfigError> {
+    let error = |e: String| ConfigError(format!("Cannot parse import file: {}", e));
+    let value: serde_json::Value = match format {
+        Format::Toml => toml::from_str(contents).map_err(|e| error(e.to_string()))?,
+        Format::Json => serde_json::from_str(contents).map_err(|e| error(e.to_string()))?,
+        Format::Yaml => serde_yaml::from_str(contents).map_err(|e| error(e.to_string()))?,
+    };
+    let serde_json::Value::Object(mut document) = value else {
+        return Err(error("expected a table of profiles".to_string()));
+    };
+
+    let profiles = if !document.contains_key("version") {
+        serde_json::Value::Object(document)
+    } else {
+        match document["version"].as_u64() {
+            Some(v) if v > CONFIG_VERSION as u64 => {
+                return Err(error(format!(
+                    "written by a newer guse (schema version {}, this release supports up to {})",
+                    v, CONFIG_VERSION
+                )))
+            }
+            Some(_) => document.remove("profiles").unwrap_or_default(),
+            None => return Err(error(format!("invalid version: {}", document["version"]))),
+        }
+    };
+    let profiles: HashMap<String, ProfileEntry> = match profiles {
+        serde_json::Value::Null => HashMap::new(),
+        profiles => serde_json::from_value(profiles).map_err(|e| error(e.to_string()))?,
+    };
+
+    Ok(ConfigFile {
+        profiles,
+        ..Default::default()
+    }
+    .resolved_profiles())
+}
+
+/// Decides what happens to each incoming profile, in name order.
+pub fn plan(
+    existing: &ProfileMap,
+    incoming: ProfileMap,
+    strategy: MergeStrategy,
+) -> Vec<ImportItem> {
+    let mut incoming: Vec<_> = incoming.into_iter().collect();
+    incoming.sort_by(|a, b| a.0.cmp(&b.0));
+    let incoming_names: Vec<String> = incoming.iter().map(|(name, _)| name.clone()).collect();
+    // A new name must not collide with any profile already there or on its way in.
+    let taken = |name: &str, items: &[ImportItem]| {
+        existing.contains_key(name)
+            || incoming_names.iter().any(|n| n == name)
+            || items.iter().any(|i| i.name == name)
+    };
+
+    let mut items: Vec<ImportItem> = Vec::new();
+    for (name, profile) in incoming {
+        let item = match existing.get(&name) {
+            None => ImportItem {
+                name,
+                profile,
+                action: ImportAction::Add,
+            },
+            Some(current) if *current == profile => ImportItem {
+                name,
+                profile,
+                action: ImportAction::Unchanged,
+            },
+            Some(_) => match strategy {
+                MergeStrategy::Skip => ImportItem {
+                    name,
+                    profile,
+                    action: ImportAction::Skip,
+                },
+                MergeStrategy::Overwrite => ImportItem {
+                    name,
+                    profile,
+                    action: ImportAction::Overwrite,
+                },
+                MergeStrategy::Rename => {
+                    let new_name = (2..)
+                        .map(|n| format!("{}-{}", name, n))
+                        .find(|candidate| !taken(candidate, &items))
+                        .expect("an unused name exists");
+                    ImportItem {
+                        name: new_name,
+                        profile,
+                        action: ImportAction::Rename(name),
+                    }
+                }
+            },
+        };
+        items.push(item);
+    }
+    items
+}
+
+/// Applies a plan from [`plan`] to `file`.
+pub fn apply(file: &mut ConfigFile, items: &[ImportItem]) -> Result<(), ConfigError> {
+    for item in items {
+        match item.action {
+            ImportAction::Add | ImportAction::Overwrite | ImportAction::Rename(_) => {
+                file.set_profile(&item.name, item.profile.clone())?;
+            }
+            ImportAction::Skip | ImportAction::Unchanged => {}
+        }
+    }
+    Ok(())
+}
@@ -1,6 +1,7 @@
 use thiserror::Error;
 
 #[derive(Debug, Error)]
+#[allow(clippy::enum_variant_names)]
 pub enum GuseError {
     #[error("IO Error: {0}")]
     IoError(#[from] std::io::Error),
@@ -16,7 +17,13 @@ pub enum GuseError {
     
     #[error("Configuration Error: {0}")]
     ConfigError(String),
+
+    #[error("SSH Config Error: {0}")]
+    SshError(String),
     
+    #[error("Backup Error: {0}")]
+    BackupError(String),
+
     #[error("Interactive Input Error: {0}")]
     DialoguerError(#[from] dialoguer::Error),
 }
@@ -39,6 +46,18 @@ impl From<crate::git::GitError> for GuseError {
     }
 }
 
+impl From<crate::ssh::SshError> for GuseError {
+    fn from(err: crate::ssh::SshError) -> Self {
+        GuseError::SshError(err.to_string())
+    }
+}
+
+impl From<crate::backup::BackupError> for GuseError {
+    fn from(err: crate::backup::BackupError) -> Self {
+        GuseError::BackupError(err.to_string())
+    }
+}
+
 #[cfg(test)]
 mod tests;
 
@@ -1,3 +1,5 @@
+#![allow(clippy::module_inception)]
+
 #[cfg(test)]
 mod tests {
     use crate::error::GuseError;
@@ -44,7 +46,7 @@ mod tests {
     #[test]
     fn test_custom_from_implementations() {
         // Test From<toml::ser::Error> 
-        let data = std::collections::HashMap::from([("key", std::f64::NAN)]);
+        let data = std::collections::HashMap::from([("key", f64::NAN)]);
         let toml_ser_result = toml::to_string(&data);
         if let Err(toml_ser_error) = toml_ser_result {
             let guse_error: GuseError = toml_ser_error.into();
@@ -0,0 +1,268 @@
+//! The operations guse needs from Git, so that [`super::Git`] can run on the
+//! `git` binary, on its own config reader, on libgit2 in-process, or on an
+//! in-memory fake in tests.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use super::config_file::{normalize_key, ConfigValue};
+use super::{GitError, GitVersion};
+
+/// The config file a read or write is limited to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConfigScope {
+    /// The machine-wide `/etc/gitconfig`.
+    System,
+    /// The user's `~/.gitconfig`.
+    Global,
+    /// The repository's `.git/config`.
+    Local,
+    /// `config.worktree` of the current worktree.
+    Worktree,
+}
+
+impl std::fmt::Display for ConfigScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigScope::System => write!(f, "system"),
+            ConfigScope::Global => write!(f, "global"),
+            ConfigScope::Local => write!(f, "local"),
+            ConfigScope::Worktree => write!(f, "worktree"),
+        }
+    }
+}
+
+pub trait GitBackend {
+    /// The effective value of `key`, or `None` when no config file sets it.
+    fn config_get(&self, key: &str) -> Result<Option<String>, GitError>;
+
+    /// Every value of `key` with the scope and file it comes from, lowest
+    /// precedence first, so the last one wins.
+    fn config_origins(&self, key: &str) -> Result<Vec<ConfigValue>, GitError>;
+
+    /// The value of `key` in `scope` only.
+    fn config_get_in(&self, scope: ConfigScope, key: &str) -> Result<Option<String>, GitError>;
+
+    fn config_set(&mut self, scope: ConfigScope, key: &str, value: &str) -> Result<(), GitError>;
+
+    /// Removes `key` from `scope`. Removing a key that is not set succeeds.
+    fn config_unset(&mut self, scope: ConfigScope, key: &str) -> Result<(), GitError>;
+
+    /// Names of all remotes, in the order they are configured.
+    fn remotes(&self) -> Result<Vec<String>, GitError>;
+
+    /// URL of `remote`, or `None` when there is no such remote.
+    fn remote_url(&self, remote: &str) -> Result<Option<String>, GitError>;
+
+    fn set_remote_url(&mut self, remote: &str, url: &str) -> Result<(), GitError>;
+
+    /// Top-level directory of the work tree.
+    fn repo_root(&self) -> Result<PathBuf, GitError>;
+
+    /// The repository's Git directory, which is not `.git` in worktrees.
+    fn git_dir(&self) -> Result<PathBuf, GitError>;
+
+    /// A backend of the same kind for the repository at `dir`, such as a
+    /// submodule's checkout.
+    fn open(&self, dir: &Path) -> Result<Self, GitError>
+    where
+        Self: Sized;
+}
+
+/// Runs the `git` binary.
+#[derive(Debug, Default)]
+pub struct ProcessBackend {
+    /// Where `git` runs; the current directory when `None`.
+    dir: Option<PathBuf>,
+}
+
+/// `git config` exits with this status when the key is not set.
+const CONFIG_KEY_NOT_FOUND: i32 = 1;
+
+/// The first release where `git config` takes `--show-scope`.
+const SHOW_SCOPE_MIN_VERSION: GitVersion = GitVersion(2, 26, 0);
+
+impl ProcessBackend {
+    pub fn execute_command(&self, args: &[&str]) -> Result<String, GitError> {
+        let output = self.output(args)?;
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(GitError(error.to_string()));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    fn output(&self, args: &[&str]) -> Result<Output, GitError> {
+        let mut command = Command::new("git");
+        if let Some(dir) = &self.dir {
+            command.current_dir(dir);
+        }
+        command
+            .args(args)
+            .output()
+            .map_err(|e| GitError(format!("Failed to execute Git command: {}", e)))
+    }
+
+    /// Runs a `git config` read, mapping "not set" to `None`.
+    fn read_config(&self, args: &[&str]) -> Result<Option<String>, GitError> {
+        let output = self.output(args)?;
+        match output.status.code() {
+            Some(0) => Ok(Some(
+                String::from_utf8_lossy(&output.stdout).trim().to_string(),
+            )),
+            Some(CONFIG_KEY_NOT_FOUND) => Ok(None),
+            _ => Err(GitError(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            )),
+        }
+    }
+
+    /// `config_origins` for Git older than 2.26, which cannot print scopes:
+    /// each scope is read on its own, in the order Git reads them.
+    fn config_origins_by_scope(&self, key: &str) -> Result<Vec<ConfigValue>, GitError> {
+        let mut scopes = vec![ConfigScope::System, ConfigScope::Global];
+        if self.git_dir().is_ok() {
+            scopes.push(ConfigScope::Local);
+            // Without the extension, `--worktree` reads the local file again.
+            let worktree_config =
+                self.read_config(&["config", "--bool", "extensions.worktreeConfig"])?;
+            if worktree_config.as_deref() == Some("true") {
+                scopes.push(ConfigScope::Worktree);
+            }
+        }
+
+        let mut values = Vec::new();
+        for scope in scopes {
+            let args = [
+                "config",
+                "-z",
+                "--includes",
+                "--show-origin",
+                scope_flag(scope),
+                "--get-all",
+                key,
+            ];
+            let Some(output) = self.read_config(&args)? else {
+                continue;
+            };
+            // `<origin>\0<value>\0` per value.
+            let fields: Vec<&str> = output.split('\0').collect();
+            values.extend(
+                fields
+                    .chunks_exact(2)
+                    .filter_map(|value| origin_value(key, scope, value[0], value[1])),
+            );
+        }
+        Ok(values)
+    }
+}
+
+/// A value `git config --show-origin` printed, unless it has no file.
+fn origin_value(key: &str, scope: ConfigScope, origin: &str, value: &str) -> Option<ConfigValue> {
+    Some(ConfigValue {
+        key: normalize_key(key),
+        value: Some(value.to_string()),
+        scope,
+        origin: PathBuf::from(origin.strip_prefix("file:")?),
+    })
+}
+
+fn scope_flag(scope: ConfigScope) -> &'static str {
+    match scope {
+        ConfigScope::System => "--system",
+        ConfigScope::Global => "--global",
+        ConfigScope::Local => "--local",
+        ConfigScope::Worktree => "--worktree",
+    }
+}
+
+impl GitBackend for ProcessBackend {
+    fn config_get(&self, key: &str) -> Result<Option<String>, GitError> {
+        self.read_config(&["config", "--get", key])
+    }
+
+    fn config_origins(&self, key: &str) -> Result<Vec<ConfigValue>, GitError> {
+        if super::version()? < SHOW_SCOPE_MIN_VERSION {
+            return self.config_origins_by_scope(key);
+        }
+        let args = [
+            "config",
+            "-z",
+            "--show-scope",
+            "--show-origin",
+            "--get-all",
+            key,
+        ];
+        let Some(output) = self.read_config(&args)? else {
+            return Ok(Vec::new());
+        };
+        // `<scope>\0<origin>\0<value>\0` per value. Values from the command
+        // line or the environment have no file and are left out.
+        let fields: Vec<&str> = output.split('\0').collect();
+        Ok(fields
+            .chunks_exact(3)
+            .filter_map(|value| {
+                let scope = match value[0] {
+                    "system" => ConfigScope::System,
+                    "global" => ConfigScope::Global,
+                    "local" => ConfigScope::Local,
+                    "worktree" => ConfigScope::Worktree,
+                    _ => return None,
+                };
+                origin_value(key, scope, value[1], value[2])
+            })
+            .collect())
+    }
+
+    fn config_get_in(&self, scope: ConfigScope, key: &str) -> Result<Option<String>, GitError> {
+        self.read_config(&["config", scope_flag(scope), "--get", key])
+    }
+
+    fn config_set(&mut self, scope: ConfigScope, key: &str, value: &str) -> Result<(), GitError> {
+        self.execute_command(&["config", scope_flag(scope), key, value])
+            .map(|_| ())
+    }
+
+    fn config_unset(&mut self, scope: ConfigScope, key: &str) -> Result<(), GitError> {
+        if self.config_get_in(scope, key)?.is_none() {
+            return Ok(());
+        }
+        self.execute_command(&["config", scope_flag(scope), "--unset", key])
+            .map(|_| ())
+    }
+
+    fn remotes(&self) -> Result<Vec<String>, GitError> {
+        let output = self.execute_command(&["remote"])?;
+        Ok(output.lines().map(str::to_string).collect())
+    }
+
+    fn remote_url(&self, remote: &str) -> Result<Option<String>, GitError> {
+        // Checked up front; git's "No such remote" message is translated.
+        if !self.remotes()?.iter().any(|r| r == remote) {
+            return Ok(None);
+        }
+        self.execute_command(&["remote", "get-url", remote])
+            .map(Some)
+    }
+
+    fn set_remote_url(&mut self, remote: &str, url: &str) -> Result<(), GitError> {
+        self.execute_command(&["remote", "set-url", remote, url])
+            .map(|_| ())
+    }
+
+    fn repo_root(&self) -> Result<PathBuf, GitError> {
+        self.execute_command(&["rev-parse", "--show-toplevel"])
+            .map(PathBuf::from)
+    }
+
+    fn git_dir(&self) -> Result<PathBuf, GitError> {
+        self.execute_command(&["rev-parse", "--absolute-git-dir"])
+            .map(PathBuf::from)
+    }
+
+    fn open(&self, dir: &Path) -> Result<Self, GitError> {
+        Ok(Self {
+            dir: Some(dir.to_path_buf()),
+        })
+    }
+}
@@ -0,0 +1,1050 @@
+//! Git's INI-style config files, read and written without running `git`.
+//!
+//! [`ConfigFile`] keeps the file's lines as they are, so a write only
+//! replaces the lines of the settings it changes. [`ConfigSet`] reads the
+//! files `git config` would, in order, following `include.path` and
+//! `includeIf`.
+
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::iter::Peekable;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::str::Chars;
+
+use super::backend::ConfigScope;
+use super::GitError;
+
+/// Git refuses to follow includes nested deeper than this.
+const MAX_INCLUDE_DEPTH: usize = 10;
+
+/// One `name = value` setting.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigEntry {
+    /// Lowercased, as section names are case-insensitive.
+    pub section: String,
+    /// Kept as written for `[section "sub"]`; lowercased for the legacy
+    /// `[section.sub]` form.
+    pub subsection: Option<String>,
+    /// Lowercased, as variable names are case-insensitive.
+    pub name: String,
+    /// `None` for a bare `name`, which Git reads as boolean true.
+    pub value: Option<String>,
+    /// Lines the setting spans; more than one when continued with `\`.
+    lines: Range<usize>,
+    /// Index of the section header the setting belongs to.
+    header: usize,
+    /// A comment following the value on its line, kept on rewrites.
+    comment: Option<String>,
+}
+
+impl ConfigEntry {
+    /// The canonical `section.subsection.name` form of the key.
+    pub fn key(&self) -> String {
+        match &self.subsection {
+            Some(subsection) => format!("{}.{}.{}", self.section, subsection, self.name),
+            None => format!("{}.{}", self.section, self.name),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Header {
+    section: String,
+    subsection: Option<String>,
+    line: usize,
+}
+
+/// A config file with its original text.
+#[derive(Debug, Clone)]
+pub struct ConfigFile {
+    pub path: PathBuf,
+    lines: Vec<String>,
+    headers: Vec<Header>,
+    pub entries: Vec<ConfigEntry>,
+}
+
+impl ConfigFile {
+    /// Opens `path`. A missing file yields an empty one.
+    pub fn load(path: &Path) -> Result<Self, GitError> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(GitError(format!("Cannot read {}: {}", path.display(), e))),
+        };
+        Self::parse(&content, path)
+    }
+
+    pub fn parse(content: &str, path: &Path) -> Result<Self, GitError> {
+        let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+        let mut lines: Vec<String> = content.split('\n').map(str::to_string).collect();
+        if lines.last().is_some_and(|l| l.is_empty()) {
+            lines.pop();
+        }
+
+        let mut parser = Parser::new(content, path);
+        let mut headers: Vec<Header> = Vec::new();
+        let mut entries = Vec::new();
+        while let Some(c) = parser.peek() {
+            match c {
+                c if c.is_whitespace() => {
+                    parser.next();
+                }
+                '#' | ';' => {
+                    parser.rest_of_line();
+                }
+                '[' => {
+                    let line = parser.line;
+                    parser.next();
+                    let (section, subsection) = parser.header()?;
+                    headers.push(Header {
+                        section,
+                        subsection,
+                        line,
+                    });
+                }
+                c if c.is_ascii_alphabetic() => {
+                    let Some(header) = headers.len().checked_sub(1) else {
+                        return Err(parser.error());
+                    };
+                    let start = parser.line;
+                    let name = parser.name();
+                    let (value, comment) = parser.value()?;
+                    entries.push(ConfigEntry {
+                        section: headers[header].section.clone(),
+                        subsection: headers[header].subsection.clone(),
+                        name,
+                        value,
+                        lines: start..parser.end_line(),
+                        header,
+                        comment,
+                    });
+                }
+                _ => return Err(parser.error()),
+            }
+        }
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            lines,
+            headers,
+            entries,
+        })
+    }
+
+    /// The last value of `key`, which is the one Git uses.
+    pub fn get(&self, key: &str) -> Option<&ConfigEntry> {
+        let key = normalize_key(key);
+        self.entries.iter().rev().find(|e| e.key() == key)
+    }
+
+    /// Sets `key` to `value`, rewriting the line of an existing setting or
+    /// adding one to the last matching section. Like `git config`, this
+    /// refuses to pick one of several values.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), GitError> {
+        let (section, subsection, name) = split_key(key)?;
+        let matching = self.matching(key);
+        if matching.len() > 1 {
+            return Err(GitError(format!(
+                "{} has multiple values in {}",
+                key,
+                self.path.display()
+            )));
+        }
+
+        if let Some(&index) = matching.first() {
+            self.check_alone(index)?;
+            let entry = &self.entries[index];
+            let first = &self.lines[entry.lines.start];
+            let indent: String = first.chars().take_while(|c| c.is_whitespace()).collect();
+            let written_name: String = first
+                .trim_start()
+                .chars()
+                .take_while(|c| is_key_char(*c))
+                .collect();
+            let mut line = format!("{}{} = {}", indent, written_name, quote_value(value));
+            if let Some(comment) = &entry.comment {
+                line.push(' ');
+                line.push_str(comment);
+            }
+            line.push_str(self.line_ending());
+            let range = entry.lines.clone();
+            self.lines.splice(range, [line]);
+        } else if let Some(header) = self.headers.iter().rposition(|h| {
+            h.section == section.to_lowercase() && h.subsection.as_deref() == subsection
+        }) {
+            let last = self.entries.iter().rev().find(|e| e.header == header);
+            // Indent like the setting before, unless it follows the header
+            // on the same line.
+            let (at, indent) = match last {
+                Some(entry) if entry.lines.start != self.headers[header].line => (
+                    entry.lines.end,
+                    self.lines[entry.lines.start]
+                        .chars()
+                        .take_while(|c| c.is_whitespace())
+                        .collect(),
+                ),
+                Some(entry) => (entry.lines.end, "\t".to_string()),
+                None => (self.headers[header].line + 1, "\t".to_string()),
+            };
+            let line = format!(
+                "{}{} = {}{}",
+                indent,
+                name,
+                quote_value(value),
+                self.line_ending()
+            );
+            self.lines.insert(at, line);
+        } else {
+            let header = match subsection {
+                Some(subsection) => format!("[{} \"{}\"]", section, escape_subsection(subsection)),
+                None => format!("[{}]", section),
+            };
+            let ending = self.line_ending().to_string();
+            self.lines.push(format!("{}{}", header, ending));
+            self.lines
+                .push(format!("\t{} = {}{}", name, quote_value(value), ending));
+        }
+        self.reparse()
+    }
+
+    /// Removes every value of `key`. Removing a key that is not set succeeds.
+    pub fn unset(&mut self, key: &str) -> Result<(), GitError> {
+        split_key(key)?;
+        let matching = self.matching(key);
+        if matching.is_empty() {
+            return Ok(());
+        }
+        for &index in &matching {
+            self.check_alone(index)?;
+        }
+        for &index in matching.iter().rev() {
+            let range = self.entries[index].lines.clone();
+            self.lines.drain(range);
+        }
+        self.reparse()
+    }
+
+    /// Writes the file back the way Git does: into `<path>.lock`, which is
+    /// then renamed over `path`. A `.lock` left by a running `git` makes
+    /// this fail instead of losing either write.
+    pub fn save(&self) -> Result<(), GitError> {
+        let target = fs::canonicalize(&self.path).unwrap_or_else(|_| self.path.clone());
+        let mut lock_name = target.clone().into_os_string();
+        lock_name.push(".lock");
+        let lock = PathBuf::from(lock_name);
+
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&lock)
+            .map_err(|e| {
+                GitError(format!(
+                    "Cannot lock config file {}: {}",
+                    target.display(),
+                    e
+                ))
+            })?;
+        let result = (|| {
+            if let Ok(metadata) = fs::metadata(&target) {
+                file.set_permissions(metadata.permissions())?;
+            }
+            file.write_all(self.to_string().as_bytes())?;
+            file.sync_all()?;
+            fs::rename(&lock, &target)
+        })();
+        if result.is_err() {
+            let _ = fs::remove_file(&lock);
+        }
+        result.map_err(|e| GitError(format!("Cannot write {}: {}", target.display(), e)))
+    }
+
+    fn matching(&self, key: &str) -> Vec<usize> {
+        let key = normalize_key(key);
+        (0..self.entries.len())
+            .filter(|&i| self.entries[i].key() == key)
+            .collect()
+    }
+
+    /// Edits replace whole lines, so a setting sharing a line with a header
+    /// or another setting is left for the user to change.
+    fn check_alone(&self, index: usize) -> Result<(), GitError> {
+        let lines = &self.entries[index].lines;
+        let shared =
+            self.headers.iter().any(|h| lines.contains(&h.line))
+                || self.entries.iter().enumerate().any(|(i, e)| {
+                    i != index && e.lines.start < lines.end && lines.start < e.lines.end
+                });
+        if shared {
+            return Err(GitError(format!(
+                "{}:{}: cannot edit a setting that shares its line; move it to a line of its own",
+                self.path.display(),
+                lines.start + 1
+            )));
+        }
+        Ok(())
+    }
+
+    /// Keeps Windows line endings in files that use them.
+    fn line_ending(&self) -> &'static str {
+        if self.lines.first().is_some_and(|l| l.ends_with('\r')) {
+            "\r"
+        } else {
+            ""
+        }
+    }
+
+    fn reparse(&mut self) -> Result<(), GitError> {
+        *self = Self::parse(&self.to_string(), &self.path)?;
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for ConfigFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads config text the way Git's own parser does.
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    path: &'a Path,
+    /// Zero-based line of the next character.
+    line: usize,
+    /// Whether the last character read ended a line.
+    at_line_start: bool,
+}
+
+impl<'a> Parser<'a> {
+    fn new(content: &'a str, path: &'a Path) -> Self {
+        Self {
+            chars: content.chars().peekable(),
+            path,
+            line: 0,
+            at_line_start: true,
+        }
+    }
+
+    /// The next character, with `\r\n` read as `\n`.
+    fn next(&mut self) -> Option<char> {
+        let mut c = self.chars.next()?;
+        if c == '\r' && self.chars.peek() == Some(&'\n') {
+            c = self.chars.next()?;
+        }
+        self.at_line_start = c == '\n';
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+
+    /// One past the last line read so far.
+    fn end_line(&self) -> usize {
+        if self.at_line_start {
+            self.line
+        } else {
+            self.line + 1
+        }
+    }
+
+    fn error(&self) -> GitError {
+        GitError(format!(
+            "bad config line {} in file {}",
+            self.line + 1,
+            self.path.display()
+        ))
+    }
+
+    fn rest_of_line(&mut self) -> String {
+        let mut rest = String::new();
+        while let Some(c) = self.peek() {
+            if c == '\n' || (c == '\r' && self.chars.clone().nth(1) == Some('\n')) {
+                break;
+            }
+            rest.push(c);
+            self.next();
+        }
+        rest
+    }
+
+    /// The rest of a `[section]` or `[section "subsection"]` header.
+    fn header(&mut self) -> Result<(String, Option<String>), GitError> {
+        let mut section = String::new();
+        loop {
+            match self.next() {
+                Some(']') => break,
+                Some(c) if c.is_whitespace() && c != '\n' => {
+                    let subsection = self.quoted_subsection()?;
+                    return Ok((section, Some(subsection)));
+                }
+                Some(c) if is_key_char(c) || c == '.' => section.push(c.to_ascii_lowercase()),
+                _ => return Err(self.error()),
+            }
+        }
+        if section.is_empty() {
+            return Err(self.error());
+        }
+        // The deprecated `[section.subsection]` form.
+        Ok(match section.split_once('.') {
+            Some((section, subsection)) => (section.to_string(), Some(subsection.to_string())),
+            None => (section, None),
+        })
+    }
+
+    fn quoted_subsection(&mut self) -> Result<String, GitError> {
+        while self.peek().is_some_and(|c| c == ' ' || c == '\t') {
+            self.next();
+        }
+        if self.next() != Some('"') {
+            return Err(self.error());
+        }
+        let mut subsection = String::new();
+        loop {
+            match self.next() {
+                Some('"') => break,
+                Some('\\') => match self.next() {
+                    Some(c) if c != '\n' => subsection.push(c),
+                    _ => return Err(self.error()),
+                },
+                Some(c) if c != '\n' => subsection.push(c),
+                _ => return Err(self.error()),
+            }
+        }
+        if self.next() != Some(']') {
+            return Err(self.error());
+        }
+        Ok(subsection)
+    }
+
+    fn name(&mut self) -> String {
+        let mut name = String::new();
+        while let Some(c) = self.peek().filter(|c| is_key_char(*c)) {
+            name.push(c.to_ascii_lowercase());
+            self.next();
+        }
+        name
+    }
+
+    /// What follows a variable name: nothing, or `=` and a value, up to the
+    /// end of the line. Returns the value and any trailing comment.
+    fn value(&mut self) -> Result<(Option<String>, Option<String>), GitError> {
+        while self.peek().is_some_and(|c| c == ' ' || c == '\t') {
+            self.next();
+        }
+        match self.next() {
+            None | Some('\n') => return Ok((None, None)),
+            Some('=') => {}
+            Some(_) => return Err(self.error()),
+        }
+
+        let mut value = String::new();
+        let mut spaces = 0;
+        let mut quoted = false;
+        loop {
+            let Some(c) = self.next() else {
+                if quoted {
+                    return Err(self.error());
+                }
+                break;
+            };
+            match c {
+                '\n' if quoted => return Err(self.error()),
+                '\n' => break,
+                c if c.is_whitespace() && !quoted => {
+                    if !value.is_empty() {
+                        spaces += 1;
+                    }
+                }
+                '#' | ';' if !quoted => {
+                    let comment = format!("{}{}", c, self.rest_of_line());
+                    self.next();
+                    return Ok((Some(value), Some(comment)));
+                }
+                c => {
+                    value.extend(std::iter::repeat_n(' ', spaces));
+                    spaces = 0;
+                    match c {
+                        '\\' => match self.next() {
+                            Some('\n') => {}
+                            Some('t') => value.push('\t'),
+                            Some('b') => value.push('\u{8}'),
+                            Some('n') => value.push('\n'),
+                            Some(c @ ('\\' | '"')) => value.push(c),
+                            _ => return Err(self.error()),
+                        },
+                        '"' => quoted = !quoted,
+                        c => value.push(c),
+                    }
+                }
+            }
+        }
+        Ok((Some(value), None))
+    }
+}
+
+fn is_key_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-'
+}
+
+/// Splits `section[.subsection].name`, checking it is a valid key.
+fn split_key(key: &str) -> Result<(&str, Option<&str>, &str), GitError> {
+    let invalid = || GitError(format!("invalid key: {}", key));
+    let (section, rest) = key.split_once('.').ok_or_else(invalid)?;
+    let (subsection, name) = match rest.rsplit_once('.') {
+        Some((subsection, name)) => (Some(subsection), name),
+        None => (None, rest),
+    };
+    let valid_name = |s: &str| {
+        s.chars().next().is_some_and(|c| c.is_ascii_alphabetic()) && s.chars().all(is_key_char)
+    };
+    if !section.chars().all(is_key_char) || section.is_empty() || !valid_name(name) {
+        return Err(invalid());
+    }
+    Ok((section, subsection, name))
+}
+
+/// Section and variable names are case-insensitive, subsections are not.
+pub fn normalize_key(key: &str) -> String {
+    match (key.find('.'), key.rfind('.')) {
+        (Some(first), Some(last)) if first < last => format!(
+            "{}{}{}",
+            key[..first].to_lowercase(),
+            &key[first..last],
+            key[last..].to_lowercase()
+        ),
+        _ => key.to_lowercase(),
+    }
+}
+
+fn escape_subsection(subsection: &str) -> String {
+    subsection.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Reads a boolean the way Git does; a bare `name` is true.
+pub fn parse_bool(value: Option<&str>) -> Option<bool> {
+    let Some(value) = value else {
+        return Some(true);
+    };
+    match value.to_ascii_lowercase().as_str() {
+        "true" | "yes" | "on" => Some(true),
+        "false" | "no" | "off" | "" => Some(false),
+        number => number.parse::<i64>().ok().map(|n| n != 0),
+    }
+}
+
+/// Writes `value` so that [`Parser::value`] reads it back unchanged, quoting
+/// only when Git would.
+pub fn quote_value(value: &str) -> String {
+    let needs_quotes = value.is_empty()
+        || value.starts_with(char::is_whitespace)
+        || value.ends_with(char::is_whitespace)
+        || value.contains(['#', ';']);
+    let mut out = String::with_capacity(value.len() + 2);
+    if needs_quotes {
+        out.push('"');
+    }
+    for c in value.chars() {
+        match c {
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\u{8}' => out.push_str("\\b"),
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c => out.push(c),
+        }
+    }
+    if needs_quotes {
+        out.push('"');
+    }
+    out
+}
+
+/// A value read by a [`ConfigSet`] and the file it came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigValue {
+    /// The canonical key, as returned by [`ConfigEntry::key`].
+    pub key: String,
+    pub value: Option<String>,
+    /// The scope of the top-level file, even for values from its includes.
+    pub scope: ConfigScope,
+    pub origin: PathBuf,
+}
+
+/// What `includeIf` conditions are tested against.
+#[derive(Debug, Clone, Default)]
+pub struct IncludeContext {
+    /// For `gitdir:` conditions; outside a repository they never match.
+    pub git_dir: Option<PathBuf>,
+    /// For `onbranch:` conditions.
+    pub branch: Option<String>,
+}
+
+/// Every value of the config files Git reads, lowest precedence first.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigSet {
+    pub values: Vec<ConfigValue>,
+}
+
+impl ConfigSet {
+    /// Reads `files` in order, skipping those that do not exist.
+    pub fn load(
+        files: &[(ConfigScope, PathBuf)],
+        context: &IncludeContext,
+    ) -> Result<Self, GitError> {
+        let mut reader = Reader {
+            context,
+            remote_urls: None,
+            uses_hasconfig: false,
+            values: Vec::new(),
+            skipped: Vec::new(),
+        };
+        reader.read_all(files)?;
+        if !reader.uses_hasconfig {
+            return Ok(Self {
+                values: reader.values,
+            });
+        }
+
+        // `hasconfig:remote.*.url:` depends on every remote URL, so Git
+        // collects those first, without the includes such conditions guard.
+        let urls = reader
+            .values
+            .iter()
+            .filter(|v| v.key.starts_with("remote.") && v.key.ends_with(".url"))
+            .filter_map(|v| v.value.clone())
+            .collect();
+        reader.remote_urls = Some(urls);
+        reader.values.clear();
+        reader.read_all(files)?;
+        Ok(Self {
+            values: reader.values,
+        })
+    }
+
+    /// The value Git uses for `key`: the last one read.
+    pub fn get(&self, key: &str) -> Option<&ConfigValue> {
+        let key = normalize_key(key);
+        self.values.iter().rev().find(|v| v.key == key)
+    }
+
+    /// Every value of `key`, in the order read.
+    pub fn get_all(&self, key: &str) -> Vec<&ConfigValue> {
+        let key = normalize_key(key);
+        self.values.iter().filter(|v| v.key == key).collect()
+    }
+
+    /// The distinct subsections of `section` that hold a setting, in the
+    /// order they first appear.
+    pub fn subsections(&self, section: &str) -> Vec<String> {
+        let prefix = format!("{}.", section.to_lowercase());
+        let mut subsections: Vec<String> = Vec::new();
+        for value in &self.values {
+            let Some((subsection, _)) = value
+                .key
+                .strip_prefix(&prefix)
+                .and_then(|rest| rest.rsplit_once('.'))
+            else {
+                continue;
+            };
+            if !subsections.iter().any(|s| s == subsection) {
+                subsections.push(subsection.to_string());
+            }
+        }
+        subsections
+    }
+
+    /// Applies the longest matching `url.<base>.insteadOf` to `url`, as Git
+    /// does before using a remote URL.
+    pub fn rewrite_url(&self, url: &str) -> String {
+        self.values
+            .iter()
+            .filter_map(|v| {
+                let base = v.key.strip_prefix("url.")?.strip_suffix(".insteadof")?;
+                let prefix = v.value.as_deref()?;
+                url.starts_with(prefix).then_some((base, prefix))
+            })
+            .max_by_key(|(_, prefix)| prefix.len())
+            .map(|(base, prefix)| format!("{}{}", base, &url[prefix.len()..]))
+            .unwrap_or_else(|| url.to_string())
+    }
+}
+
+struct Reader<'a> {
+    context: &'a IncludeContext,
+    /// `None` while collecting URLs, when `hasconfig:` never matches.
+    remote_urls: Option<Vec<String>>,
+    uses_hasconfig: bool,
+    values: Vec<ConfigValue>,
+    /// System and global files left out because they could not be read.
+    skipped: Vec<PathBuf>,
+}
+
+impl Reader<'_> {
+    fn read_all(&mut self, files: &[(ConfigScope, PathBuf)]) -> Result<(), GitError> {
+        for (scope, path) in files {
+            if !path.is_file() || self.skipped.contains(path) {
+                continue;
+            }
+            let start = self.values.len();
+            match self.read(path, *scope, 0) {
+                Ok(()) => {}
+                // A broken file outside the repository should not keep guse
+                // from reading and writing the repository's own config.
+                Err(e) if matches!(scope, ConfigScope::System | ConfigScope::Global) => {
+                    log::warn!("Ignoring {}: {}", path.display(), e);
+                    self.values.truncate(start);
+                    self.skipped.push(path.clone());
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    fn read(&mut self, path: &Path, scope: ConfigScope, depth: usize) -> Result<(), GitError> {
+        let file = ConfigFile::load(path)?;
+        for entry in file.entries {
+            let include = match (&*entry.section, &entry.subsection, &*entry.name) {
+                ("include", None, "path") => true,
+                ("includeif", Some(condition), "path") => self.matches(condition, path)?,
+                _ => false,
+            };
+            let key = entry.key();
+            let value = entry.value.clone();
+            self.values.push(ConfigValue {
+                key: key.clone(),
+                value: entry.value,
+                scope,
+                origin: path.to_path_buf(),
+            });
+            if !include {
+                continue;
+            }
+
+            let value = value.ok_or_else(|| {
+                GitError(format!("missing value for '{}' in {}", key, path.display()))
+            })?;
+            let target = relative_to(&expand_home(&value), path);
+            if depth + 1 > MAX_INCLUDE_DEPTH {
+                return Err(GitError(format!(
+                    "exceeded maximum include depth ({}) while including {} from {}",
+                    MAX_INCLUDE_DEPTH,
+                    target.display(),
+                    path.display()
+                )));
+            }
+            // Git skips includes that do not exist.
+            if target.is_file() {
+                self.read(&target, scope, depth + 1)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn matches(&mut self, condition: &str, file: &Path) -> Result<bool, GitError> {
+        if let Some(pattern) = condition.strip_prefix("gitdir:") {
+            return Ok(self.gitdir_matches(pattern, file, true));
+        }
+        if let Some(pattern) = condition.strip_prefix("gitdir/i:") {
+            return Ok(self.gitdir_matches(pattern, file, false));
+        }
+        if let Some(pattern) = condition.strip_prefix("onbranch:") {
+            let Some(branch) = &self.context.branch else {
+                return Ok(false);
+            };
+            let pattern = match pattern.ends_with('/') {
+                true => format!("{}**", pattern),
+                false => pattern.to_string(),
+            };
+            return Ok(wildmatch(&pattern, branch, true));
+        }
+        if let Some(pattern) = condition.strip_prefix("hasconfig:remote.*.url:") {
+            self.uses_hasconfig = true;
+            return Ok(self
+                .remote_urls
+                .as_ref()
+                .is_some_and(|urls| urls.iter().any(|url| wildmatch(pattern, url, true))));
+        }
+        // Git ignores conditions it does not know.
+        Ok(false)
+    }
+
+    fn gitdir_matches(&self, pattern: &str, file: &Path, case_sensitive: bool) -> bool {
+        let Some(git_dir) = &self.context.git_dir else {
+            return false;
+        };
+        let mut pattern = expand_home(pattern);
+        if let Some(rest) = pattern.strip_prefix("./") {
+            let dir = file.parent().unwrap_or(Path::new(""));
+            pattern = format!("{}/{}", dir.to_string_lossy().trim_end_matches('/'), rest);
+        }
+        if !Path::new(&pattern).is_absolute() {
+            pattern = format!("**/{}", pattern);
+        }
+        if pattern.ends_with('/') {
+            pattern.push_str("**");
+        }
+
+        let mut candidates = vec![git_dir.clone()];
+        if let Ok(real) = fs::canonicalize(git_dir) {
+            candidates.push(real);
+        }
+        candidates
+            .iter()
+            .any(|dir| wildmatch(&pattern, &dir.to_string_lossy(), case_sensitive))
+    }
+}
+
+/// Matches like Git's `wildmatch` with `WM_PATHNAME`: `*` and `?` stay
+/// within a path segment, and `**` crosses segments only as a whole segment
+/// (`a/**/b`, `**/b`, `a/**`); elsewhere it acts like `*`.
+fn wildmatch(pattern: &str, text: &str, case_sensitive: bool) -> bool {
+    dowild(pattern.as_bytes(), text.as_bytes(), !case_sensitive) == Wild::Match
+}
+
+/// Outcome of [`dowild`]. The abort variants let an outer `*` or `**` stop
+/// trying further positions when no later one can match either.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Wild {
+    Match,
+    NoMatch,
+    AbortAll,
+    AbortToStarStar,
+}
+
+/// A port of `dowild` from Git's `wildmatch.c`. Bytes past the end of
+/// either slice read as NUL, as they do in C.
+fn dowild(pattern: &[u8], text: &[u8], fold: bool) -> Wild {
+    let at = |s: &[u8], i: usize| s.get(i).copied().unwrap_or(0);
+    let fold_case = |c: u8| if fold { c.to_ascii_lowercase() } else { c };
+
+    let (mut p, mut t) = (0, 0);
+    while p < pattern.len() {
+        let mut t_ch = at(text, t);
+        if t_ch == 0 && pattern[p] != b'*' {
+            return Wild::AbortAll;
+        }
+        t_ch = fold_case(t_ch);
+        match fold_case(pattern[p]) {
+            b'?' => {
+                if t_ch == b'/' {
+                    return Wild::NoMatch;
+                }
+            }
+            b'*' => {
+                p += 1;
+                let mut match_slash = false;
+                if at(pattern, p) == b'*' {
+                    let before = p.checked_sub(2).map(|i| pattern[i]);
+                    while at(pattern, p) == b'*' {
+                        p += 1;
+                    }
+                    let next = at(pattern, p);
+                    if before.is_none_or(|c| c == b'/')
+                        && (next == 0
+                            || next == b'/'
+                            || (next == b'\\' && at(pattern, p + 1) == b'/'))
+                    {
+                        // `a/**/b` also matches `a/b`.
+                        if next == b'/'
+                            && dowild(&pattern[p + 1..], &text[t..], fold) == Wild::Match
+                        {
+                            return Wild::Match;
+                        }
+                        match_slash = true;
+                    }
+                }
+                if p == pattern.len() {
+                    // A trailing `*` must not cross a `/`; `**` takes the rest.
+                    if !match_slash && text[t..].contains(&b'/') {
+                        return Wild::NoMatch;
+                    }
+                    return Wild::Match;
+                }
+                if !match_slash && pattern[p] == b'/' {
+                    // `*/` takes the rest of the current segment.
+                    let Some(slash) = text[t..].iter().position(|&c| c == b'/') else {
+                        return Wild::NoMatch;
+                    };
+                    t += slash + 1;
+                    p += 1;
+                    continue;
+                }
+                while t_ch != 0 {
+                    // Skip ahead to the next occurrence of a literal that
+                    // follows the star; what comes before it is the star's.
+                    let next = at(pattern, p);
+                    if !matches!(next, b'*' | b'?' | b'[' | b'\\') {
+                        let p_ch = fold_case(next);
+                        loop {
+                            t_ch = at(text, t);
+                            if t_ch == 0 || (!match_slash && t_ch == b'/') {
+                                break;
+                            }
+                            t_ch = fold_case(t_ch);
+                            if t_ch == p_ch {
+                                break;
+                            }
+                            t += 1;
+                        }
+                        if t_ch != p_ch {
+                            return match match_slash {
+                                true => Wild::AbortAll,
+                                false => Wild::AbortToStarStar,
+                            };
+                        }
+                    }
+                    match dowild(&pattern[p..], &text[t..], fold) {
+                        Wild::NoMatch if !match_slash && t_ch == b'/' => {
+                            return Wild::AbortToStarStar
+                        }
+                        Wild::NoMatch => {}
+                        Wild::AbortToStarStar if match_slash => {}
+                        matched => return matched,
+                    }
+                    t += 1;
+                    t_ch = at(text, t);
+                }
+                return Wild::AbortAll;
+            }
+            b'[' => match match_class(pattern, &mut p, t_ch, fold) {
+                Some(true) => {}
+                Some(false) => return Wild::NoMatch,
+                None => return Wild::AbortAll,
+            },
+            b'\\' => {
+                // The escaped byte is compared as written, as Git does.
+                p += 1;
+                if t_ch != at(pattern, p) {
+                    return Wild::NoMatch;
+                }
+            }
+            p_ch => {
+                if t_ch != p_ch {
+                    return Wild::NoMatch;
+                }
+            }
+        }
+        p += 1;
+        t += 1;
+    }
+
+    match t < text.len() {
+        true => Wild::NoMatch,
+        false => Wild::Match,
+    }
+}
+
+/// Matches `t_ch` against the bracket expression starting at `pattern[*p]`,
+/// leaving `*p` on its closing `]`. `None` when the expression is malformed,
+/// which fails the whole match.
+fn match_class(pattern: &[u8], p: &mut usize, t_ch: u8, fold: bool) -> Option<bool> {
+    let at = |i: usize| pattern.get(i).copied().unwrap_or(0);
+
+    *p += 1;
+    let mut p_ch = at(*p);
+    if p_ch == b'^' {
+        p_ch = b'!';
+    }
+    let negated = p_ch == b'!';
+    if negated {
+        *p += 1;
+        p_ch = at(*p);
+    }
+
+    let mut prev_ch = 0;
+    let mut matched = false;
+    loop {
+        if p_ch == 0 {
+            return None;
+        }
+        if p_ch == b'\\' {
+            *p += 1;
+            p_ch = at(*p);
+            if p_ch == 0 {
+                return None;
+            }
+            matched |= t_ch == p_ch;
+        } else if p_ch == b'-' && prev_ch != 0 && at(*p + 1) != 0 && at(*p + 1) != b']' {
+            *p += 1;
+            p_ch = at(*p);
+            if p_ch == b'\\' {
+                *p += 1;
+                p_ch = at(*p);
+                if p_ch == 0 {
+                    return None;
+                }
+            }
+            let upper = t_ch.to_ascii_uppercase();
+            matched |= (prev_ch..=p_ch).contains(&t_ch)
+                || (fold && t_ch.is_ascii_lowercase() && (prev_ch..=p_ch).contains(&upper));
+            // A range cannot start another range.
+            p_ch = 0;
+        } else if p_ch == b'[' && at(*p + 1) == b':' {
+            let start = *p + 2;
+            *p = start;
+            while at(*p) != 0 && at(*p) != b']' {
+                *p += 1;
+            }
+            if at(*p) == 0 {
+                return None;
+            }
+            if *p == start || pattern[*p - 1] != b':' {
+                // No closing `:]`: the `[` is an ordinary member.
+                *p = start - 2;
+                p_ch = b'[';
+                matched |= t_ch == p_ch;
+            } else {
+                matched |= match &pattern[start..*p - 1] {
+                    b"alnum" => t_ch.is_ascii_alphanumeric(),
+                    b"alpha" => t_ch.is_ascii_alphabetic(),
+                    b"blank" => t_ch == b' ' || t_ch == b'\t',
+                    b"cntrl" => t_ch.is_ascii_control(),
+                    b"digit" => t_ch.is_ascii_digit(),
+                    b"graph" => t_ch.is_ascii_graphic(),
+                    b"lower" => t_ch.is_ascii_lowercase(),
+                    b"print" => t_ch.is_ascii_graphic() || t_ch == b' ',
+                    b"punct" => t_ch.is_ascii_punctuation(),
+                    b"space" => matches!(t_ch, b' ' | b'\t' | b'\n' | b'\r'),
+                    b"upper" => t_ch.is_ascii_uppercase() || (fold && t_ch.is_ascii_lowercase()),
+                    b"xdigit" => t_ch.is_ascii_hexdigit(),
+                    _ => return None,
+                };
+                p_ch = 0;
+            }
+        } else {
+            matched |= t_ch == p_ch;
+        }
+        prev_ch = p_ch;
+        *p += 1;
+        p_ch = at(*p);
+        if p_ch == b']' {
+            break;
+        }
+    }
+    Some(matched != negated && t_ch != b'/')
+}
+
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest).to_string_lossy().to_string(),
+        _ => path.to_string(),
+    }
+}
+
+/// Resolves an include path against the directory of the including file.
+fn relative_to(path: &str, file: &Path) -> PathBuf {
+    let path = PathBuf::from(path);
+    match file.parent() {
+        Some(dir) if path.is_relative() => dir.join(path),
+        _ => path,
+    }
+}
@@ -0,0 +1,281 @@
+//! Conditional includes in the global gitconfig. guse keeps its
+//! `[includeIf]` sections between two marker comments so it can rewrite them
+//! without touching anything the user wrote; each one includes a fragment
+//! holding a profile's identity.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::backup::{BackupKind, BackupStore};
+use crate::utils::file::atomic_write;
+
+use super::{GitError, GitVersion};
+
+pub const BLOCK_BEGIN: &str = "# BEGIN guse managed block; edit with `guse dir` or `guse url`";
+pub const BLOCK_END: &str = "# END guse managed block";
+
+/// Start of [`BLOCK_BEGIN`] that identifies the block whatever the rest says.
+const BLOCK_BEGIN_PREFIX: &str = "# BEGIN guse managed block";
+
+/// Prefix of conditions matching repositories below a directory.
+pub const GITDIR_PREFIX: &str = "gitdir:";
+
+/// Prefix of conditions matching repositories by a remote URL.
+pub const HASCONFIG_PREFIX: &str = "hasconfig:remote.*.url:";
+
+/// First release that understands [`HASCONFIG_PREFIX`]. Older ones ignore
+/// such rules, so the identity would silently not apply.
+pub const HASCONFIG_MIN_VERSION: GitVersion = GitVersion(2, 36, 0);
+
+/// One `[includeIf "<condition>"]` section with its `path`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IncludeRule {
+    pub condition: String,
+    pub path: PathBuf,
+}
+
+impl IncludeRule {
+    /// The profile whose fragment is included, taken from the file name.
+    pub fn profile(&self) -> Option<String> {
+        fragment_profile(&self.path)
+    }
+
+    /// The directory of a `gitdir:` condition.
+    pub fn gitdir(&self) -> Option<&str> {
+        self.condition.strip_prefix(GITDIR_PREFIX)
+    }
+
+    /// The URL pattern of a `hasconfig:remote.*.url:` condition.
+    pub fn url_pattern(&self) -> Option<&str> {
+        self.condition.strip_prefix(HASCONFIG_PREFIX)
+    }
+}
+
+/// A gitconfig file split around the guse block.
+#[derive(Debug, Clone)]
+pub struct IncludeDocument {
+    pub path: PathBuf,
+    before: Vec<String>,
+    pub rules: Vec<IncludeRule>,
+    /// Lines that followed the block when read; written before it.
+    after: Vec<String>,
+}
+
+impl IncludeDocument {
+    /// Opens `path`. A missing file yields an empty document.
+    pub fn load(path: &Path) -> Result<Self, GitError> {
+        let content = if path.exists() {
+            fs::read_to_string(path)
+                .map_err(|e| GitError(format!("Cannot read {}: {}", path.display(), e)))?
+        } else {
+            String::new()
+        };
+        Self::parse(&content, path)
+    }
+
+    pub fn parse(content: &str, path: &Path) -> Result<Self, GitError> {
+        let lines: Vec<String> = content.lines().map(str::to_string).collect();
+        let begin = lines
+            .iter()
+            .position(|l| l.trim().starts_with(BLOCK_BEGIN_PREFIX));
+        let Some(begin) = begin else {
+            return Ok(Self {
+                path: path.to_path_buf(),
+                before: lines,
+                rules: Vec::new(),
+                after: Vec::new(),
+            });
+        };
+        let end = lines[begin..]
+            .iter()
+            .position(|l| l.trim() == BLOCK_END)
+            .map(|i| begin + i)
+            .ok_or_else(|| {
+                GitError(format!(
+                    "{}: guse block is not closed by '{}'; fix the file by hand",
+                    path.display(),
+                    BLOCK_END
+                ))
+            })?;
+
+        let mut rules = Vec::new();
+        let mut condition: Option<String> = None;
+        for line in &lines[begin + 1..end] {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            if let Some(section) = line
+                .strip_prefix("[includeIf \"")
+                .and_then(|s| s.strip_suffix("\"]"))
+            {
+                condition = Some(unescape(section));
+            } else if let (Some(c), Some((key, value))) = (&condition, line.split_once('=')) {
+                if key.trim() == "path" {
+                    rules.push(IncludeRule {
+                        condition: c.clone(),
+                        path: PathBuf::from(unescape(unquote(value.trim()))),
+                    });
+                }
+            }
+        }
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            before: lines[..begin].to_vec(),
+            rules,
+            after: lines[end + 1..].to_vec(),
+        })
+    }
+
+    /// Adds `rule`, replacing a rule with the same condition.
+    pub fn set_rule(&mut self, rule: IncludeRule) {
+        match self
+            .rules
+            .iter_mut()
+            .find(|r| r.condition == rule.condition)
+        {
+            Some(existing) => *existing = rule,
+            None => self.rules.push(rule),
+        }
+    }
+
+    /// Removes the rule for `condition`, returning it.
+    pub fn remove_rule(&mut self, condition: &str) -> Option<IncludeRule> {
+        let index = self.rules.iter().position(|r| r.condition == condition)?;
+        Some(self.rules.remove(index))
+    }
+
+    /// Writes the document back, keeping a backup of the previous version.
+    pub fn save(&self, backups: &BackupStore) -> Result<(), GitError> {
+        backups
+            .create(BackupKind::GitConfig, &self.path)
+            .map_err(|e| GitError(e.to_string()))?;
+        atomic_write(&self.path, self.to_string())
+            .map_err(|e| GitError(format!("Cannot write {}: {}", self.path.display(), e)))
+    }
+}
+
+impl std::fmt::Display for IncludeDocument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // The block is moved to the end on every write, so that the included
+        // identity still wins over a `[user]` section added below it later.
+        let mut lines = self.before.clone();
+        let seam_blank = lines.last().is_none_or(|l| l.trim().is_empty());
+        lines.extend(
+            self.after
+                .iter()
+                .skip_while(|l| seam_blank && l.trim().is_empty())
+                .cloned(),
+        );
+        while lines.last().is_some_and(|l| l.trim().is_empty()) {
+            lines.pop();
+        }
+
+        if !self.rules.is_empty() {
+            if lines.last().is_some_and(|l| !l.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            lines.push(BLOCK_BEGIN.to_string());
+            for rule in &self.rules {
+                lines.push(format!("[includeIf \"{}\"]", escape(&rule.condition)));
+                lines.push(format!(
+                    "\tpath = \"{}\"",
+                    escape(&rule.path.to_string_lossy())
+                ));
+            }
+            lines.push(BLOCK_END.to_string());
+        }
+
+        if lines.is_empty() {
+            return Ok(());
+        }
+        writeln!(f, "{}", lines.join("\n"))
+    }
+}
+
+/// Contents of a fragment setting a profile's identity.
+pub fn render_fragment(name: &str, email: &str, ssh_command: Option<&str>) -> String {
+    let mut out = String::from("# Generated by guse; changes are overwritten.\n");
+    out.push_str("[user]\n");
+    out.push_str(&format!("\tname = \"{}\"\n", escape(name)));
+    out.push_str(&format!("\temail = \"{}\"\n", escape(email)));
+    if let Some(command) = ssh_command {
+        out.push_str("[core]\n");
+        out.push_str(&format!("\tsshCommand = \"{}\"\n", escape(command)));
+    }
+    out
+}
+
+/// Fragment file names are `<profile>.gitconfig`.
+pub fn fragment_file_name(profile: &str) -> String {
+    format!("{}.gitconfig", profile)
+}
+
+pub fn fragment_profile(path: &Path) -> Option<String> {
+    path.file_name()?
+        .to_str()?
+        .strip_suffix(".gitconfig")
+        .map(str::to_string)
+}
+
+/// The `gitdir:` condition matching every repository below `dir`.
+pub fn gitdir_condition(dir: &Path) -> String {
+    let dir = dir.to_string_lossy().replace('\\', "/");
+    format!("{}{}/", GITDIR_PREFIX, dir.trim_end_matches('/'))
+}
+
+/// The condition matching repositories with a remote URL like `pattern`.
+pub fn hasconfig_condition(pattern: &str) -> String {
+    format!("{}{}", HASCONFIG_PREFIX, pattern)
+}
+
+/// URL patterns for remotes that go through the SSH host alias `host`, in
+/// both the scp-like and the `ssh://` form. Git only treats `**` as crossing
+/// `/` right after a slash, so the scp-like form needs `*/**`.
+pub fn host_url_patterns(host: &str) -> Vec<String> {
+    vec![
+        format!("git@{}:*/**", host),
+        format!("ssh://git@{}/**", host),
+    ]
+}
+
+/// URL patterns for HTTPS remotes on `host`: those logged in as `user`
+/// when there is one, otherwise any, with or without a login.
+pub fn https_url_patterns(host: &str, user: Option<&str>) -> Vec<String> {
+    match user {
+        Some(user) => vec![format!("https://{}@{}/**", user, host)],
+        None => vec![
+            format!("https://*@{}/**", host),
+            format!("https://{}/**", host),
+        ],
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('t') => out.push('\t'),
+                Some('n') => out.push('\n'),
+                Some(other) => out.push(other),
+                None => {}
+            },
+            c => out.push(c),
+        }
+    }
+    out
+}
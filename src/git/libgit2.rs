@@ -0,0 +1,167 @@
+use git2::{Config, ConfigLevel, ErrorCode, Repository};
+use std::path::{Path, PathBuf};
+
+use super::backend::{ConfigScope, GitBackend};
+use super::config_file::{normalize_key, ConfigValue};
+use super::GitError;
+
+impl From<git2::Error> for GitError {
+    fn from(err: git2::Error) -> Self {
+        GitError(err.message().to_string())
+    }
+}
+
+/// Reads and writes the repository in-process through libgit2.
+pub struct Libgit2Backend {
+    /// `None` outside a repository, where only global config is available.
+    repo: Option<Repository>,
+}
+
+impl Libgit2Backend {
+    /// Opens the repository containing `dir`, if any.
+    pub fn discover(dir: &Path) -> Self {
+        Self {
+            repo: Repository::discover(dir).ok(),
+        }
+    }
+
+    fn repo(&self) -> Result<&Repository, GitError> {
+        self.repo
+            .as_ref()
+            .ok_or_else(|| GitError("fatal: not a git repository".to_string()))
+    }
+
+    fn config(&self) -> Result<Config, GitError> {
+        Ok(match &self.repo {
+            Some(repo) => repo.config()?,
+            None => Config::open_default()?,
+        })
+    }
+
+    fn config_at(&self, scope: ConfigScope) -> Result<Config, GitError> {
+        Ok(match scope {
+            ConfigScope::System => Config::open_default()?.open_level(ConfigLevel::System)?,
+            ConfigScope::Global => Config::open_default()?.open_level(ConfigLevel::Global)?,
+            ConfigScope::Local => self.repo()?.config()?.open_level(ConfigLevel::Local)?,
+            ConfigScope::Worktree => {
+                let repo = self.repo()?;
+                match repo.config()?.open_level(ConfigLevel::Worktree) {
+                    Ok(config) => config,
+                    // Like `git config --worktree`, use the shared config when
+                    // there is only one worktree and no worktreeConfig.
+                    Err(e) if e.code() == ErrorCode::NotFound && !repo.is_worktree() => {
+                        repo.config()?.open_level(ConfigLevel::Local)?
+                    }
+                    Err(e) => return Err(e.into()),
+                }
+            }
+        })
+    }
+
+    /// The file libgit2 reads for `level`. libgit2 does not say which file an
+    /// included value came from, so such values are attributed to the file
+    /// that includes them.
+    fn level_path(&self, level: ConfigLevel) -> Result<PathBuf, GitError> {
+        Ok(match level {
+            ConfigLevel::ProgramData | ConfigLevel::System => Config::find_system()?,
+            ConfigLevel::XDG => Config::find_xdg()?,
+            ConfigLevel::Global => Config::find_global()?,
+            ConfigLevel::Worktree => self.repo()?.path().join("config.worktree"),
+            _ => self.repo()?.commondir().join("config"),
+        })
+    }
+}
+
+/// The [`ConfigScope`] of a libgit2 level; application-level values have none.
+fn level_scope(level: ConfigLevel) -> Option<ConfigScope> {
+    match level {
+        ConfigLevel::ProgramData | ConfigLevel::System => Some(ConfigScope::System),
+        ConfigLevel::XDG | ConfigLevel::Global => Some(ConfigScope::Global),
+        ConfigLevel::Local => Some(ConfigScope::Local),
+        ConfigLevel::Worktree => Some(ConfigScope::Worktree),
+        ConfigLevel::App | ConfigLevel::Highest => None,
+    }
+}
+
+fn not_found_as_none<T>(result: Result<T, git2::Error>) -> Result<Option<T>, GitError> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(e) if e.code() == ErrorCode::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+impl GitBackend for Libgit2Backend {
+    fn config_get(&self, key: &str) -> Result<Option<String>, GitError> {
+        not_found_as_none(self.config()?.get_string(key))
+    }
+
+    fn config_origins(&self, key: &str) -> Result<Vec<ConfigValue>, GitError> {
+        let config = self.config()?;
+        let Some(mut entries) = not_found_as_none(config.multivar(key, None))? else {
+            return Ok(Vec::new());
+        };
+        let mut values = Vec::new();
+        while let Some(entry) = entries.next() {
+            let entry = entry?;
+            let Some(scope) = level_scope(entry.level()) else {
+                continue;
+            };
+            values.push(ConfigValue {
+                key: normalize_key(key),
+                value: entry.value().ok().map(str::to_string),
+                scope,
+                origin: self.level_path(entry.level())?,
+            });
+        }
+        values.sort_by_key(|v| v.scope);
+        Ok(values)
+    }
+
+    fn config_get_in(&self, scope: ConfigScope, key: &str) -> Result<Option<String>, GitError> {
+        not_found_as_none(self.config_at(scope)?.get_string(key))
+    }
+
+    fn config_set(&mut self, scope: ConfigScope, key: &str, value: &str) -> Result<(), GitError> {
+        Ok(self.config_at(scope)?.set_str(key, value)?)
+    }
+
+    fn config_unset(&mut self, scope: ConfigScope, key: &str) -> Result<(), GitError> {
+        not_found_as_none(self.config_at(scope)?.remove(key)).map(|_| ())
+    }
+
+    fn remotes(&self) -> Result<Vec<String>, GitError> {
+        let remotes = self.repo()?.remotes()?;
+        let mut names = Vec::new();
+        for name in remotes.iter() {
+            names.extend(name?.map(str::to_string));
+        }
+        Ok(names)
+    }
+
+    fn remote_url(&self, remote: &str) -> Result<Option<String>, GitError> {
+        let found = not_found_as_none(self.repo()?.find_remote(remote))?;
+        Ok(found.map(|r| r.url().map(str::to_string)).transpose()?)
+    }
+
+    fn set_remote_url(&mut self, remote: &str, url: &str) -> Result<(), GitError> {
+        Ok(self.repo()?.remote_set_url(remote, url)?)
+    }
+
+    fn repo_root(&self) -> Result<PathBuf, GitError> {
+        self.repo()?
+            .workdir()
+            .map(|dir| dir.components().collect())
+            .ok_or_else(|| GitError("fatal: this operation must be run in a work tree".to_string()))
+    }
+
+    fn git_dir(&self) -> Result<PathBuf, GitError> {
+        Ok(self.repo()?.path().components().collect())
+    }
+
+    fn open(&self, dir: &Path) -> Result<Self, GitError> {
+        Ok(Self {
+            repo: Some(Repository::discover(dir)?),
+        })
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::backend::{ConfigScope, GitBackend};
+use super::config_file::{normalize_key, ConfigValue};
+use super::GitError;
+
+/// A repository that lives in memory, for tests.
+#[derive(Debug, Clone)]
+pub struct MemoryBackend {
+    pub root: PathBuf,
+    pub config: BTreeMap<(ConfigScope, String), String>,
+    pub remotes: BTreeMap<String, String>,
+}
+
+impl MemoryBackend {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            config: BTreeMap::new(),
+            remotes: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_config(mut self, scope: ConfigScope, key: &str, value: &str) -> Self {
+        self.config
+            .insert((scope, normalize_key(key)), value.to_string());
+        self
+    }
+
+    pub fn with_remote(mut self, name: &str, url: &str) -> Self {
+        self.remotes.insert(name.to_string(), url.to_string());
+        self
+    }
+}
+
+impl GitBackend for MemoryBackend {
+    fn config_get(&self, key: &str) -> Result<Option<String>, GitError> {
+        let key = normalize_key(key);
+        // Scopes sort by precedence, so the last match wins.
+        Ok(self
+            .config
+            .iter()
+            .rev()
+            .find(|((_, k), _)| *k == key)
+            .map(|(_, value)| value.clone()))
+    }
+
+    fn config_origins(&self, key: &str) -> Result<Vec<ConfigValue>, GitError> {
+        let key = normalize_key(key);
+        Ok(self
+            .config
+            .iter()
+            .filter(|((_, k), _)| *k == key)
+            .map(|((scope, k), value)| ConfigValue {
+                key: k.clone(),
+                value: Some(value.clone()),
+                scope: *scope,
+                origin: self.root.join(format!("{}.gitconfig", scope)),
+            })
+            .collect())
+    }
+
+    fn config_get_in(&self, scope: ConfigScope, key: &str) -> Result<Option<String>, GitError> {
+        Ok(self.config.get(&(scope, normalize_key(key))).cloned())
+    }
+
+    fn config_set(&mut self, scope: ConfigScope, key: &str, value: &str) -> Result<(), GitError> {
+        self.config
+            .insert((scope, normalize_key(key)), value.to_string());
+        Ok(())
+    }
+
+    fn config_unset(&mut self, scope: ConfigScope, key: &str) -> Result<(), GitError> {
+        self.config.remove(&(scope, normalize_key(key)));
+        Ok(())
+    }
+
+    fn remotes(&self) -> Result<Vec<String>, GitError> {
+        Ok(self.remotes.keys().cloned().collect())
+    }
+
+    fn remote_url(&self, remote: &str) -> Result<Option<String>, GitError> {
+        Ok(self.remotes.get(remote).cloned())
+    }
+
+    fn set_remote_url(&mut self, remote: &str, url: &str) -> Result<(), GitError> {
+        match self.remotes.get_mut(remote) {
+            Some(existing) => {
+                *existing = url.to_string();
+                Ok(())
+            }
+            None => Err(GitError(format!("error: No such remote '{}'", remote))),
+        }
+    }
+
+    fn repo_root(&self) -> Result<PathBuf, GitError> {
+        Ok(self.root.clone())
+    }
+
+    fn git_dir(&self) -> Result<PathBuf, GitError> {
+        Ok(self.root.join(".git"))
+    }
+
+    fn open(&self, dir: &Path) -> Result<Self, GitError> {
+        Err(GitError(format!(
+            "fatal: not a git repository: {}",
+            dir.display()
+        )))
+    }
+}
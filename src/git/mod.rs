@@ -1,5 +1,20 @@
+pub mod backend;
+pub mod config_file;
+pub mod include;
+#[cfg(feature = "git2")]
+pub mod libgit2;
+#[cfg(test)]
+pub mod memory;
+pub mod native;
+pub mod remote_url;
+
 use log::info;
-use std::process::Command;
+use std::path::{Path, PathBuf};
+
+use backend::{ConfigScope, GitBackend, ProcessBackend};
+use config_file::{ConfigFile, ConfigValue};
+use native::NativeBackend;
+use remote_url::RemoteUrl;
 
 #[derive(Debug)]
 pub struct GitError(pub String);
@@ -12,7 +27,46 @@ impl std::fmt::Display for GitError {
 
 impl std::error::Error for GitError {}
 
-pub struct Git {
+/// A Git release number as printed by `git --version`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct GitVersion(pub u32, pub u32, pub u32);
+
+impl GitVersion {
+    /// Reads `git version 2.39.2`, ignoring vendor suffixes such as
+    /// `(Apple Git-143)` or `.windows.1`.
+    pub fn parse(output: &str) -> Option<Self> {
+        let number = output.trim().strip_prefix("git version ")?;
+        let mut parts = number
+            .split(|c: char| !c.is_ascii_digit())
+            .map(|p| p.parse::<u32>().ok());
+        let major = parts.next()??;
+        let minor = parts.next()??;
+        let patch = parts.next().flatten().unwrap_or(0);
+        Some(GitVersion(major, minor, patch))
+    }
+}
+
+impl std::fmt::Display for GitVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.0, self.1, self.2)
+    }
+}
+
+/// Which [`GitBackend`] `switch` and `show` run on.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BackendKind {
+    /// Read and write config files directly, without starting `git`.
+    #[default]
+    Native,
+    /// Run the `git` binary.
+    Process,
+    /// Use libgit2 in-process.
+    #[cfg(feature = "git2")]
+    Libgit2,
+}
+
+pub struct Git<B: GitBackend = NativeBackend> {
+    backend: B,
     config: GitConfig,
 }
 
@@ -21,115 +75,270 @@ pub struct GitConfig {
     pub user_name: String,
     pub user_email: String,
     pub remote_url: String,
+    pub ssh_command: String,
 }
 
 impl Git {
     pub fn new() -> Self {
+        Self::with_backend(NativeBackend::discover(Path::new(".")))
+    }
+}
+
+impl<B: GitBackend> Git<B> {
+    pub fn with_backend(backend: B) -> Self {
         Self {
+            backend,
             config: GitConfig {
                 user_name: String::new(),
                 user_email: String::new(),
                 remote_url: String::new(),
+                ssh_command: String::new(),
             },
         }
     }
 
-    pub fn set_config(&mut self, name: &str, email: &str) -> Result<(), GitError> {
-        info!("Setting Git username: {}", name);
-        self.execute_command(&["config", "user.name", name])?;
+    /// Sets the identity in the config file of `scope`.
+    pub fn set_config(
+        &mut self,
+        scope: ConfigScope,
+        name: &str,
+        email: &str,
+    ) -> Result<(), GitError> {
+        info!("Setting Git username ({}): {}", scope, name);
+        self.backend.config_set(scope, "user.name", name)?;
 
-        info!("Setting Git email: {}", email);
-        self.execute_command(&["config", "user.email", email])?;
+        info!("Setting Git email ({}): {}", scope, email);
+        self.backend.config_set(scope, "user.email", email)?;
 
         self.config.user_name = name.to_string();
         self.config.user_email = email.to_string();
         Ok(())
     }
 
-    pub fn set_remote(&mut self, host: &str, user: &str, repo: &str) -> Result<(), GitError> {
-        let remote_url = format!("git@{}:{}/{}.git", host, user, repo);
-        info!("Setting Git remote URL: {}", remote_url);
-        self.execute_command(&["remote", "set-url", "origin", &remote_url])?;
+    /// Points `remote` at `url`, returning the URL as written.
+    pub fn set_remote(&mut self, remote: &str, url: &RemoteUrl) -> Result<String, GitError> {
+        let remote_url = url.to_string();
+        info!("Setting Git remote URL of '{}': {}", remote, remote_url);
+        self.backend.set_remote_url(remote, &remote_url)?;
 
-        self.config.remote_url = remote_url;
+        self.config.remote_url = remote_url.clone();
+        Ok(remote_url)
+    }
+
+    /// Points `core.sshCommand` at `key` so ssh offers only that identity,
+    /// whatever the remote URL and ssh-agent say.
+    pub fn set_ssh_command(&mut self, scope: ConfigScope, key: &Path) -> Result<(), GitError> {
+        let ssh_command = ssh_command(key);
+        info!("Setting Git SSH command ({}): {}", scope, ssh_command);
+        self.backend
+            .config_set(scope, "core.sshCommand", &ssh_command)?;
+
+        self.config.ssh_command = ssh_command;
+        Ok(())
+    }
+
+    /// Removes a `core.sshCommand` previously set by [`Git::set_ssh_command`].
+    /// Commands configured by hand are left alone.
+    pub fn clear_ssh_command(&mut self, scope: ConfigScope) -> Result<(), GitError> {
+        let current = self
+            .backend
+            .config_get_in(scope, "core.sshCommand")?
+            .unwrap_or_default();
+        if is_managed_ssh_command(&current) {
+            info!("Removing Git SSH command ({}): {}", scope, current);
+            self.backend.config_unset(scope, "core.sshCommand")?;
+        }
+
+        self.config.ssh_command = String::new();
         Ok(())
     }
 
+    /// The effective configuration. Settings that are not set are empty.
     pub fn get_current_config(&self) -> Result<GitConfig, GitError> {
-        let user_name = self.execute_command(&["config", "user.name"])?;
-        let user_email = self.execute_command(&["config", "user.email"])?;
+        let user_name = self.backend.config_get("user.name")?.unwrap_or_default();
+        let user_email = self.backend.config_get("user.email")?.unwrap_or_default();
 
-        // Try to get remote URL, but return empty string if origin doesn't exist
-        let remote_url = match self.execute_command(&["remote", "get-url", "origin"]) {
-            Ok(url) => url,
-            Err(_) => String::new(),
-        };
+        // An empty remote URL means there is no origin.
+        let remote_url = self
+            .backend
+            .remote_url("origin")
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        let ssh_command = self
+            .backend
+            .config_get("core.sshCommand")?
+            .unwrap_or_default();
 
         Ok(GitConfig {
             user_name,
             user_email,
             remote_url,
+            ssh_command,
         })
     }
 
-    pub fn parse_origin_url(&self) -> Result<(String, String), GitError> {
-        // Try to get remote URL, but return error if origin doesn't exist
-        let url = match self.execute_command(&["remote", "get-url", "origin"]) {
-            Ok(url) => url,
-            Err(e) => {
-                return Err(GitError(format!(
-                    "No remote 'origin' found. Please add a remote repository first: {}",
-                    e
-                )))
-            }
-        };
+    /// Every value of `key` with the scope and file setting it, lowest
+    /// precedence first.
+    pub fn config_origins(&self, key: &str) -> Result<Vec<ConfigValue>, GitError> {
+        self.backend.config_origins(key)
+    }
 
-        if url.is_empty() {
-            return Err(GitError("Remote 'origin' URL is empty".to_string()));
-        }
+    /// Top-level directory of the work tree the current directory is in.
+    pub fn repo_root(&self) -> Result<PathBuf, GitError> {
+        self.backend.repo_root()
+    }
 
-        if url.starts_with("git@") {
-            let parts: Vec<&str> = url.split(':').collect();
-            if parts.len() == 2 {
-                let path = parts[1].trim_end_matches(".git");
-                let mut segments = path.split('/');
-                let user = segments
-                    .next()
-                    .ok_or_else(|| GitError("Invalid remote repository URL format".to_string()))?
-                    .to_string();
-                let repo = segments
-                    .next()
-                    .ok_or_else(|| GitError("Invalid remote repository URL format".to_string()))?
-                    .to_string();
-                return Ok((user, repo));
+    /// The repository's Git directory, which is not `.git` in worktrees.
+    pub fn git_dir(&self) -> Result<PathBuf, GitError> {
+        self.backend.git_dir()
+    }
+
+    /// Names of all remotes, in the order they are configured.
+    pub fn remotes(&self) -> Result<Vec<String>, GitError> {
+        self.backend.remotes()
+    }
+
+    /// URL of `remote`, or `None` when there is no such remote.
+    pub fn remote_url(&self, remote: &str) -> Result<Option<String>, GitError> {
+        self.backend.remote_url(remote)
+    }
+
+    /// Submodules listed in the work tree's `.gitmodules`, in file order.
+    /// Entries without a `path` are left out, as Git ignores them.
+    pub fn submodules(&self) -> Result<Vec<Submodule>, GitError> {
+        let file = ConfigFile::load(&self.repo_root()?.join(".gitmodules"))?;
+        let mut found: Vec<(String, Option<String>)> = Vec::new();
+        for entry in file.entries.iter().filter(|e| e.section == "submodule") {
+            let (Some(name), "path") = (&entry.subsection, entry.name.as_str()) else {
+                continue;
+            };
+            let path = entry.value.clone();
+            match found.iter_mut().find(|(n, _)| n == name) {
+                Some(existing) => existing.1 = path,
+                None => found.push((name.clone(), path)),
             }
         }
+        Ok(found
+            .into_iter()
+            .filter_map(|(name, path)| {
+                Some(Submodule {
+                    name,
+                    path: PathBuf::from(path.filter(|p| !p.is_empty())?),
+                })
+            })
+            .collect())
+    }
 
-        if url.starts_with("https://") {
-            let parts: Vec<&str> = url.split('/').collect();
-            if parts.len() >= 2 {
-                let user = parts[parts.len() - 2].to_string();
-                let repo = parts[parts.len() - 1].trim_end_matches(".git").to_string();
-                return Ok((user, repo));
-            }
+    /// The checkout of `submodule`, or `None` when it is not checked out.
+    pub fn open_submodule(&self, submodule: &Submodule) -> Result<Option<Git<B>>, GitError> {
+        let dir = self.repo_root()?.join(&submodule.path);
+        if !dir.join(".git").exists() {
+            return Ok(None);
         }
+        Ok(Some(Git::with_backend(self.backend.open(&dir)?)))
+    }
 
-        Err(GitError(
-            "Unsupported remote repository URL format".to_string(),
-        ))
+    /// The URL `git submodule init` copied to the repository's config, which
+    /// Git clones the submodule from.
+    pub fn submodule_url(&self, name: &str) -> Result<Option<String>, GitError> {
+        self.backend
+            .config_get_in(ConfigScope::Local, &submodule_url_key(name))
     }
 
-    fn execute_command(&self, args: &[&str]) -> Result<String, GitError> {
-        let output = Command::new("git")
-            .args(args)
-            .output()
-            .map_err(|e| GitError(format!("Failed to execute Git command: {}", e)))?;
+    /// Points the submodule's configured URL at `url`, returning it as written.
+    pub fn set_submodule_url(&mut self, name: &str, url: &RemoteUrl) -> Result<String, GitError> {
+        let url = url.to_string();
+        info!("Setting URL of submodule '{}': {}", name, url);
+        self.backend
+            .config_set(ConfigScope::Local, &submodule_url_key(name), &url)?;
+        Ok(url)
+    }
+}
 
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            return Err(GitError(error.to_string()));
-        }
+/// A submodule declared in `.gitmodules`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Submodule {
+    pub name: String,
+    /// Relative to the superproject's work tree.
+    pub path: PathBuf,
+}
+
+fn submodule_url_key(name: &str) -> String {
+    format!("submodule.{}.url", name)
+}
+
+/// Hosts of the big Git providers, which `switch --known-hosts-only` always
+/// treats as known.
+pub const PROVIDER_HOSTS: &[&str] = &["github.com", "gitlab.com", "bitbucket.org", "codeberg.org"];
+
+/// What `switch` did to one remote.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteChange {
+    pub remote: String,
+    pub old_url: String,
+    pub outcome: RemoteOutcome,
+}
 
-        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+#[derive(Debug, Clone, PartialEq)]
+pub enum RemoteOutcome {
+    Updated(String),
+    Unchanged,
+    /// Left alone, for the given reason.
+    Skipped(String),
+}
+
+/// Environment variables that override the configured identity in commits,
+/// with the setting each one replaces.
+pub const IDENTITY_ENV_VARS: &[(&str, &str)] = &[
+    ("GIT_AUTHOR_NAME", "user.name"),
+    ("GIT_AUTHOR_EMAIL", "user.email"),
+    ("GIT_COMMITTER_NAME", "user.name"),
+    ("GIT_COMMITTER_EMAIL", "user.email"),
+];
+
+/// The [`IDENTITY_ENV_VARS`] that are set: each name, the setting it
+/// replaces, and its value.
+pub fn identity_env_overrides() -> Vec<(&'static str, &'static str, String)> {
+    IDENTITY_ENV_VARS
+        .iter()
+        .filter_map(|&(name, key)| std::env::var(name).ok().map(|value| (name, key, value)))
+        .collect()
+}
+
+/// The installed Git release, which only the `git` binary can tell.
+pub fn version() -> Result<GitVersion, GitError> {
+    let output = ProcessBackend::default().execute_command(&["--version"])?;
+    GitVersion::parse(&output)
+        .ok_or_else(|| GitError(format!("Cannot read the Git version from '{}'", output)))
+}
+
+/// The `core.sshCommand` value guse uses to pin `key`.
+pub fn ssh_command(key: &Path) -> String {
+    format!(
+        "ssh -i {} -o IdentitiesOnly=yes",
+        shell_quote(&key.to_string_lossy().replace('\\', "/"))
+    )
+}
+
+/// `~/.gitconfig`, the global file `git config --global` writes to.
+pub fn global_config_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".gitconfig"))
+}
+
+fn is_managed_ssh_command(command: &str) -> bool {
+    command.starts_with("ssh -i ") && command.ends_with(" -o IdentitiesOnly=yes")
+}
+
+/// Quotes `value` for the shell Git runs `core.sshCommand` through.
+fn shell_quote(value: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "/._-:+@%".contains(c);
+    if !value.is_empty() && value.chars().all(safe) {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', "'\\''"))
     }
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,326 @@
+use std::cell::OnceCell;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::backend::{ConfigScope, GitBackend};
+use super::config_file::{parse_bool, ConfigFile, ConfigSet, ConfigValue, IncludeContext};
+use super::{global_config_path, GitError};
+
+/// Where the system-wide config lives for Git built with the usual prefix.
+/// Builds with another prefix, such as Homebrew's, read their own file; set
+/// `GIT_CONFIG_SYSTEM` or use the process backend for those.
+const SYSTEM_CONFIG: &str = "/etc/gitconfig";
+
+#[derive(Debug, Clone)]
+struct Repository {
+    work_tree: PathBuf,
+    /// `.git`, or `.git/worktrees/<name>` in a linked worktree.
+    git_dir: PathBuf,
+    /// Where the shared `config` lives; the main `.git` in a linked worktree.
+    common_dir: PathBuf,
+}
+
+/// Reads and writes config files directly, so that no `git` process starts.
+#[derive(Debug)]
+pub struct NativeBackend {
+    /// `None` outside a repository, where only system and global config exist.
+    repo: Option<Repository>,
+    /// Every file Git would read, loaded on first use and dropped on writes.
+    config: OnceCell<ConfigSet>,
+    /// System and global files to use instead of those the environment names.
+    paths: Option<UserPaths>,
+}
+
+#[derive(Debug, Clone)]
+struct UserPaths {
+    system: PathBuf,
+    global: PathBuf,
+}
+
+impl NativeBackend {
+    /// Finds the repository containing `dir` the way `git` does, honouring
+    /// `GIT_DIR` and `GIT_WORK_TREE`.
+    pub fn discover(dir: &Path) -> Self {
+        Self {
+            repo: discover_repository(dir),
+            config: OnceCell::new(),
+            paths: None,
+        }
+    }
+
+    /// Like [`NativeBackend::discover`], but reads `system` and `global` in
+    /// place of the files on this machine.
+    #[cfg(test)]
+    pub fn with_paths(dir: &Path, system: &Path, global: &Path) -> Self {
+        Self {
+            paths: Some(UserPaths {
+                system: system.to_path_buf(),
+                global: global.to_path_buf(),
+            }),
+            ..Self::discover(dir)
+        }
+    }
+
+    fn repo(&self) -> Result<&Repository, GitError> {
+        self.repo
+            .as_ref()
+            .ok_or_else(|| GitError("fatal: not a git repository".to_string()))
+    }
+
+    fn config(&self) -> Result<&ConfigSet, GitError> {
+        if let Some(config) = self.config.get() {
+            return Ok(config);
+        }
+        let context = IncludeContext {
+            git_dir: self.repo.as_ref().map(|r| r.git_dir.clone()),
+            branch: self.repo.as_ref().and_then(|r| current_branch(&r.git_dir)),
+        };
+        let config = ConfigSet::load(&self.files(), &context)?;
+        Ok(self.config.get_or_init(|| config))
+    }
+
+    /// The files `git config` reads, lowest precedence first.
+    fn files(&self) -> Vec<(ConfigScope, PathBuf)> {
+        let mut files = Vec::new();
+        if let Some(paths) = &self.paths {
+            files.push((ConfigScope::System, paths.system.clone()));
+            files.push((ConfigScope::Global, paths.global.clone()));
+        } else {
+            if !env_bool("GIT_CONFIG_NOSYSTEM") {
+                files.push((ConfigScope::System, system_config_path()));
+            }
+            match env::var_os("GIT_CONFIG_GLOBAL") {
+                Some(path) => files.push((ConfigScope::Global, PathBuf::from(path))),
+                None => files.extend(
+                    [xdg_config_path(), global_config_path()]
+                        .into_iter()
+                        .flatten()
+                        .map(|path| (ConfigScope::Global, path)),
+                ),
+            }
+        }
+        if let Some(repo) = &self.repo {
+            files.push((ConfigScope::Local, repo.common_dir.join("config")));
+            if worktree_config_enabled(repo) {
+                files.push((ConfigScope::Worktree, repo.git_dir.join("config.worktree")));
+            }
+        }
+        files
+    }
+
+    /// The one file `git config --<scope>` reads and writes.
+    fn scope_path(&self, scope: ConfigScope) -> Result<PathBuf, GitError> {
+        if let Some(paths) = &self.paths {
+            match scope {
+                ConfigScope::System => return Ok(paths.system.clone()),
+                ConfigScope::Global => return Ok(paths.global.clone()),
+                _ => {}
+            }
+        }
+        Ok(match scope {
+            ConfigScope::System => system_config_path(),
+            ConfigScope::Global => match env::var_os("GIT_CONFIG_GLOBAL") {
+                Some(path) => PathBuf::from(path),
+                None => {
+                    let home = global_config_path()
+                        .ok_or_else(|| GitError("Cannot find the home directory".to_string()))?;
+                    // Git writes to the XDG file only when it is the one in use.
+                    match xdg_config_path() {
+                        Some(xdg) if !home.exists() && xdg.exists() => xdg,
+                        _ => home,
+                    }
+                }
+            },
+            ConfigScope::Local => self.repo()?.common_dir.join("config"),
+            ConfigScope::Worktree => {
+                let repo = self.repo()?;
+                if worktree_config_enabled(repo) {
+                    repo.git_dir.join("config.worktree")
+                } else if repo.git_dir == repo.common_dir {
+                    repo.common_dir.join("config")
+                } else {
+                    return Err(GitError(
+                        "fatal: --worktree cannot be used with multiple working trees unless \
+                         the config extension worktreeConfig is enabled"
+                            .to_string(),
+                    ));
+                }
+            }
+        })
+    }
+
+    fn edit(
+        &mut self,
+        scope: ConfigScope,
+        change: impl FnOnce(&mut ConfigFile) -> Result<bool, GitError>,
+    ) -> Result<(), GitError> {
+        let mut file = ConfigFile::load(&self.scope_path(scope)?)?;
+        if change(&mut file)? {
+            file.save()?;
+            self.config = OnceCell::new();
+        }
+        Ok(())
+    }
+}
+
+impl GitBackend for NativeBackend {
+    fn config_get(&self, key: &str) -> Result<Option<String>, GitError> {
+        Ok(self
+            .config()?
+            .get(key)
+            .map(|v| v.value.clone().unwrap_or_default()))
+    }
+
+    fn config_origins(&self, key: &str) -> Result<Vec<ConfigValue>, GitError> {
+        Ok(self.config()?.get_all(key).into_iter().cloned().collect())
+    }
+
+    fn config_get_in(&self, scope: ConfigScope, key: &str) -> Result<Option<String>, GitError> {
+        // Like `git config --<scope>`, this reads one file without its includes.
+        let file = ConfigFile::load(&self.scope_path(scope)?)?;
+        Ok(file.get(key).map(|e| e.value.clone().unwrap_or_default()))
+    }
+
+    fn config_set(&mut self, scope: ConfigScope, key: &str, value: &str) -> Result<(), GitError> {
+        self.edit(scope, |file| file.set(key, value).map(|_| true))
+    }
+
+    fn config_unset(&mut self, scope: ConfigScope, key: &str) -> Result<(), GitError> {
+        self.edit(scope, |file| {
+            if file.get(key).is_none() {
+                return Ok(false);
+            }
+            file.unset(key).map(|_| true)
+        })
+    }
+
+    fn remotes(&self) -> Result<Vec<String>, GitError> {
+        Ok(self.config()?.subsections("remote"))
+    }
+
+    fn remote_url(&self, remote: &str) -> Result<Option<String>, GitError> {
+        let config = self.config()?;
+        let urls = config.get_all(&format!("remote.{}.url", remote));
+        // `git remote get-url` prints the first URL, rewritten by `insteadOf`.
+        Ok(urls
+            .first()
+            .map(|v| config.rewrite_url(v.value.as_deref().unwrap_or_default())))
+    }
+
+    fn set_remote_url(&mut self, remote: &str, url: &str) -> Result<(), GitError> {
+        if !self
+            .config()?
+            .subsections("remote")
+            .iter()
+            .any(|r| r == remote)
+        {
+            return Err(GitError(format!("error: No such remote '{}'", remote)));
+        }
+        self.config_set(ConfigScope::Local, &format!("remote.{}.url", remote), url)
+    }
+
+    fn repo_root(&self) -> Result<PathBuf, GitError> {
+        Ok(self.repo()?.work_tree.clone())
+    }
+
+    fn git_dir(&self) -> Result<PathBuf, GitError> {
+        Ok(self.repo()?.git_dir.clone())
+    }
+
+    fn open(&self, dir: &Path) -> Result<Self, GitError> {
+        let backend = Self {
+            paths: self.paths.clone(),
+            ..Self::discover(dir)
+        };
+        backend.repo()?;
+        Ok(backend)
+    }
+}
+
+fn discover_repository(dir: &Path) -> Option<Repository> {
+    let dir = fs::canonicalize(dir).ok()?;
+    if let Some(git_dir) = env::var_os("GIT_DIR") {
+        let git_dir = fs::canonicalize(dir.join(git_dir)).ok()?;
+        // Without GIT_WORK_TREE, Git takes the current directory as the top.
+        let work_tree = match env::var_os("GIT_WORK_TREE") {
+            Some(work_tree) => fs::canonicalize(dir.join(work_tree)).ok()?,
+            None => dir,
+        };
+        return Some(Repository::new(work_tree, git_dir));
+    }
+
+    dir.ancestors().find_map(|candidate| {
+        let dot_git = candidate.join(".git");
+        let git_dir = if dot_git.join("HEAD").is_file() {
+            dot_git
+        } else if dot_git.is_file() {
+            read_git_file(&dot_git)?
+        } else {
+            return None;
+        };
+        Some(Repository::new(
+            candidate.to_path_buf(),
+            fs::canonicalize(git_dir).ok()?,
+        ))
+    })
+}
+
+impl Repository {
+    fn new(work_tree: PathBuf, git_dir: PathBuf) -> Self {
+        let common_dir = fs::read_to_string(git_dir.join("commondir"))
+            .ok()
+            .and_then(|dir| fs::canonicalize(git_dir.join(dir.trim())).ok())
+            .unwrap_or_else(|| git_dir.clone());
+        Self {
+            work_tree,
+            git_dir,
+            common_dir,
+        }
+    }
+}
+
+/// The directory a `.git` file of a worktree or submodule points to.
+fn read_git_file(path: &Path) -> Option<PathBuf> {
+    let content = fs::read_to_string(path).ok()?;
+    let target = content.trim().strip_prefix("gitdir:")?.trim();
+    Some(path.parent()?.join(target))
+}
+
+fn current_branch(git_dir: &Path) -> Option<String> {
+    let head = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    head.trim()
+        .strip_prefix("ref: refs/heads/")
+        .map(str::to_string)
+}
+
+fn worktree_config_enabled(repo: &Repository) -> bool {
+    ConfigFile::load(&repo.common_dir.join("config"))
+        .ok()
+        .and_then(|file| {
+            let entry = file.get("extensions.worktreeConfig")?;
+            parse_bool(entry.value.as_deref())
+        })
+        .unwrap_or(false)
+}
+
+fn system_config_path() -> PathBuf {
+    env::var_os("GIT_CONFIG_SYSTEM")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(SYSTEM_CONFIG))
+}
+
+/// `$XDG_CONFIG_HOME/git/config`, which Git reads before `~/.gitconfig`.
+fn xdg_config_path() -> Option<PathBuf> {
+    match env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => Some(PathBuf::from(dir).join("git").join("config")),
+        None => dirs::home_dir().map(|home| home.join(".config").join("git").join("config")),
+    }
+}
+
+fn env_bool(name: &str) -> bool {
+    env::var(name)
+        .ok()
+        .and_then(|value| parse_bool(Some(&value)))
+        .unwrap_or(false)
+}
@@ -0,0 +1,272 @@
+//! Remote URLs in every form Git accepts, split into parts that rebuild the
+//! exact original text.
+
+use std::fmt;
+use std::str::FromStr;
+
+use super::GitError;
+
+/// A parsed remote URL. Git understands three forms:
+///
+/// - `scheme://[user[:password]@]host[:port]/path`, e.g. `ssh://`, `https://`
+///   or `file://`
+/// - the scp-like `[user@]host:path`, which implies SSH
+/// - a local path, which has neither a scheme nor a host
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteUrl {
+    /// As written, e.g. `ssh`, `git+ssh` or `HTTPS`; `None` in the scp-like
+    /// form and for local paths.
+    pub scheme: Option<String>,
+    pub user: Option<String>,
+    /// Only the `scheme://` form can carry one.
+    pub password: Option<String>,
+    /// IPv6 addresses keep their brackets. Empty for local paths and usually
+    /// for `file://`.
+    pub host: String,
+    pub port: Option<String>,
+    /// Everything after the host: starts with `/` in the `scheme://` form and
+    /// is relative to the login directory in the scp-like form.
+    pub path: String,
+}
+
+impl RemoteUrl {
+    pub fn parse(url: &str) -> Result<Self, GitError> {
+        if let Some((scheme, rest)) = url.split_once("://") {
+            if is_scheme(scheme) {
+                return parse_url_form(scheme, rest, url);
+            }
+        }
+
+        // Git reads `host:path` as scp-like unless a slash comes before the
+        // colon, which makes it a local path such as `./a:b`.
+        let Some(colon) = first_unbracketed_colon(url) else {
+            return Ok(Self::local(url));
+        };
+        let authority = &url[..colon];
+        if authority.contains('/') {
+            return Ok(Self::local(url));
+        }
+        let (user, host) = match authority.rsplit_once('@') {
+            Some((user, host)) => (Some(user.to_string()), host),
+            None => (None, authority),
+        };
+        if host.is_empty() {
+            return Ok(Self::local(url));
+        }
+        Ok(Self {
+            scheme: None,
+            user,
+            password: None,
+            host: host.to_string(),
+            port: None,
+            path: url[colon + 1..].to_string(),
+        })
+    }
+
+    fn local(path: &str) -> Self {
+        Self {
+            scheme: None,
+            user: None,
+            password: None,
+            host: String::new(),
+            port: None,
+            path: path.to_string(),
+        }
+    }
+
+    /// A path on this machine, which has no host to rewrite.
+    pub fn is_local(&self) -> bool {
+        self.scheme.is_none() && self.host.is_empty()
+    }
+
+    /// Whether Git reaches the remote over SSH.
+    pub fn is_ssh(&self) -> bool {
+        match &self.scheme {
+            None => !self.host.is_empty(),
+            Some(scheme) => matches!(
+                scheme.to_ascii_lowercase().as_str(),
+                "ssh" | "git+ssh" | "ssh+git"
+            ),
+        }
+    }
+
+    /// The host without the brackets of an IPv6 address.
+    pub fn hostname(&self) -> &str {
+        self.host
+            .strip_prefix('[')
+            .and_then(|h| h.strip_suffix(']'))
+            .unwrap_or(&self.host)
+    }
+
+    /// Whether Git reaches the remote over HTTP or HTTPS.
+    pub fn is_http(&self) -> bool {
+        self.scheme
+            .as_deref()
+            .is_some_and(|s| s.eq_ignore_ascii_case("http") || s.eq_ignore_ascii_case("https"))
+    }
+
+    /// The same repository through the SSH host alias `alias`, keeping the
+    /// path and the login user. The port is dropped, since the alias's own
+    /// `Port` applies. Local paths cannot be reached through a host.
+    pub fn to_ssh(&self, alias: &str) -> Option<Self> {
+        if self.is_local() {
+            return None;
+        }
+        if self.is_ssh() {
+            return Some(Self {
+                host: alias.to_string(),
+                port: None,
+                ..self.clone()
+            });
+        }
+        // Other transports become scp-like, which GitHub, GitLab and
+        // Bitbucket all serve to the `git` user.
+        Some(Self {
+            scheme: None,
+            user: Some("git".to_string()),
+            password: None,
+            host: alias.to_string(),
+            port: None,
+            path: self.path.trim_start_matches('/').to_string(),
+        })
+    }
+
+    /// The same repository over HTTPS on `host`, logging in as `user`. An
+    /// HTTP(S) URL keeps its scheme, and its port while the host stays the
+    /// same; other forms become `https://[user@]host/path`, as an SSH port
+    /// means nothing to HTTPS. A password is kept only for the same user.
+    pub fn to_https(&self, host: &str, user: Option<&str>) -> Option<Self> {
+        if self.is_local() {
+            return None;
+        }
+        if self.is_http() {
+            let same_user = self.user.as_deref() == user;
+            let same_host = self.host.eq_ignore_ascii_case(host);
+            return Some(Self {
+                user: user.map(str::to_string),
+                password: self.password.clone().filter(|_| same_user),
+                host: host.to_string(),
+                port: self.port.clone().filter(|_| same_host),
+                ..self.clone()
+            });
+        }
+        Some(Self {
+            scheme: Some("https".to_string()),
+            user: user.map(str::to_string),
+            password: None,
+            host: host.to_string(),
+            port: None,
+            path: format!("/{}", self.path.trim_start_matches('/')),
+        })
+    }
+}
+
+impl fmt::Display for RemoteUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_local() {
+            return f.write_str(&self.path);
+        }
+        if let Some(scheme) = &self.scheme {
+            write!(f, "{}://", scheme)?;
+        }
+        if let Some(user) = &self.user {
+            f.write_str(user)?;
+            if let Some(password) = &self.password {
+                write!(f, ":{}", password)?;
+            }
+            f.write_str("@")?;
+        }
+        f.write_str(&self.host)?;
+        if let Some(port) = &self.port {
+            write!(f, ":{}", port)?;
+        }
+        if self.scheme.is_none() {
+            f.write_str(":")?;
+        }
+        f.write_str(&self.path)
+    }
+}
+
+impl FromStr for RemoteUrl {
+    type Err = GitError;
+
+    fn from_str(url: &str) -> Result<Self, Self::Err> {
+        Self::parse(url)
+    }
+}
+
+fn parse_url_form(scheme: &str, rest: &str, url: &str) -> Result<RemoteUrl, GitError> {
+    let invalid = |what: &str| GitError(format!("Invalid remote URL '{}': {}", url, what));
+    let (authority, path) = match rest.find('/') {
+        Some(slash) => rest.split_at(slash),
+        None => (rest, ""),
+    };
+    let (userinfo, host_port) = match authority.rsplit_once('@') {
+        Some((userinfo, host_port)) => (Some(userinfo), host_port),
+        None => (None, authority),
+    };
+    let (user, password) = match userinfo {
+        Some(userinfo) => match userinfo.split_once(':') {
+            Some((user, password)) => (Some(user.to_string()), Some(password.to_string())),
+            None => (Some(userinfo.to_string()), None),
+        },
+        None => (None, None),
+    };
+
+    let (host, port) = if host_port.starts_with('[') {
+        let end = host_port
+            .find(']')
+            .ok_or_else(|| invalid("unclosed '[' in the host"))?;
+        match &host_port[end + 1..] {
+            "" => (&host_port[..=end], None),
+            after => (
+                &host_port[..=end],
+                Some(after.strip_prefix(':').ok_or_else(|| invalid("bad port"))?),
+            ),
+        }
+    } else {
+        match host_port.split_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (host_port, None),
+        }
+    };
+    if let Some(port) = port {
+        if port.is_empty() || !port.chars().all(|c| c.is_ascii_digit()) {
+            return Err(invalid("bad port"));
+        }
+    }
+
+    Ok(RemoteUrl {
+        scheme: Some(scheme.to_string()),
+        user,
+        password,
+        host: host.to_string(),
+        port: port.map(str::to_string),
+        path: path.to_string(),
+    })
+}
+
+/// `ssh`, `https`, `git+ssh`: a letter, then letters, digits, `+`, `-`, `.`.
+fn is_scheme(scheme: &str) -> bool {
+    scheme
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+}
+
+/// The colon ending the host of an scp-like URL; the one in `[::1]` does not.
+fn first_unbracketed_colon(url: &str) -> Option<usize> {
+    let mut in_brackets = false;
+    for (i, c) in url.char_indices() {
+        match c {
+            '[' => in_brackets = true,
+            ']' => in_brackets = false,
+            ':' if !in_brackets => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
@@ -1,31 +1,77 @@
+mod backup;
 mod cli;
 mod config;
 mod error;
 mod git;
+mod ssh;
 mod ui;
 mod utils;
 
 use clap::Parser;
-use env_logger;
 
 use crate::cli::{Args, Commands};
 use crate::config::Config;
 use crate::error::GuseError;
+use crate::git::{BackendKind, Git};
+use crate::ssh::SshConfig;
+
+/// Runs `$body` with `$git` bound to a [`git::Git`] on the chosen backend.
+macro_rules! with_git {
+    ($kind:expr, |$git:ident| $body:expr) => {
+        match $kind {
+            BackendKind::Native => {
+                #[allow(unused_mut)]
+                let mut $git = Git::new();
+                $body
+            }
+            BackendKind::Process => {
+                #[allow(unused_mut)]
+                let mut $git = Git::with_backend(git::backend::ProcessBackend::default());
+                $body
+            }
+            #[cfg(feature = "git2")]
+            BackendKind::Libgit2 => {
+                #[allow(unused_mut)]
+                let mut $git =
+                    Git::with_backend(git::libgit2::Libgit2Backend::discover(".".as_ref()));
+                $body
+            }
+        }
+    };
+}
 
 fn main() -> Result<(), GuseError> {
     env_logger::init();
 
     let args = Args::parse();
-    let config = Config::new();
+    let config = Config::resolve(args.config.as_deref())?;
 
     match args.command {
         Commands::Add(cmd) => cmd.execute(&config),
-        Commands::Delete(cmd) => cmd.execute(&config),
+        Commands::Delete(cmd) => with_git!(args.git_backend, |git| cmd.execute(&config, &git)),
         Commands::List(cmd) => cmd.execute(&config),
         Commands::ListSsh(cmd) => cmd.execute(),
-        Commands::Show(cmd) => cmd.execute(),
-        Commands::Switch(cmd) => cmd.execute(&config),
-        Commands::Update(cmd) => cmd.execute(&config),
-        Commands::AddSsh(cmd) => cmd.execute(),
+        Commands::Show(cmd) => with_git!(args.git_backend, |git| cmd.execute(&config, &git)),
+        Commands::ShowProfile(cmd) => cmd.execute(&config),
+        Commands::Switch(cmd) => {
+            let ssh_config = SshConfig::load()?;
+            with_git!(args.git_backend, |git| cmd.execute(
+                &config,
+                &mut git,
+                &ssh_config
+            ))
+        }
+        Commands::Update(cmd) => with_git!(args.git_backend, |git| cmd.execute(&config, &git)),
+        Commands::AddSsh(cmd) => cmd.execute(&config),
+        Commands::Ssh(cmd) => cmd.execute(&config),
+        Commands::Export(cmd) => cmd.execute(&config),
+        Commands::Import(cmd) => cmd.execute(&config),
+        Commands::Dir(cmd) => cmd.execute(&config),
+        Commands::Url(cmd) => cmd.execute(&config),
+        Commands::Remote(cmd) => with_git!(args.git_backend, |git| cmd.execute(&config, &mut git)),
+        Commands::Pin(cmd) => with_git!(args.git_backend, |git| cmd.execute(&config, &git)),
+        Commands::Default(cmd) => cmd.execute(&config),
+        Commands::Order(cmd) => cmd.execute(&config),
+        Commands::Backup(cmd) => cmd.execute(&config),
     }
 }
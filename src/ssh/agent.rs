@@ -0,0 +1,189 @@
+use ssh_encoding::Encode;
+use ssh_key::{PrivateKey, PublicKey};
+use std::io::{Read, Write};
+
+use super::SshError;
+
+const SSH_AGENT_FAILURE: u8 = 5;
+const SSH_AGENT_SUCCESS: u8 = 6;
+const SSH_AGENTC_REQUEST_IDENTITIES: u8 = 11;
+const SSH_AGENT_IDENTITIES_ANSWER: u8 = 12;
+const SSH_AGENTC_ADD_IDENTITY: u8 = 17;
+const SSH_AGENTC_REMOVE_IDENTITY: u8 = 18;
+
+/// Upper bound on a reply, as in OpenSSH's `AGENT_MAX_LEN`.
+const MAX_MESSAGE_LEN: usize = 256 * 1024;
+
+#[cfg(unix)]
+pub type AgentStream = std::os::unix::net::UnixStream;
+
+/// On Windows the OpenSSH agent listens on a named pipe, which opens like a file.
+#[cfg(not(unix))]
+pub type AgentStream = std::fs::File;
+
+/// A key held by the agent.
+#[derive(Debug, Clone)]
+pub struct AgentIdentity {
+    pub public_key: PublicKey,
+    pub comment: String,
+}
+
+/// Minimal ssh-agent protocol client (draft-miller-ssh-agent).
+pub struct Agent<S> {
+    stream: S,
+}
+
+impl Agent<AgentStream> {
+    /// Connects to the agent named by `SSH_AUTH_SOCK`.
+    pub fn connect() -> Result<Self, SshError> {
+        Self::connect_to(std::env::var_os("SSH_AUTH_SOCK").as_deref())
+    }
+
+    /// Connects to the agent listening on `socket`.
+    pub fn connect_to(socket: Option<&std::ffi::OsStr>) -> Result<Self, SshError> {
+        #[cfg(unix)]
+        let stream = {
+            let socket = socket.ok_or_else(|| {
+                SshError("SSH_AUTH_SOCK is not set; is ssh-agent running?".to_string())
+            })?;
+            AgentStream::connect(socket)
+        };
+        #[cfg(not(unix))]
+        let stream = {
+            let socket = socket.unwrap_or(r"\\.\pipe\openssh-ssh-agent".as_ref());
+            std::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(socket)
+        };
+
+        let stream = stream.map_err(|e| SshError(format!("Cannot connect to ssh-agent: {}", e)))?;
+        Ok(Agent::new(stream))
+    }
+}
+
+impl<S: Read + Write> Agent<S> {
+    pub fn new(stream: S) -> Self {
+        Agent { stream }
+    }
+
+    pub fn identities(&mut self) -> Result<Vec<AgentIdentity>, SshError> {
+        let reply = self.request(&[SSH_AGENTC_REQUEST_IDENTITIES])?;
+        let mut reader = MessageReader::new(&reply);
+        if reader.byte()? != SSH_AGENT_IDENTITIES_ANSWER {
+            return Err(SshError("Unexpected reply from ssh-agent".to_string()));
+        }
+
+        let count = reader.u32()?;
+        let mut identities = Vec::new();
+        for _ in 0..count {
+            let blob = reader.string()?;
+            let comment = String::from_utf8_lossy(reader.string()?).to_string();
+            // Keys of types we cannot decode are skipped rather than failing the listing.
+            if let Ok(public_key) = PublicKey::from_bytes(blob) {
+                identities.push(AgentIdentity {
+                    public_key,
+                    comment,
+                });
+            }
+        }
+        Ok(identities)
+    }
+
+    pub fn contains(&mut self, key: &PublicKey) -> Result<bool, SshError> {
+        Ok(self
+            .identities()?
+            .iter()
+            .any(|i| i.public_key.key_data() == key.key_data()))
+    }
+
+    /// Adds a decrypted private key.
+    pub fn add(&mut self, key: &PrivateKey) -> Result<(), SshError> {
+        if key.is_encrypted() {
+            return Err(SshError("Private key must be decrypted first".to_string()));
+        }
+        let mut message = vec![SSH_AGENTC_ADD_IDENTITY];
+        key.key_data()
+            .encode(&mut message)
+            .map_err(|e| SshError(format!("Cannot encode private key: {}", e)))?;
+        put_string(&mut message, key.comment().as_bytes());
+        self.expect_success(&message, "add key to")
+    }
+
+    pub fn remove(&mut self, key: &PublicKey) -> Result<(), SshError> {
+        let blob = key
+            .to_bytes()
+            .map_err(|e| SshError(format!("Cannot encode public key: {}", e)))?;
+        let mut message = vec![SSH_AGENTC_REMOVE_IDENTITY];
+        put_string(&mut message, &blob);
+        self.expect_success(&message, "remove key from")
+    }
+
+    fn expect_success(&mut self, message: &[u8], action: &str) -> Result<(), SshError> {
+        match self.request(message)?.first() {
+            Some(&SSH_AGENT_SUCCESS) => Ok(()),
+            Some(&SSH_AGENT_FAILURE) => {
+                Err(SshError(format!("ssh-agent refused to {} it", action)))
+            }
+            _ => Err(SshError("Unexpected reply from ssh-agent".to_string())),
+        }
+    }
+
+    fn request(&mut self, message: &[u8]) -> Result<Vec<u8>, SshError> {
+        let io_error =
+            |e: std::io::Error| SshError(format!("ssh-agent communication failed: {}", e));
+
+        let mut framed = Vec::with_capacity(message.len() + 4);
+        put_string(&mut framed, message);
+        self.stream.write_all(&framed).map_err(io_error)?;
+        self.stream.flush().map_err(io_error)?;
+
+        let mut len = [0u8; 4];
+        self.stream.read_exact(&mut len).map_err(io_error)?;
+        let len = u32::from_be_bytes(len) as usize;
+        if len == 0 || len > MAX_MESSAGE_LEN {
+            return Err(SshError("Invalid reply length from ssh-agent".to_string()));
+        }
+        let mut reply = vec![0u8; len];
+        self.stream.read_exact(&mut reply).map_err(io_error)?;
+        Ok(reply)
+    }
+}
+
+fn put_string(buf: &mut Vec<u8>, data: &[u8]) {
+    buf.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    buf.extend_from_slice(data);
+}
+
+struct MessageReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> MessageReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], SshError> {
+        if self.data.len() < n {
+            return Err(SshError("Truncated reply from ssh-agent".to_string()));
+        }
+        let (head, tail) = self.data.split_at(n);
+        self.data = tail;
+        Ok(head)
+    }
+
+    fn byte(&mut self) -> Result<u8, SshError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, SshError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn string(&mut self) -> Result<&'a [u8], SshError> {
+        let len = self.u32()? as usize;
+        self.take(len)
+    }
+}
@@ -0,0 +1,370 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::backup::{BackupKind, BackupStore};
+use crate::utils::file::atomic_write;
+
+use super::{is_wildcard, parse_line, quote_arg, BlockKind, SshError};
+
+/// Indentation used when a file gives no hint of its own.
+const DEFAULT_INDENT: &str = "    ";
+
+/// A `Host` or `Match` section as a range of lines in an [`SshConfigDocument`].
+#[derive(Debug, Clone)]
+struct Section {
+    kind: BlockKind,
+    /// Index of the `Host`/`Match` line.
+    header: usize,
+    /// First line of the section, including comments directly above the header.
+    start: usize,
+    /// One past the last line of the section.
+    end: usize,
+}
+
+/// One ssh_config file kept line by line so that edits leave comments,
+/// indentation and unrelated sections untouched.
+#[derive(Debug, Clone)]
+pub struct SshConfigDocument {
+    pub path: PathBuf,
+    lines: Vec<String>,
+}
+
+impl SshConfigDocument {
+    /// Opens `path`. A missing file yields an empty document.
+    pub fn load(path: &Path) -> Result<Self, SshError> {
+        let content = if path.exists() {
+            fs::read_to_string(path)
+                .map_err(|e| SshError(format!("Cannot read {}: {}", path.display(), e)))?
+        } else {
+            String::new()
+        };
+        Ok(Self::parse(&content, path))
+    }
+
+    pub fn parse(content: &str, path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            lines: content.lines().map(str::to_string).collect(),
+        }
+    }
+
+    /// Writes the document back, keeping a backup of the previous version.
+    pub fn save(&self, backups: &BackupStore) -> Result<(), SshError> {
+        backups.create(BackupKind::SshConfig, &self.path)?;
+        atomic_write(&self.path, self.to_string())?;
+        Ok(())
+    }
+
+    pub fn contains_host(&self, alias: &str) -> bool {
+        self.find_host(alias).is_some()
+    }
+
+    /// Adds a `Host` section for `alias`. It goes in front of the first
+    /// wildcard `Host` or `Match` section so those defaults do not shadow it.
+    pub fn insert_host(&mut self, alias: &str, options: &[(&str, &str)]) -> Result<(), SshError> {
+        if self.contains_host(alias) {
+            return Err(SshError(format!(
+                "Host '{}' already exists in {}",
+                alias,
+                self.path.display()
+            )));
+        }
+
+        let indent = self.indent();
+        let mut block = vec![format!("Host {}", alias)];
+        block.extend(
+            options
+                .iter()
+                .map(|(keyword, value)| format!("{}{} {}", indent, keyword, quote_arg(value))),
+        );
+
+        let position = self
+            .sections()
+            .into_iter()
+            .find(|s| match &s.kind {
+                BlockKind::Host(patterns) => patterns.iter().all(|p| is_wildcard(p)),
+                BlockKind::Match(_) => true,
+                BlockKind::Global => false,
+            })
+            .map(|s| s.start);
+
+        match position {
+            Some(at) => {
+                if at > 0 && !self.lines[at - 1].trim().is_empty() {
+                    block.insert(0, String::new());
+                }
+                block.push(String::new());
+                self.lines.splice(at..at, block);
+            }
+            None => {
+                if self.lines.last().is_some_and(|l| !l.trim().is_empty()) {
+                    self.lines.push(String::new());
+                }
+                self.lines.extend(block);
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets `keyword` in the section for `alias`, replacing the existing line
+    /// in place or appending a new one. `None` removes the option.
+    pub fn set_option(
+        &mut self,
+        alias: &str,
+        keyword: &str,
+        value: Option<&str>,
+    ) -> Result<(), SshError> {
+        let section = self.require_host(alias)?;
+        let wanted = keyword.to_lowercase();
+
+        let existing: Vec<usize> = (section.header + 1..section.end)
+            .filter(|&i| parse_line(&self.lines[i]).is_some_and(|o| o.keyword == wanted))
+            .collect();
+
+        match (existing.first(), value) {
+            (Some(&first), Some(value)) => {
+                self.lines[first] = rewrite_value(&self.lines[first], &quote_arg(value));
+                for &i in existing[1..].iter().rev() {
+                    self.lines.remove(i);
+                }
+            }
+            (Some(_), None) => {
+                for &i in existing.iter().rev() {
+                    self.lines.remove(i);
+                }
+            }
+            (None, Some(value)) => {
+                let indent = self.section_indent(&section);
+                let at = self.last_content_line(&section) + 1;
+                self.lines
+                    .insert(at, format!("{}{} {}", indent, keyword, quote_arg(value)));
+            }
+            (None, None) => {}
+        }
+        Ok(())
+    }
+
+    /// Removes `alias`. A section shared with other aliases only loses the
+    /// pattern; otherwise the whole section goes, with its leading comments.
+    pub fn remove_host(&mut self, alias: &str) -> Result<(), SshError> {
+        let section = self.require_host(alias)?;
+        let BlockKind::Host(patterns) = &section.kind else {
+            unreachable!("require_host only returns Host sections");
+        };
+
+        if patterns.len() > 1 {
+            let remaining: Vec<String> = patterns
+                .iter()
+                .filter(|p| *p != alias)
+                .map(|p| quote_arg(p))
+                .collect();
+            self.lines[section.header] =
+                rewrite_value(&self.lines[section.header], &remaining.join(" "));
+            return Ok(());
+        }
+
+        self.lines.drain(section.start..section.end);
+        // Avoid leaving two blank lines where the section used to be.
+        let at = section.start;
+        if at > 0
+            && self.lines.get(at - 1).is_some_and(|l| l.trim().is_empty())
+            && self.lines.get(at).is_none_or(|l| l.trim().is_empty())
+        {
+            self.lines.remove(at - 1);
+        }
+        Ok(())
+    }
+
+    /// Moves `alias` out of a `Host` line it shares with other patterns into
+    /// its own section, carrying over the shared options, so it can be edited
+    /// without affecting the others. Returns the aliases it was shared with.
+    pub fn split_host(&mut self, alias: &str) -> Result<Vec<String>, SshError> {
+        let section = self.require_host(alias)?;
+        let BlockKind::Host(patterns) = &section.kind else {
+            unreachable!("require_host only returns Host sections");
+        };
+        let others: Vec<String> = patterns.iter().filter(|p| *p != alias).cloned().collect();
+        if others.is_empty() {
+            return Ok(others);
+        }
+
+        let indent = leading_whitespace(&self.lines[section.header]).to_string();
+        let mut block = vec![format!("{}Host {}", indent, quote_arg(alias))];
+        block.extend(self.lines[section.header + 1..=self.last_content_line(&section)].to_vec());
+        block.push(String::new());
+
+        let remaining: Vec<String> = others.iter().map(|p| quote_arg(p)).collect();
+        self.lines[section.header] =
+            rewrite_value(&self.lines[section.header], &remaining.join(" "));
+        // The copy takes the shared section's place so the order ssh
+        // evaluates sections in stays the same for every alias.
+        self.lines.splice(section.header..section.header, block);
+        Ok(others)
+    }
+
+    /// Renames `old` to `new` on its `Host` line.
+    pub fn rename_host(&mut self, old: &str, new: &str) -> Result<(), SshError> {
+        if self.contains_host(new) {
+            return Err(SshError(format!(
+                "Host '{}' already exists in {}",
+                new,
+                self.path.display()
+            )));
+        }
+        let section = self.require_host(old)?;
+        let BlockKind::Host(patterns) = &section.kind else {
+            unreachable!("require_host only returns Host sections");
+        };
+
+        let renamed: Vec<String> = patterns
+            .iter()
+            .map(|p| {
+                if p == old {
+                    quote_arg(new)
+                } else {
+                    quote_arg(p)
+                }
+            })
+            .collect();
+        self.lines[section.header] = rewrite_value(&self.lines[section.header], &renamed.join(" "));
+        Ok(())
+    }
+
+    fn find_host(&self, alias: &str) -> Option<Section> {
+        self.sections().into_iter().find(|s| match &s.kind {
+            BlockKind::Host(patterns) => patterns.iter().any(|p| p == alias),
+            _ => false,
+        })
+    }
+
+    fn require_host(&self, alias: &str) -> Result<Section, SshError> {
+        self.find_host(alias).ok_or_else(|| {
+            SshError(format!(
+                "Host '{}' not found in {}",
+                alias,
+                self.path.display()
+            ))
+        })
+    }
+
+    fn sections(&self) -> Vec<Section> {
+        let mut sections: Vec<Section> = Vec::new();
+        for (i, line) in self.lines.iter().enumerate() {
+            let Some(option) = parse_line(line) else {
+                continue;
+            };
+            let kind = match option.keyword.as_str() {
+                "host" => BlockKind::Host(option.args),
+                "match" => BlockKind::Match(option.args),
+                _ => continue,
+            };
+
+            // Comment lines directly above the header describe this section.
+            let floor = sections.last().map_or(0, |s| s.header + 1);
+            let mut start = i;
+            while start > floor && self.lines[start - 1].trim_start().starts_with('#') {
+                start -= 1;
+            }
+            if let Some(previous) = sections.last_mut() {
+                previous.end = start;
+            }
+            sections.push(Section {
+                kind,
+                header: i,
+                start,
+                end: self.lines.len(),
+            });
+        }
+        sections
+    }
+
+    fn last_content_line(&self, section: &Section) -> usize {
+        (section.header..section.end)
+            .rev()
+            .find(|&i| !self.lines[i].trim().is_empty())
+            .unwrap_or(section.header)
+    }
+
+    /// Indentation of the first indented option in the section, falling back
+    /// to the file-wide style.
+    fn section_indent(&self, section: &Section) -> String {
+        (section.header + 1..section.end)
+            .map(|i| &self.lines[i])
+            .find(|l| parse_line(l).is_some())
+            .map(|l| leading_whitespace(l).to_string())
+            .unwrap_or_else(|| self.indent())
+    }
+
+    fn indent(&self) -> String {
+        self.lines
+            .iter()
+            .filter(|l| parse_line(l).is_some_and(|o| o.keyword != "host" && o.keyword != "match"))
+            .map(|l| leading_whitespace(l))
+            .find(|ws| !ws.is_empty())
+            .unwrap_or(DEFAULT_INDENT)
+            .to_string()
+    }
+}
+
+impl std::fmt::Display for SshConfigDocument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+fn leading_whitespace(line: &str) -> &str {
+    &line[..line.len() - line.trim_start().len()]
+}
+
+/// Replaces the value of an option line, keeping its indentation, keyword
+/// spelling, separator and any trailing comment.
+fn rewrite_value(line: &str, value: &str) -> String {
+    let indent = leading_whitespace(line);
+    let body = line.trim_start();
+    let key_end = body
+        .find(|c: char| c.is_whitespace() || c == '=')
+        .unwrap_or(body.len());
+    let rest = &body[key_end..];
+    let separator_len = rest.len() - rest.trim_start_matches([' ', '\t', '=']).len();
+    let separator = if separator_len == 0 {
+        " "
+    } else {
+        &rest[..separator_len]
+    };
+
+    let args = &rest[separator_len..];
+    let comment = match comment_start(args) {
+        Some(i) => {
+            let before = &args[..i];
+            &args[before.trim_end().len()..]
+        }
+        None => "",
+    };
+
+    format!(
+        "{}{}{}{}{}",
+        indent,
+        &body[..key_end],
+        separator,
+        value,
+        comment
+    )
+}
+
+/// Byte offset of a `#` that starts a trailing comment, if any.
+fn comment_start(args: &str) -> Option<usize> {
+    let mut quoted = false;
+    let mut previous = ' ';
+    for (i, c) in args.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '#' if !quoted && previous.is_whitespace() => return Some(i),
+            _ => {}
+        }
+        previous = c;
+    }
+    None
+}
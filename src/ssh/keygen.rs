@@ -0,0 +1,243 @@
+use ssh_key::private::{EcdsaKeypair, Ed25519Keypair, KeypairData, RsaKeypair};
+use ssh_key::rand_core::OsRng;
+use ssh_key::{EcdsaCurve, LineEnding, PrivateKey};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::keys::public_key_path;
+use super::SshError;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum KeyAlgorithm {
+    #[default]
+    Ed25519,
+    Rsa,
+    Ecdsa,
+}
+
+impl KeyAlgorithm {
+    pub const ALL: [KeyAlgorithm; 3] = [
+        KeyAlgorithm::Ed25519,
+        KeyAlgorithm::Rsa,
+        KeyAlgorithm::Ecdsa,
+    ];
+
+    /// Name understood by `ssh-keygen -t`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KeyAlgorithm::Ed25519 => "ed25519",
+            KeyAlgorithm::Rsa => "rsa",
+            KeyAlgorithm::Ecdsa => "ecdsa",
+        }
+    }
+
+    pub fn default_bits(&self) -> Option<u32> {
+        match self {
+            KeyAlgorithm::Ed25519 => None,
+            KeyAlgorithm::Rsa => Some(4096),
+            KeyAlgorithm::Ecdsa => Some(256),
+        }
+    }
+
+    /// Checks a bit size against what the algorithm supports.
+    pub fn validate_bits(&self, bits: u32) -> Result<(), SshError> {
+        let valid = match self {
+            KeyAlgorithm::Ed25519 => false,
+            KeyAlgorithm::Rsa => (2048..=16384).contains(&bits),
+            KeyAlgorithm::Ecdsa => matches!(bits, 256 | 384 | 521),
+        };
+        if valid {
+            return Ok(());
+        }
+        Err(SshError(match self {
+            KeyAlgorithm::Ed25519 => {
+                "ed25519 keys have a fixed size; omit the bit size".to_string()
+            }
+            KeyAlgorithm::Rsa => format!("RSA keys must be 2048 to 16384 bits, got {}", bits),
+            KeyAlgorithm::Ecdsa => format!("ECDSA keys must be 256, 384 or 521 bits, got {}", bits),
+        }))
+    }
+}
+
+impl std::fmt::Display for KeyAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct KeyGenOptions {
+    pub algorithm: KeyAlgorithm,
+    pub bits: Option<u32>,
+    /// Empty for an unencrypted key.
+    pub passphrase: String,
+    pub comment: String,
+    /// Shell out to `ssh-keygen` instead of generating the key in-process.
+    pub use_ssh_keygen: bool,
+}
+
+/// Generates a key pair at `path` (and `path.pub`). Keys are generated
+/// in-process unless `use_ssh_keygen` is set.
+pub fn generate(path: &Path, options: &KeyGenOptions) -> Result<(), SshError> {
+    if path.exists() {
+        return Err(SshError(format!(
+            "Key already exists at {}",
+            path.display()
+        )));
+    }
+    if let Some(bits) = options.bits {
+        options.algorithm.validate_bits(bits)?;
+    }
+    if let Some(parent) = path.parent() {
+        create_private_dir(parent)?;
+    }
+
+    if options.use_ssh_keygen {
+        generate_with_ssh_keygen(path, options)
+    } else {
+        generate_native(path, options)
+    }
+}
+
+fn generate_native(path: &Path, options: &KeyGenOptions) -> Result<(), SshError> {
+    let key_error = |e: ssh_key::Error| SshError(format!("Key generation failed: {}", e));
+    let mut rng = OsRng;
+
+    let bits = options.bits.or(options.algorithm.default_bits());
+    let key_data = match options.algorithm {
+        KeyAlgorithm::Ed25519 => KeypairData::from(Ed25519Keypair::random(&mut rng)),
+        KeyAlgorithm::Rsa => KeypairData::from(
+            RsaKeypair::random(&mut rng, bits.unwrap_or(4096) as usize).map_err(key_error)?,
+        ),
+        KeyAlgorithm::Ecdsa => {
+            let curve = match bits {
+                Some(384) => EcdsaCurve::NistP384,
+                Some(521) => EcdsaCurve::NistP521,
+                _ => EcdsaCurve::NistP256,
+            };
+            KeypairData::from(EcdsaKeypair::random(&mut rng, curve).map_err(key_error)?)
+        }
+    };
+
+    let mut key = PrivateKey::new(key_data, options.comment.as_str()).map_err(key_error)?;
+    // Taken before encryption, which moves the comment into the encrypted part.
+    let public_key = key.public_key().clone();
+    if !options.passphrase.is_empty() {
+        key = key
+            .encrypt(&mut rng, &options.passphrase)
+            .map_err(key_error)?;
+    }
+
+    // Private key is written with 0600 permissions on Unix.
+    key.write_openssh_file(path, LineEnding::LF)
+        .map_err(key_error)?;
+    if let Err(e) = public_key.write_openssh_file(&public_key_path(path)) {
+        let _ = fs::remove_file(path);
+        return Err(key_error(e));
+    }
+    Ok(())
+}
+
+/// The passphrase never goes on the `ssh-keygen` command line, where other
+/// users could read it. The key is created unencrypted in a private staging
+/// directory next to `path`, encrypted there and only then moved into place,
+/// so no unencrypted key ever sits at `path`.
+fn generate_with_ssh_keygen(path: &Path, options: &KeyGenOptions) -> Result<(), SshError> {
+    let staging = staging_dir(path)?;
+    let staged = staging.join("key");
+    let result = run_ssh_keygen(&staged, options)
+        .and_then(|()| {
+            if options.passphrase.is_empty() {
+                return Ok(());
+            }
+            encrypt_in_place(&staged, &options.passphrase)
+        })
+        .and_then(|()| move_key_pair(&staged, path));
+    let _ = fs::remove_dir_all(&staging);
+    result
+}
+
+fn run_ssh_keygen(path: &Path, options: &KeyGenOptions) -> Result<(), SshError> {
+    let mut command = Command::new("ssh-keygen");
+    command
+        .arg("-q")
+        .arg("-t")
+        .arg(options.algorithm.as_str())
+        .arg("-f")
+        .arg(path)
+        .arg("-N")
+        .arg("");
+    if !options.comment.is_empty() {
+        command.arg("-C").arg(&options.comment);
+    }
+    if let Some(bits) = options.bits {
+        command.arg("-b").arg(bits.to_string());
+    }
+
+    let output = command
+        .output()
+        .map_err(|e| SshError(format!("Failed to run ssh-keygen: {}", e)))?;
+    if !output.status.success() {
+        return Err(SshError(format!(
+            "ssh-keygen failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+fn encrypt_in_place(path: &Path, passphrase: &str) -> Result<(), SshError> {
+    let key_error = |e: ssh_key::Error| SshError(format!("Key encryption failed: {}", e));
+    let key = PrivateKey::read_openssh_file(path).map_err(key_error)?;
+    let key = key.encrypt(&mut OsRng, passphrase).map_err(key_error)?;
+    key.write_openssh_file(path, LineEnding::LF)
+        .map_err(key_error)
+}
+
+/// A fresh 0700 directory beside `path`, on the same file system so that
+/// the finished key can be renamed into place.
+fn staging_dir(path: &Path) -> Result<PathBuf, SshError> {
+    let parent = path.parent().unwrap_or(Path::new("."));
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let dir = parent.join(format!(".{}.guse-keygen-{}", name, std::process::id()));
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    let mut builder = fs::DirBuilder::new();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder.create(&dir)?;
+    Ok(dir)
+}
+
+/// Moves `from` and `from.pub` to `to` and `to.pub`, the private key last.
+fn move_key_pair(from: &Path, to: &Path) -> Result<(), SshError> {
+    let move_error =
+        |e: std::io::Error| SshError(format!("Cannot move key to {}: {}", to.display(), e));
+    fs::rename(public_key_path(from), public_key_path(to)).map_err(move_error)?;
+    if let Err(e) = fs::rename(from, to) {
+        let _ = fs::remove_file(public_key_path(to));
+        return Err(move_error(e));
+    }
+    Ok(())
+}
+
+/// Creates `dir` (and parents) with 0700 permissions on Unix, like `~/.ssh`.
+fn create_private_dir(dir: &Path) -> Result<(), SshError> {
+    if dir.exists() {
+        return Ok(());
+    }
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder.create(dir)?;
+    Ok(())
+}
@@ -0,0 +1,237 @@
+use chrono::{DateTime, Local};
+use ssh_key::public::KeyData;
+use ssh_key::{EcdsaCurve, HashAlg, PrivateKey, PublicKey};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::{SshError, SshHost};
+
+/// File names ssh tries when a host has no `IdentityFile`.
+const DEFAULT_IDENTITIES: [&str; 6] = [
+    "id_rsa",
+    "id_ecdsa",
+    "id_ecdsa_sk",
+    "id_ed25519",
+    "id_ed25519_sk",
+    "id_dsa",
+];
+
+/// A key pair found on disk. `path` is the private key, or the `.pub` file
+/// when only the public half exists.
+#[derive(Debug, Clone)]
+pub struct KeyInfo {
+    pub path: PathBuf,
+    pub has_private: bool,
+    pub has_public: bool,
+    pub algorithm: String,
+    pub bits: Option<u32>,
+    /// `SHA256:...` fingerprint as printed by `ssh-keygen -l`.
+    pub fingerprint: String,
+    pub comment: String,
+    pub modified: Option<DateTime<Local>>,
+}
+
+impl KeyInfo {
+    /// Reads the key at `path`, taking metadata from `path.pub` when present
+    /// and falling back to the public half stored in the private key file.
+    pub fn read(path: &Path) -> Result<Self, SshError> {
+        let private_path = private_key_path(path);
+        let public_path = public_key_path(&private_path);
+        let has_private = private_path.is_file();
+        let has_public = public_path.is_file();
+
+        let public_key = load_public_key(&private_path)?;
+
+        let path = if has_private {
+            private_path
+        } else {
+            public_path
+        };
+        let modified = fs::metadata(&path)
+            .and_then(|m| m.modified())
+            .ok()
+            .map(DateTime::<Local>::from);
+
+        Ok(KeyInfo {
+            algorithm: algorithm_name(public_key.key_data()).to_string(),
+            bits: key_bits(public_key.key_data()),
+            fingerprint: public_key.fingerprint(HashAlg::Sha256).to_string(),
+            comment: public_key.comment().to_string(),
+            path,
+            has_private,
+            has_public,
+            modified,
+        })
+    }
+
+    pub fn is_default_identity(&self) -> bool {
+        self.path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| DEFAULT_IDENTITIES.contains(&n.trim_end_matches(".pub")))
+    }
+}
+
+/// Loads the public half of the key at `path`, from `path.pub` when present
+/// and from the public part stored in the private key file otherwise.
+pub fn load_public_key(path: &Path) -> Result<PublicKey, SshError> {
+    let private_path = private_key_path(path);
+    let public_path = public_key_path(&private_path);
+
+    if public_path.is_file() {
+        return PublicKey::read_openssh_file(&public_path)
+            .map_err(|e| SshError(format!("Cannot read {}: {}", public_path.display(), e)));
+    }
+    Ok(PrivateKey::read_openssh_file(&private_path)
+        .map_err(|e| SshError(format!("Cannot read {}: {}", private_path.display(), e)))?
+        .public_key()
+        .clone())
+}
+
+/// Returns the public key for `path` in `authorized_keys` format, reading
+/// `path.pub` when present and deriving it from the private key otherwise.
+pub fn read_public_key(path: &Path) -> Result<String, SshError> {
+    let private_path = private_key_path(path);
+    let public_path = public_key_path(&private_path);
+
+    if public_path.is_file() {
+        let content = fs::read_to_string(&public_path)
+            .map_err(|e| SshError(format!("Cannot read {}: {}", public_path.display(), e)))?;
+        return Ok(content.trim().to_string());
+    }
+
+    PrivateKey::read_openssh_file(&private_path)
+        .map_err(|e| SshError(format!("Cannot read {}: {}", private_path.display(), e)))?
+        .public_key()
+        .to_openssh()
+        .map_err(|e| SshError(format!("Cannot encode public key: {}", e)))
+}
+
+/// Strips a trailing `.pub` so either half of a pair maps to the private key.
+pub fn private_key_path(path: &Path) -> PathBuf {
+    match path.to_str().and_then(|p| p.strip_suffix(".pub")) {
+        Some(stripped) => PathBuf::from(stripped),
+        None => path.to_path_buf(),
+    }
+}
+
+pub fn public_key_path(private_key: &Path) -> PathBuf {
+    let mut path = private_key.as_os_str().to_owned();
+    path.push(".pub");
+    PathBuf::from(path)
+}
+
+/// Finds every key pair in `dir`. Files that are not SSH keys are skipped.
+pub fn scan(dir: &Path) -> Vec<KeyInfo> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut candidates: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_file() && looks_like_key(p))
+        .map(|p| private_key_path(&p))
+        .collect();
+    candidates.sort();
+    candidates.dedup();
+
+    candidates
+        .iter()
+        .filter_map(|p| KeyInfo::read(p).ok())
+        .collect()
+}
+
+fn looks_like_key(path: &Path) -> bool {
+    let Ok(content) = fs::read_to_string(path) else {
+        return false;
+    };
+    let content = content.trim();
+    let first_line = content.lines().next().unwrap_or_default();
+    (first_line.starts_with("-----BEGIN") && first_line.contains("PRIVATE KEY"))
+        || (path.extension().is_some_and(|e| e == "pub")
+            && PublicKey::from_openssh(content).is_ok())
+}
+
+/// Expands an `IdentityFile` value for `host` the way ssh does: `~`,
+/// `${VAR}` and the `%` tokens ssh_config(5) allows. Relative paths are
+/// taken relative to the home directory. Returns `None` when the value uses
+/// a token that cannot be worked out without connecting, such as `%C`.
+pub fn expand_identity_path(value: &str, host: &SshHost) -> Option<PathBuf> {
+    let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+    let local_user = std::env::var("USER")
+        .or_else(|_| std::env::var("LOGNAME"))
+        .ok();
+
+    let mut expanded = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '%' => match chars.next()? {
+                '%' => expanded.push('%'),
+                'd' => expanded.push_str(&home.to_string_lossy()),
+                'h' => expanded.push_str(host.hostname.as_deref().unwrap_or(&host.alias)),
+                'n' => expanded.push_str(&host.alias),
+                'p' => expanded.push_str(host.port.as_deref().unwrap_or("22")),
+                'r' => expanded.push_str(host.user.as_deref().or(local_user.as_deref())?),
+                'u' => expanded.push_str(local_user.as_deref()?),
+                _ => return None,
+            },
+            '$' if chars.as_str().starts_with('{') => {
+                let rest = &chars.as_str()[1..];
+                let end = rest.find('}')?;
+                expanded.push_str(&std::env::var(&rest[..end]).ok()?);
+                chars = rest[end + 1..].chars();
+            }
+            _ => expanded.push(c),
+        }
+    }
+
+    let expanded = PathBuf::from(shellexpand::tilde(&expanded).to_string());
+    Some(if expanded.is_absolute() {
+        expanded
+    } else {
+        home.join(expanded)
+    })
+}
+
+/// Compares two key paths, resolving symlinks when both exist.
+pub fn same_key(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+fn algorithm_name(key: &KeyData) -> &'static str {
+    match key {
+        KeyData::Dsa(_) => "DSA",
+        KeyData::Ecdsa(_) => "ECDSA",
+        KeyData::Ed25519(_) => "ED25519",
+        KeyData::Rsa(_) => "RSA",
+        KeyData::SkEcdsaSha2NistP256(_) => "ECDSA-SK",
+        KeyData::SkEd25519(_) => "ED25519-SK",
+        _ => "UNKNOWN",
+    }
+}
+
+fn key_bits(key: &KeyData) -> Option<u32> {
+    match key {
+        KeyData::Ed25519(_) | KeyData::SkEd25519(_) | KeyData::SkEcdsaSha2NistP256(_) => Some(256),
+        KeyData::Ecdsa(ecdsa) => Some(match ecdsa.curve() {
+            EcdsaCurve::NistP256 => 256,
+            EcdsaCurve::NistP384 => 384,
+            EcdsaCurve::NistP521 => 521,
+        }),
+        KeyData::Rsa(rsa) => rsa.n.as_positive_bytes().map(bit_length),
+        KeyData::Dsa(dsa) => dsa.p.as_positive_bytes().map(bit_length),
+        _ => None,
+    }
+}
+
+fn bit_length(bytes: &[u8]) -> u32 {
+    match bytes.first() {
+        Some(first) => bytes.len() as u32 * 8 - first.leading_zeros(),
+        None => 0,
+    }
+}
@@ -0,0 +1,449 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::utils::get_ssh_config_path;
+
+pub mod agent;
+pub mod edit;
+pub mod keygen;
+pub mod keys;
+
+/// Maximum nesting depth for `Include` directives, same as OpenSSH.
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+#[derive(Debug)]
+pub struct SshError(pub String);
+
+impl std::fmt::Display for SshError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for SshError {}
+
+impl From<crate::backup::BackupError> for SshError {
+    fn from(err: crate::backup::BackupError) -> Self {
+        SshError(err.to_string())
+    }
+}
+
+impl From<std::io::Error> for SshError {
+    fn from(err: std::io::Error) -> Self {
+        SshError(format!("IO Error: {}", err))
+    }
+}
+
+/// A single `Keyword value...` line. The keyword is stored lowercased.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SshOption {
+    pub keyword: String,
+    pub args: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockKind {
+    /// Options that appear before the first `Host` or `Match` line.
+    Global,
+    Host(Vec<String>),
+    Match(Vec<String>),
+}
+
+/// A `Host` or `Match` section together with the options that belong to it.
+#[derive(Debug, Clone)]
+pub struct Block {
+    pub kind: BlockKind,
+    pub options: Vec<SshOption>,
+    /// File the section header was read from.
+    pub file: PathBuf,
+}
+
+impl Block {
+    /// Whether this section applies to `host`. `Match` criteria that cannot be
+    /// evaluated offline (`exec`, `localuser`, ...) are treated as not matching.
+    pub fn applies_to(&self, host: &str) -> bool {
+        match &self.kind {
+            BlockKind::Global => true,
+            BlockKind::Host(patterns) => match_pattern_list(patterns, host),
+            BlockKind::Match(criteria) => match_criteria(criteria, host),
+        }
+    }
+}
+
+/// A concrete host alias with the values ssh would use for it.
+#[derive(Debug, Clone)]
+pub struct SshHost {
+    pub alias: String,
+    pub hostname: Option<String>,
+    pub user: Option<String>,
+    pub port: Option<String>,
+    pub identity_files: Vec<String>,
+    /// File that declares the alias.
+    pub source: PathBuf,
+}
+
+impl SshHost {
+    /// Label used by the interactive host pickers.
+    pub fn label(&self) -> String {
+        let mut info = self.alias.clone();
+        if let Some(hostname) = &self.hostname {
+            info.push_str(&format!(" ({})", hostname));
+        }
+        if let Some(user) = &self.user {
+            info.push_str(&format!(" - User: {}", user));
+        }
+        if let Some(port) = &self.port {
+            info.push_str(&format!(" - Port: {}", port));
+        }
+        info
+    }
+}
+
+/// Parsed ssh_config(5) file with all `Include` directives expanded.
+#[derive(Debug, Clone, Default)]
+pub struct SshConfig {
+    pub blocks: Vec<Block>,
+    /// Every file that was read, in the order it was read.
+    pub files: Vec<PathBuf>,
+}
+
+impl SshConfig {
+    /// Loads `~/.ssh/config`. A missing file yields an empty configuration.
+    pub fn load() -> Result<Self, SshError> {
+        let path = get_ssh_config_path().map_err(|e| SshError(e.to_string()))?;
+        Self::load_from(&path)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, SshError> {
+        if !path.exists() {
+            return Ok(SshConfig::default());
+        }
+        let content = fs::read_to_string(path)
+            .map_err(|e| SshError(format!("Cannot read {}: {}", path.display(), e)))?;
+        Self::parse(&content, path, path.parent().unwrap_or(Path::new(".")))
+    }
+
+    /// Parses `content` as if it had been read from `path`. Relative `Include`
+    /// paths are resolved against `base_dir`.
+    pub fn parse(content: &str, path: &Path, base_dir: &Path) -> Result<Self, SshError> {
+        let mut config = SshConfig::default();
+        let mut parser = Parser {
+            config: &mut config,
+            base_dir: base_dir.to_path_buf(),
+        };
+        parser.parse(content, path, 0, None)?;
+        Ok(config)
+    }
+
+    /// All concrete aliases declared by `Host` lines, in file order. Wildcard
+    /// and negated patterns are skipped.
+    pub fn hosts(&self) -> Vec<SshHost> {
+        let mut hosts: Vec<SshHost> = Vec::new();
+        for block in &self.blocks {
+            let BlockKind::Host(patterns) = &block.kind else {
+                continue;
+            };
+            for pattern in patterns {
+                if is_wildcard(pattern) || hosts.iter().any(|h| &h.alias == pattern) {
+                    continue;
+                }
+                let mut host = self.resolve(pattern);
+                host.source = block.file.clone();
+                hosts.push(host);
+            }
+        }
+        hosts
+    }
+
+    /// Expanded `IdentityFile` paths for `host` that exist on disk, in the
+    /// order ssh tries them.
+    pub fn identity_paths(&self, host: &str) -> Vec<PathBuf> {
+        let host = self.resolve(host);
+        host.identity_files
+            .iter()
+            .filter_map(|i| keys::expand_identity_path(i, &host))
+            .filter(|p| p.exists() || keys::public_key_path(p).exists())
+            .collect()
+    }
+
+    /// Public keys of the identities configured for `host`. Keys that cannot
+    /// be read are skipped.
+    pub fn public_keys(&self, host: &str) -> Vec<(PathBuf, ssh_key::PublicKey)> {
+        self.identity_paths(host)
+            .into_iter()
+            .filter_map(|path| keys::load_public_key(&path).ok().map(|key| (path, key)))
+            .collect()
+    }
+
+    /// Resolves the effective options for `host` the way ssh does: the first
+    /// value obtained for a keyword wins, except `IdentityFile` which
+    /// accumulates.
+    pub fn resolve(&self, host: &str) -> SshHost {
+        let mut resolved = SshHost {
+            alias: host.to_string(),
+            hostname: None,
+            user: None,
+            port: None,
+            identity_files: Vec::new(),
+            source: PathBuf::new(),
+        };
+
+        for block in self.blocks.iter().filter(|b| b.applies_to(host)) {
+            for option in &block.options {
+                let Some(value) = option.args.first() else {
+                    continue;
+                };
+                match option.keyword.as_str() {
+                    "hostname" if resolved.hostname.is_none() => {
+                        resolved.hostname = Some(value.clone())
+                    }
+                    "user" if resolved.user.is_none() => resolved.user = Some(value.clone()),
+                    "port" if resolved.port.is_none() => resolved.port = Some(value.clone()),
+                    "identityfile" if !resolved.identity_files.contains(value) => {
+                        resolved.identity_files.push(value.clone())
+                    }
+                    _ => {}
+                }
+            }
+        }
+        resolved
+    }
+}
+
+struct Parser<'a> {
+    config: &'a mut SshConfig,
+    base_dir: PathBuf,
+}
+
+impl Parser<'_> {
+    /// Parses one file. `current` is the section that encloses the file: lines
+    /// before its first section header belong to it.
+    fn parse(
+        &mut self,
+        content: &str,
+        path: &Path,
+        depth: usize,
+        mut current: Option<usize>,
+    ) -> Result<(), SshError> {
+        if depth > MAX_INCLUDE_DEPTH {
+            return Err(SshError(format!(
+                "Include nested too deeply at {}",
+                path.display()
+            )));
+        }
+        self.config.files.push(path.to_path_buf());
+
+        for raw in content.lines() {
+            let Some(option) = parse_line(raw) else {
+                continue;
+            };
+
+            match option.keyword.as_str() {
+                "host" => {
+                    self.config.blocks.push(Block {
+                        kind: BlockKind::Host(option.args),
+                        options: Vec::new(),
+                        file: path.to_path_buf(),
+                    });
+                    current = Some(self.config.blocks.len() - 1);
+                }
+                "match" => {
+                    self.config.blocks.push(Block {
+                        kind: BlockKind::Match(option.args),
+                        options: Vec::new(),
+                        file: path.to_path_buf(),
+                    });
+                    current = Some(self.config.blocks.len() - 1);
+                }
+                "include" => {
+                    for pattern in &option.args {
+                        for included in self.expand_include(pattern) {
+                            let Ok(content) = fs::read_to_string(&included) else {
+                                continue;
+                            };
+                            self.parse(&content, &included, depth + 1, current)?;
+                        }
+                    }
+                    // Lines after the `Include` continue the enclosing section,
+                    // so reopen it if the included files started new ones.
+                    if let Some(i) = current.filter(|&i| i + 1 < self.config.blocks.len()) {
+                        let mut block = self.config.blocks[i].clone();
+                        block.options.clear();
+                        self.config.blocks.push(block);
+                        current = Some(self.config.blocks.len() - 1);
+                    }
+                }
+                _ => {
+                    let i = match current {
+                        Some(i) => i,
+                        None => {
+                            self.config.blocks.push(Block {
+                                kind: BlockKind::Global,
+                                options: Vec::new(),
+                                file: path.to_path_buf(),
+                            });
+                            self.config.blocks.len() - 1
+                        }
+                    };
+                    current = Some(i);
+                    self.config.blocks[i].options.push(option);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn expand_include(&self, pattern: &str) -> Vec<PathBuf> {
+        let expanded = shellexpand::tilde(pattern).to_string();
+        let full = if Path::new(&expanded).is_absolute() {
+            PathBuf::from(expanded)
+        } else {
+            self.base_dir.join(expanded)
+        };
+        match glob::glob(&full.to_string_lossy()) {
+            Ok(paths) => paths
+                .filter_map(Result::ok)
+                .filter(|p| p.is_file())
+                .collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+/// Parses one line into an option. Returns `None` for blank lines and
+/// comments. Keywords are matched case-insensitively and may be separated
+/// from their value by whitespace or a single `=`.
+pub fn parse_line(line: &str) -> Option<SshOption> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let key_end = line
+        .find(|c: char| c.is_whitespace() || c == '=')
+        .unwrap_or(line.len());
+    let keyword = line[..key_end].to_lowercase();
+    let mut rest = line[key_end..].trim_start();
+    if let Some(stripped) = rest.strip_prefix('=') {
+        rest = stripped.trim_start();
+    }
+
+    Some(SshOption {
+        keyword,
+        args: split_args(rest),
+    })
+}
+
+/// Splits an argument list, honouring double quotes and stopping at a `#`
+/// that starts a new argument.
+pub fn split_args(input: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    loop {
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+        match chars.peek() {
+            None | Some('#') => break,
+            _ => {}
+        }
+
+        let mut arg = String::new();
+        let mut quoted = false;
+        while let Some(&c) = chars.peek() {
+            if c == '"' {
+                quoted = !quoted;
+            } else if c.is_whitespace() && !quoted {
+                break;
+            } else {
+                arg.push(c);
+            }
+            chars.next();
+        }
+        args.push(arg);
+    }
+    args
+}
+
+/// Quotes a value if ssh would otherwise split it.
+pub fn quote_arg(value: &str) -> String {
+    if value.is_empty() || value.contains(char::is_whitespace) {
+        format!("\"{}\"", value)
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn is_wildcard(pattern: &str) -> bool {
+    pattern.starts_with('!') || pattern.contains(['*', '?'])
+}
+
+/// Matches `text` against a glob supporting `*` and `?`, case-insensitively.
+pub fn match_pattern(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((sp, st)) = star {
+            p = sp + 1;
+            t = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Matches a pattern list (`Host a b !c`). A negated match always wins.
+pub fn match_pattern_list<S: AsRef<str>>(patterns: &[S], host: &str) -> bool {
+    let mut matched = false;
+    for pattern in patterns.iter().map(AsRef::as_ref) {
+        if let Some(negated) = pattern.strip_prefix('!') {
+            if match_pattern(negated, host) {
+                return false;
+            }
+        } else if match_pattern(pattern, host) {
+            matched = true;
+        }
+    }
+    matched
+}
+
+fn match_criteria(criteria: &[String], host: &str) -> bool {
+    let mut iter = criteria.iter();
+    let mut matched = false;
+    while let Some(criterion) = iter.next() {
+        let lower = criterion.to_lowercase();
+        let (negate, name) = match lower.strip_prefix('!') {
+            Some(name) => (true, name.to_string()),
+            None => (false, lower),
+        };
+        let result = match name.as_str() {
+            "all" => true,
+            "host" | "originalhost" => match iter.next() {
+                // Unlike `Host` lines, `Match host` takes a comma-separated list.
+                Some(list) => match_pattern_list(&list.split(',').collect::<Vec<_>>(), host),
+                None => return false,
+            },
+            _ => return false,
+        };
+        if result == negate {
+            return false;
+        }
+        matched = true;
+    }
+    matched
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,474 @@
+use crate::ssh::agent::Agent;
+use crate::ssh::edit::SshConfigDocument;
+use crate::ssh::keygen::{self, KeyAlgorithm, KeyGenOptions};
+use crate::ssh::keys;
+use crate::ssh::{match_pattern, parse_line, split_args, BlockKind, SshConfig, SshHost};
+use std::fs;
+use std::io::{Cursor, Read, Write};
+use std::path::Path;
+
+fn parse(content: &str) -> SshConfig {
+    SshConfig::parse(content, Path::new("config"), Path::new(".")).unwrap()
+}
+
+fn host(config: &SshConfig, alias: &str) -> SshHost {
+    config
+        .hosts()
+        .into_iter()
+        .find(|h| h.alias == alias)
+        .unwrap()
+}
+
+#[test]
+fn test_parse_line_variants() {
+    // Keyword case and `=` separator
+    let option = parse_line("  hostname=github.com").unwrap();
+    assert_eq!(option.keyword, "hostname");
+    assert_eq!(option.args, vec!["github.com"]);
+
+    let option = parse_line("IdentityFile = \"~/.ssh/my key\"").unwrap();
+    assert_eq!(option.keyword, "identityfile");
+    assert_eq!(option.args, vec!["~/.ssh/my key"]);
+
+    // Comments and blank lines
+    assert!(parse_line("# comment").is_none());
+    assert!(parse_line("   ").is_none());
+
+    // Trailing comments
+    assert_eq!(split_args("a b # trailing"), vec!["a", "b"]);
+}
+
+#[test]
+fn test_multi_pattern_hosts_and_wildcards() {
+    let config = parse(
+        "Host work personal\n  User git\n\nHost *.example.com !skip.example.com\n  Port 2222\n\nHost *\n  IdentityFile ~/.ssh/id_default\n",
+    );
+    let aliases: Vec<_> = config.hosts().into_iter().map(|h| h.alias).collect();
+    assert_eq!(aliases, vec!["work", "personal"]);
+
+    let host = host(&config, "personal");
+    assert_eq!(host.user.as_deref(), Some("git"));
+    assert_eq!(
+        host.identity_files.first().map(String::as_str),
+        Some("~/.ssh/id_default")
+    );
+
+    assert_eq!(
+        config.resolve("a.example.com").port.as_deref(),
+        Some("2222")
+    );
+    assert_eq!(config.resolve("skip.example.com").port, None);
+}
+
+#[test]
+fn test_first_value_wins() {
+    let config = parse(
+        "Host github-work\n  HostName github.com\n  IdentityFile ~/.ssh/id_work\n\nHost *\n  HostName ignored\n  User git\n  IdentityFile ~/.ssh/id_default\n",
+    );
+    let host = host(&config, "github-work");
+    assert_eq!(host.hostname.as_deref(), Some("github.com"));
+    assert_eq!(host.user.as_deref(), Some("git"));
+    assert_eq!(
+        host.identity_files,
+        vec!["~/.ssh/id_work", "~/.ssh/id_default"]
+    );
+}
+
+#[test]
+fn test_match_blocks() {
+    let config = parse(
+        "Match host gitlab-*\n  Port 2222\n\nMatch exec \"true\"\n  User nobody\n\nHost gitlab-work\n  HostName gitlab.com\n",
+    );
+    assert!(matches!(config.blocks[0].kind, BlockKind::Match(_)));
+    let host = host(&config, "gitlab-work");
+    assert_eq!(host.port.as_deref(), Some("2222"));
+    assert_eq!(host.user, None);
+}
+
+#[test]
+fn test_commas_only_separate_match_hosts() {
+    let config = parse("Host a,b\n  Port 1\n\nMatch host c,d\n  User git\n");
+    assert_eq!(config.resolve("a").port, None);
+    assert_eq!(config.resolve("a,b").port.as_deref(), Some("1"));
+    assert_eq!(config.resolve("d").user.as_deref(), Some("git"));
+}
+
+#[test]
+fn test_include_directives() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("config.d")).unwrap();
+    fs::write(
+        dir.path().join("config.d/client-a"),
+        "Host client-a\n  HostName a.example.com\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("config.d/client-b"),
+        "Host client-b\n  HostName b.example.com\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("config"),
+        "Include config.d/*\n\nHost personal\n  HostName github.com\n",
+    )
+    .unwrap();
+
+    let config = SshConfig::load_from(&dir.path().join("config")).unwrap();
+    let aliases: Vec<_> = config.hosts().into_iter().map(|h| h.alias).collect();
+    assert_eq!(aliases, vec!["client-a", "client-b", "personal"]);
+    assert_eq!(config.files.len(), 3);
+
+    let host = host(&config, "client-b");
+    assert_eq!(host.hostname.as_deref(), Some("b.example.com"));
+    assert!(host.source.ends_with("config.d/client-b"));
+}
+
+#[test]
+fn test_include_continues_enclosing_section() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("common"), "User git\n").unwrap();
+    fs::write(
+        dir.path().join("config"),
+        "Host work\n  Include common\n  Port 22\n",
+    )
+    .unwrap();
+
+    let config = SshConfig::load_from(&dir.path().join("config")).unwrap();
+    let host = host(&config, "work");
+    assert_eq!(host.user.as_deref(), Some("git"));
+    assert_eq!(host.port.as_deref(), Some("22"));
+}
+
+#[test]
+fn test_match_pattern() {
+    assert!(match_pattern("*", "anything"));
+    assert!(match_pattern("github-*", "GitHub-Work"));
+    assert!(match_pattern("host?", "host1"));
+    assert!(!match_pattern("host?", "host12"));
+    assert!(match_pattern("*.example.*", "a.example.com"));
+}
+
+const DOCUMENT: &str = "# Personal account
+Host github-personal
+\tHostName github.com
+\tIdentityFile ~/.ssh/id_personal # main key
+
+Host work extra
+\tHostName github.com
+
+# Defaults
+Host *
+\tAddKeysToAgent yes
+";
+
+fn document() -> SshConfigDocument {
+    SshConfigDocument::parse(DOCUMENT, Path::new("config"))
+}
+
+#[test]
+fn test_insert_host_before_wildcard() {
+    let mut doc = document();
+    doc.insert_host(
+        "github-work",
+        &[("HostName", "github.com"), ("User", "git")],
+    )
+    .unwrap();
+    let output = doc.to_string();
+    assert!(output
+        .contains("Host github-work\n\tHostName github.com\n\tUser git\n\n# Defaults\nHost *\n"));
+    assert!(output.starts_with("# Personal account\nHost github-personal\n"));
+
+    // Duplicate aliases are refused, including ones on multi-pattern lines
+    assert!(doc.insert_host("github-work", &[]).is_err());
+    assert!(doc.insert_host("extra", &[]).is_err());
+}
+
+#[test]
+fn test_set_option_preserves_formatting() {
+    let mut doc = document();
+    doc.set_option("github-personal", "IdentityFile", Some("~/.ssh/id_new"))
+        .unwrap();
+    doc.set_option("github-personal", "Port", Some("2222"))
+        .unwrap();
+    doc.set_option("work", "hostname", None).unwrap();
+
+    let output = doc.to_string();
+    assert!(output.contains("\tIdentityFile ~/.ssh/id_new # main key\n\tPort 2222\n\nHost work"));
+    assert!(output.contains("Host work extra\n\n# Defaults"));
+    assert!(doc.set_option("missing", "Port", Some("22")).is_err());
+}
+
+#[test]
+fn test_remove_host() {
+    let mut doc = document();
+    doc.remove_host("extra").unwrap();
+    assert!(doc
+        .to_string()
+        .contains("Host work\n\tHostName github.com\n"));
+
+    doc.remove_host("github-personal").unwrap();
+    let output = doc.to_string();
+    assert!(output.starts_with("Host work\n"));
+    assert!(!output.contains("Personal account"));
+    assert!(output.contains("# Defaults\nHost *\n"));
+}
+
+#[test]
+fn test_split_host() {
+    let mut doc = document();
+    assert_eq!(doc.split_host("extra").unwrap(), vec!["work"]);
+    doc.set_option("extra", "Port", Some("2222")).unwrap();
+    assert!(doc.to_string().contains(
+        "Host extra\n\tHostName github.com\n\tPort 2222\n\nHost work\n\tHostName github.com\n\n# Defaults"
+    ));
+
+    // A section of its own is left as it is
+    assert!(doc.split_host("github-personal").unwrap().is_empty());
+    assert!(doc
+        .to_string()
+        .starts_with("# Personal account\nHost github-personal\n"));
+}
+
+#[test]
+fn test_rename_host() {
+    let mut doc = document();
+    doc.rename_host("work", "github-work").unwrap();
+    assert!(doc.to_string().contains("Host github-work extra\n"));
+    assert!(doc.rename_host("github-work", "github-personal").is_err());
+    assert!(doc.rename_host("missing", "other").is_err());
+}
+
+#[test]
+fn test_key_algorithm_bits() {
+    assert!(KeyAlgorithm::Rsa.validate_bits(4096).is_ok());
+    assert!(KeyAlgorithm::Rsa.validate_bits(1024).is_err());
+    assert!(KeyAlgorithm::Ecdsa.validate_bits(384).is_ok());
+    assert!(KeyAlgorithm::Ecdsa.validate_bits(300).is_err());
+    assert!(KeyAlgorithm::Ed25519.validate_bits(256).is_err());
+    assert_eq!(KeyAlgorithm::default(), KeyAlgorithm::Ed25519);
+}
+
+#[test]
+fn test_generate_refuses_existing_key() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("id_existing");
+    fs::write(&path, "secret").unwrap();
+    let result = keygen::generate(&path, &KeyGenOptions::default());
+    assert!(result.is_err());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "secret");
+}
+
+const ED25519_PUB: &str = "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIKLuMI97tgrusx3czzFsOyAoBSubIPHlOpxhdai/3dln work@example.com\n";
+
+#[test]
+fn test_expand_identity_path_tokens() {
+    let config = parse("Host work\n  HostName github.com\n  User git\n  Port 2222\n");
+    let host = host(&config, "work");
+    let home = dirs::home_dir().unwrap();
+
+    assert_eq!(
+        keys::expand_identity_path("~/.ssh/%r@%h:%p-%n%%", &host),
+        Some(home.join(".ssh/git@github.com:2222-work%"))
+    );
+    assert_eq!(
+        keys::expand_identity_path("%d/.ssh/id", &host),
+        Some(home.join(".ssh/id"))
+    );
+    // Values only known when connecting are left unresolved
+    assert_eq!(keys::expand_identity_path("~/.ssh/%C", &host), None);
+    assert_eq!(
+        keys::expand_identity_path("${GUSE_TEST_UNSET_VARIABLE}/id", &host),
+        None
+    );
+}
+
+#[test]
+fn test_key_inventory() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("id_work.pub"), ED25519_PUB).unwrap();
+    fs::write(
+        dir.path().join("known_hosts"),
+        "github.com ssh-ed25519 AAAA\n",
+    )
+    .unwrap();
+    fs::write(dir.path().join("config"), "Host *\n").unwrap();
+
+    let inventory = keys::scan(dir.path());
+    assert_eq!(inventory.len(), 1);
+
+    let key = &inventory[0];
+    assert_eq!(key.algorithm, "ED25519");
+    assert_eq!(key.bits, Some(256));
+    assert_eq!(
+        key.fingerprint,
+        "SHA256:F6pIXn0yGxUQwbKVXeqRd98INjSJAELItgW18vQwlKU"
+    );
+    assert_eq!(key.comment, "work@example.com");
+    assert!(!key.has_private);
+    assert!(key.path.ends_with("id_work.pub"));
+    assert_eq!(
+        keys::private_key_path(&key.path),
+        dir.path().join("id_work")
+    );
+}
+
+#[test]
+fn test_generate_native_ed25519() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("keys").join("id_work");
+    let options = KeyGenOptions {
+        passphrase: "correct horse".to_string(),
+        comment: "work@example.com".to_string(),
+        ..Default::default()
+    };
+    keygen::generate(&path, &options).unwrap();
+
+    let private = ssh_key::PrivateKey::read_openssh_file(&path).unwrap();
+    assert!(private.is_encrypted());
+    let decrypted = private.decrypt("correct horse").unwrap();
+    assert_eq!(decrypted.algorithm(), ssh_key::Algorithm::Ed25519);
+
+    let key = keys::KeyInfo::read(&path).unwrap();
+    assert!(key.has_private && key.has_public);
+    assert_eq!(key.comment, "work@example.com");
+    assert_eq!(
+        key.fingerprint,
+        decrypted
+            .public_key()
+            .fingerprint(ssh_key::HashAlg::Sha256)
+            .to_string()
+    );
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = |p: &Path| fs::metadata(p).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(&path), 0o600);
+        assert_eq!(mode(path.parent().unwrap()), 0o700);
+    }
+}
+
+#[test]
+fn test_generate_native_ecdsa_unencrypted() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("id_ecdsa");
+    let options = KeyGenOptions {
+        algorithm: KeyAlgorithm::Ecdsa,
+        bits: Some(384),
+        ..Default::default()
+    };
+    keygen::generate(&path, &options).unwrap();
+
+    let key = keys::KeyInfo::read(&path).unwrap();
+    assert_eq!(key.algorithm, "ECDSA");
+    assert_eq!(key.bits, Some(384));
+    assert!(!ssh_key::PrivateKey::read_openssh_file(&path)
+        .unwrap()
+        .is_encrypted());
+}
+
+#[test]
+fn test_generate_ssh_keygen_encrypts_in_process() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("id_work");
+    let options = KeyGenOptions {
+        passphrase: "correct horse".to_string(),
+        comment: "work@example.com".to_string(),
+        use_ssh_keygen: true,
+        ..Default::default()
+    };
+    match keygen::generate(&path, &options) {
+        Err(e) if e.0.starts_with("Failed to run ssh-keygen") => return,
+        result => result.unwrap(),
+    }
+
+    let private = ssh_key::PrivateKey::read_openssh_file(&path).unwrap();
+    assert!(private.is_encrypted());
+    let decrypted = private.decrypt("correct horse").unwrap();
+    assert_eq!(decrypted.comment(), "work@example.com");
+    assert_eq!(
+        keys::KeyInfo::read(&path).unwrap().comment,
+        "work@example.com"
+    );
+    // Only the finished pair is left; the staging directory is gone.
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+}
+
+/// Replays canned agent replies and records what was sent.
+struct FakeAgentStream {
+    replies: Cursor<Vec<u8>>,
+    sent: Vec<u8>,
+}
+
+impl FakeAgentStream {
+    fn new(replies: &[&[u8]]) -> Self {
+        let mut data = Vec::new();
+        for reply in replies {
+            data.extend_from_slice(&(reply.len() as u32).to_be_bytes());
+            data.extend_from_slice(reply);
+        }
+        Self {
+            replies: Cursor::new(data),
+            sent: Vec::new(),
+        }
+    }
+}
+
+impl Read for FakeAgentStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.replies.read(buf)
+    }
+}
+
+impl Write for FakeAgentStream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.sent.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn identities_answer(keys: &[(&ssh_key::PublicKey, &str)]) -> Vec<u8> {
+    let mut reply = vec![12];
+    reply.extend_from_slice(&(keys.len() as u32).to_be_bytes());
+    for (key, comment) in keys {
+        let blob = key.to_bytes().unwrap();
+        reply.extend_from_slice(&(blob.len() as u32).to_be_bytes());
+        reply.extend_from_slice(&blob);
+        reply.extend_from_slice(&(comment.len() as u32).to_be_bytes());
+        reply.extend_from_slice(comment.as_bytes());
+    }
+    reply
+}
+
+#[test]
+fn test_agent_identities() {
+    let key = ssh_key::PublicKey::from_openssh(ED25519_PUB).unwrap();
+    let answer = identities_answer(&[(&key, "work@example.com")]);
+    let mut agent = Agent::new(FakeAgentStream::new(&[&answer, &answer]));
+
+    let identities = agent.identities().unwrap();
+    assert_eq!(identities.len(), 1);
+    assert_eq!(identities[0].comment, "work@example.com");
+    assert!(agent.contains(&key).unwrap());
+}
+
+#[test]
+fn test_agent_add_and_remove() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("id_work");
+    keygen::generate(&path, &KeyGenOptions::default()).unwrap();
+    let private = ssh_key::PrivateKey::read_openssh_file(&path).unwrap();
+
+    // SSH_AGENT_SUCCESS, then SSH_AGENT_FAILURE
+    let mut agent = Agent::new(FakeAgentStream::new(&[&[6], &[5]]));
+    agent.add(&private).unwrap();
+    assert!(agent.remove(private.public_key()).is_err());
+
+    let encrypted = private
+        .encrypt(&mut ssh_key::rand_core::OsRng, "secret")
+        .unwrap();
+    assert!(agent.add(&encrypted).is_err());
+}
@@ -1,18 +1,43 @@
-use crate::config::Profile;
-use crate::git::GitConfig;
+use crate::config::inherit::{Resolved, ResolvedProfile};
+use crate::config::state::State;
+use crate::config::transfer::{ImportAction, ImportItem};
+use crate::config::{Profile, ProfileEntry};
+use crate::git::config_file::ConfigValue;
+use crate::git::{GitConfig, RemoteChange, RemoteOutcome};
+use crate::ssh::agent::AgentIdentity;
 use colored::*;
 use prettytable::*;
 
 pub struct UI;
 
 impl UI {
-    pub fn print_profile_table(profile: &Profile, github_user: &str, repo_name: &str) {
+    pub fn print_profile_table(profile: &Profile) {
         let mut table = Table::new();
         table.add_row(row!["Name", &profile.name]);
         table.add_row(row!["Email", &profile.email]);
         table.add_row(row!["SSH Host", &profile.ssh_host]);
-        table.add_row(row!["GitHub User", github_user]);
-        table.add_row(row!["Repository", repo_name]);
+        table.add_row(row!["Strategy", profile.strategy]);
+        table.add_row(row!["Transport", profile.transport]);
+        if let Some(user) = &profile.https_user {
+            table.add_row(row!["HTTPS User", user]);
+        }
+        if let Some(host) = &profile.https_host {
+            table.add_row(row!["HTTPS Host", host]);
+        }
+        table.printstd();
+    }
+
+    pub fn print_remote_changes(changes: &[RemoteChange]) {
+        let mut table = Table::new();
+        table.add_row(row!["Remote", "Old URL", "New URL"]);
+        for change in changes {
+            let new_url = match &change.outcome {
+                RemoteOutcome::Updated(url) => url.green(),
+                RemoteOutcome::Unchanged => "(unchanged)".normal(),
+                RemoteOutcome::Skipped(reason) => format!("(skipped: {})", reason).yellow(),
+            };
+            table.add_row(row![&change.remote, &change.old_url, new_url]);
+        }
         table.printstd();
     }
 
@@ -20,9 +45,16 @@ impl UI {
         println!("\n{}", "⚙️  Current Git Configuration:".cyan().bold());
         println!("{}", "=".repeat(40).cyan());
 
+        let not_set = |value: &str| {
+            if value.is_empty() {
+                "Not configured".red()
+            } else {
+                value.normal()
+            }
+        };
         let mut table = Table::new();
-        table.add_row(row!["Name", &config.user_name]);
-        table.add_row(row!["Email", &config.user_email]);
+        table.add_row(row!["Name", not_set(&config.user_name)]);
+        table.add_row(row!["Email", not_set(&config.user_email)]);
 
         // Display remote URL or "Not configured" if empty
         let remote_display = if config.remote_url.is_empty() {
@@ -31,23 +63,223 @@ impl UI {
             config.remote_url.green()
         };
         table.add_row(row!["Remote", remote_display]);
+        if !config.ssh_command.is_empty() {
+            table.add_row(row!["SSH Command", &config.ssh_command]);
+        }
 
         table.printstd();
         println!();
     }
 
-    pub fn print_profiles(profiles: &Vec<(String, Profile)>) {
+    /// Lists every value of each key with its scope and file, marking the
+    /// one that takes effect.
+    pub fn print_config_origins(keys: &[(&str, Vec<ConfigValue>)]) {
+        println!("{}", "📚 Configuration Precedence:".cyan().bold());
+        println!("{}", "=".repeat(40).cyan());
+
+        let mut table = Table::new();
+        table.add_row(row!["Key", "Scope", "Value", "Origin"]);
+        for (key, values) in keys {
+            if values.is_empty() {
+                table.add_row(row![key, "-", "(not set)".red(), "-"]);
+            }
+            for (i, value) in values.iter().enumerate() {
+                let text = value.value.clone().unwrap_or_default();
+                let text = if i + 1 == values.len() {
+                    format!("{} (wins)", text).green()
+                } else {
+                    text.dimmed()
+                };
+                table.add_row(row![key, value.scope, text, value.origin.display()]);
+            }
+        }
+        table.printstd();
+        println!();
+    }
+
+    /// Lists profiles with the time each was last switched to, marking the
+    /// configured default.
+    pub fn print_profiles(profiles: &[(String, Profile)], default: Option<&str>, state: &State) {
         println!("\n{}", "📋 Available Profiles:".cyan().bold());
         println!("{}", "=".repeat(40).cyan());
 
         let mut table = Table::new();
-        table.add_row(row!["Profile", "Name", "Email", "SSH Host"]);
+        table.add_row(row![
+            "Profile",
+            "Name",
+            "Email",
+            "SSH Host",
+            "Strategy",
+            "Transport",
+            "Last Used"
+        ]);
 
         for (name, profile) in profiles {
-            table.add_row(row![name, profile.name, profile.email, profile.ssh_host]);
+            let label = if default == Some(name.as_str()) {
+                format!("{} (default)", name)
+            } else {
+                name.clone()
+            };
+            let last_used = state
+                .profiles
+                .get(name)
+                .map(|at| at.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|| "-".to_string());
+            table.add_row(row![
+                label,
+                profile.name,
+                profile.email,
+                profile.ssh_host,
+                profile.strategy,
+                profile.transport,
+                last_used
+            ]);
+        }
+
+        table.printstd();
+        println!();
+    }
+
+    /// Lists the keys held by ssh-agent with the profiles that use them.
+    pub fn print_agent_identities(identities: &[(AgentIdentity, Vec<String>)]) {
+        println!("{}", "🔐 ssh-agent:".cyan().bold());
+        println!("{}", "=".repeat(40).cyan());
+
+        if identities.is_empty() {
+            println!("{}", "No keys loaded.".yellow());
+            println!();
+            return;
+        }
+
+        let mut table = Table::new();
+        table.add_row(row!["Fingerprint", "Comment", "Profiles"]);
+        for (identity, profiles) in identities {
+            table.add_row(row![
+                identity.public_key.fingerprint(ssh_key::HashAlg::Sha256),
+                identity.comment,
+                profiles.join(", ")
+            ]);
+        }
+        table.printstd();
+        println!();
+    }
+
+    pub fn print_import_plan(items: &[ImportItem]) {
+        println!("\n{}", "📥 Import Preview:".cyan().bold());
+        println!("{}", "=".repeat(40).cyan());
+
+        let mut table = Table::new();
+        table.add_row(row!["Profile", "Action", "Name", "Email", "SSH Host"]);
+        for item in items {
+            let action = match item.action {
+                ImportAction::Add => item.action.to_string().green(),
+                ImportAction::Overwrite | ImportAction::Rename(_) => {
+                    item.action.to_string().yellow()
+                }
+                ImportAction::Skip | ImportAction::Unchanged => item.action.to_string().dimmed(),
+            };
+            table.add_row(row![
+                item.name,
+                action,
+                item.profile.name,
+                item.profile.email,
+                item.profile.ssh_host
+            ]);
         }
+        table.printstd();
+        println!();
+    }
 
+    /// Prints a profile the way it is written in the configuration file.
+    pub fn print_profile_entry(profile_name: &str, entry: &ProfileEntry) {
+        println!(
+            "\n{}",
+            format!("👤 Profile '{}':", profile_name).cyan().bold()
+        );
+        println!("{}", "=".repeat(40).cyan());
+
+        let value = |v: Option<String>| match (v, &entry.extends) {
+            (Some(v), _) => v.normal(),
+            (None, Some(_)) => "(inherited)".dimmed(),
+            (None, None) => "(not set)".dimmed(),
+        };
+        let mut table = Table::new();
+        if let Some(parent) = &entry.extends {
+            table.add_row(row!["Extends", parent]);
+        }
+        table.add_row(row!["Name", value(entry.name.clone())]);
+        table.add_row(row!["Email", value(entry.email.clone())]);
+        table.add_row(row!["SSH Host", value(entry.ssh_host.clone())]);
+        let strategy = match (entry.strategy, &entry.extends) {
+            (None, None) => "alias (default)".dimmed(),
+            (strategy, _) => value(strategy.map(|s| s.to_string())),
+        };
+        table.add_row(row!["Strategy", strategy]);
+        table.add_row(row![
+            "URL Patterns",
+            value(entry.url_patterns.as_ref().map(|p| p.join("\n")))
+        ]);
+        let transport = match (entry.transport, &entry.extends) {
+            (None, None) => "ssh (default)".dimmed(),
+            (transport, _) => value(transport.map(|t| t.to_string())),
+        };
+        table.add_row(row!["Transport", transport]);
+        table.add_row(row!["HTTPS User", value(entry.https_user.clone())]);
+        table.add_row(row!["HTTPS Host", value(entry.https_host.clone())]);
         table.printstd();
         println!();
     }
+
+    /// Prints the effective values of a profile and where each comes from.
+    pub fn print_resolved_profile(profile_name: &str, resolved: &ResolvedProfile) {
+        println!(
+            "\n{}",
+            format!("👤 Profile '{}' (resolved):", profile_name)
+                .cyan()
+                .bold()
+        );
+        println!("{}", "=".repeat(40).cyan());
+
+        let row_for = |value: Option<&Resolved<String>>| match value {
+            Some(r) => (r.value.normal(), r.source.normal()),
+            None => ("(not set)".red(), "-".dimmed()),
+        };
+        let mut table = Table::new();
+        table.add_row(row!["Field", "Value", "From"]);
+        for (field, value) in [
+            ("Name", resolved.name.as_ref()),
+            ("Email", resolved.email.as_ref()),
+            ("SSH Host", resolved.ssh_host.as_ref()),
+        ] {
+            let (value, source) = row_for(value);
+            table.add_row(row![field, value, source]);
+        }
+        match &resolved.strategy {
+            Some(r) => table.add_row(row!["Strategy", r.value, r.source]),
+            None => table.add_row(row!["Strategy", "alias", "(default)".dimmed()]),
+        };
+        if let Some(patterns) = &resolved.url_patterns {
+            table.add_row(row![
+                "URL Patterns",
+                patterns.value.join("\n"),
+                patterns.source
+            ]);
+        }
+        match &resolved.transport {
+            Some(r) => table.add_row(row!["Transport", r.value, r.source]),
+            None => table.add_row(row!["Transport", "ssh", "(default)".dimmed()]),
+        };
+        if let Some(user) = &resolved.https_user {
+            table.add_row(row!["HTTPS User", user.value, user.source]);
+        }
+        if let Some(host) = &resolved.https_host {
+            table.add_row(row!["HTTPS Host", host.value, host.source]);
+        }
+        table.printstd();
+
+        if resolved.chain.len() > 1 {
+            println!("Inherits: {}", resolved.chain.join(" → ").cyan());
+        }
+        println!();
+    }
 }
@@ -1,4 +1,5 @@
-use regex;
+pub mod file;
+
 use std::path::PathBuf;
 
 use crate::error::GuseError;
@@ -34,15 +35,5 @@ pub fn get_ssh_config_path() -> Result<PathBuf, GuseError> {
     Ok(home_dir.join(".ssh").join("config"))
 }
 
-pub fn backup_config_file(config_path: &PathBuf) -> Result<(), GuseError> {
-    if config_path.exists() {
-        let backup_path = config_path.with_extension("config.bak");
-        std::fs::copy(config_path, &backup_path).map_err(|e| {
-            GuseError::IoError(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("Failed to backup configuration file: {}", e),
-            ))
-        })?;
-    }
-    Ok(())
-}
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,50 @@
+use crate::utils::file::{atomic_write, FileLock};
+use std::fs;
+
+#[test]
+fn test_atomic_write_replaces_contents() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("profiles.toml");
+    fs::write(&path, "old contents that are longer").unwrap();
+
+    atomic_write(&path, "new").unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+
+    // No temp files are left behind.
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_atomic_write_keeps_symlink_and_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    let target = dir.path().join("dotfiles-config");
+    let link = dir.path().join("config");
+    fs::write(&target, "Host old\n").unwrap();
+    fs::set_permissions(&target, fs::Permissions::from_mode(0o600)).unwrap();
+    std::os::unix::fs::symlink(&target, &link).unwrap();
+
+    atomic_write(&link, "Host new\n").unwrap();
+    assert!(fs::symlink_metadata(&link)
+        .unwrap()
+        .file_type()
+        .is_symlink());
+    assert_eq!(fs::read_to_string(&target).unwrap(), "Host new\n");
+    assert_eq!(
+        fs::metadata(&target).unwrap().permissions().mode() & 0o777,
+        0o600
+    );
+}
+
+#[test]
+fn test_file_lock_is_exclusive() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("profiles.toml");
+
+    let lock = FileLock::acquire(&path).unwrap();
+    assert!(FileLock::try_acquire(&path).unwrap().is_none());
+    drop(lock);
+    assert!(FileLock::try_acquire(&path).unwrap().is_some());
+}